@@ -0,0 +1,282 @@
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::date::Date;
+use crate::{api, echapper_xml, recuperer};
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une actualité publiée par la fédération
+pub struct Actualite {
+    /// date de publication
+    pub date: Date,
+    /// titre de l'actualité
+    pub titre: String,
+    /// résumé de l'actualité
+    pub description: String,
+    /// lien vers l'article complet
+    pub url: String,
+    /// lien vers la photo illustrant l'actualité, s'il y en a une
+    pub photo: Option<String>,
+    /// catégorie de l'actualité, si elle est connue
+    pub categorie: Option<String>,
+}
+
+impl Actualite {
+    /// créé les actualités à partir du document XML retourné par l'API
+    pub fn from_xml(xml: &str) -> Result<Vec<Actualite>, quick_xml::DeError> {
+        let doc: DocumentActualites = quick_xml::de::from_str(xml)?;
+        Ok(doc.news.into_iter().map(Actualite::from).collect())
+    }
+
+    /// retourne les dernières actualités de la fédération, de la plus récente à la plus ancienne
+    pub async fn api_actualites() -> Vec<Actualite> {
+        let api = api();
+        let request_url = format!("{api}/proxy/xml_new_actu.php");
+        let Some(xml) = recuperer(&request_url).await else {
+            return vec![];
+        };
+        let mut actualites = Actualite::from_xml(&xml).unwrap_or_else(|e| {
+            log::error!("Erreur lors de la désérialisation des actualités : {e}");
+            vec![]
+        });
+        actualites.sort_by(|a, b| b.date.cmp(&a.date));
+        actualites
+    }
+}
+
+/// retourne les actualités publiées entre les deux dates incluses, si elles sont données
+pub fn filtrer<'a>(
+    actualites: &'a [Actualite],
+    depuis: Option<&Date>,
+    jusqu_a: Option<&Date>,
+) -> Vec<&'a Actualite> {
+    actualites
+        .iter()
+        .filter(|a| depuis.is_none_or(|d| a.date >= *d))
+        .filter(|a| jusqu_a.is_none_or(|d| a.date <= *d))
+        .collect()
+}
+
+/// exporte les actualités au format RSS 2.0
+///
+/// Les actualités sans lien sont ignorées, faute de pouvoir les identifier.
+pub fn rss(actualites: &[Actualite], titre: &str, lien: &str) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\">\n<channel>\n");
+    let _ = writeln!(xml, "<title>{}</title>", echapper_xml(titre));
+    let _ = writeln!(xml, "<link>{}</link>", echapper_xml(lien));
+    let _ = writeln!(xml, "<description>{}</description>", echapper_xml(titre));
+    xml.push_str("<language>fr</language>\n");
+    for a in publiables(actualites) {
+        xml.push_str("<item>\n");
+        let _ = writeln!(xml, "<title>{}</title>", echapper_xml(&a.titre));
+        let _ = writeln!(xml, "<link>{}</link>", echapper_xml(&a.url));
+        let _ = writeln!(xml, "<guid>{}</guid>", echapper_xml(&a.url));
+        let _ = writeln!(
+            xml,
+            "<description>{}</description>",
+            echapper_xml(&a.description)
+        );
+        if let Some(jour) = jour(&a.date) {
+            let _ = writeln!(
+                xml,
+                "<pubDate>{}</pubDate>",
+                jour.format("%a, %d %b %Y 00:00:00 +0000")
+            );
+        }
+        if let Some(categorie) = &a.categorie {
+            let _ = writeln!(xml, "<category>{}</category>", echapper_xml(categorie));
+        }
+        if let Some(photo) = &a.photo {
+            let _ = writeln!(
+                xml,
+                "<enclosure url=\"{}\" length=\"0\" type=\"{}\"/>",
+                echapper_xml(photo),
+                type_image(photo)
+            );
+        }
+        xml.push_str("</item>\n");
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+/// exporte les actualités au format Atom (RFC 4287)
+///
+/// Le flux est daté de l'actualité la plus récente, ou de l'instant présent s'il est vide ;
+/// une actualité dont la date est invalide reçoit la date du flux.
+/// Les actualités sans lien sont ignorées, faute de pouvoir les identifier.
+pub fn atom(actualites: &[Actualite], titre: &str, lien: &str) -> String {
+    let mis_a_jour = publiables(actualites)
+        .filter_map(|a| jour(&a.date))
+        .max()
+        .map(date_atom)
+        .unwrap_or_else(|| Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string());
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\" xml:lang=\"fr\">\n");
+    let _ = writeln!(xml, "<title>{}</title>", echapper_xml(titre));
+    let _ = writeln!(xml, "<id>{}</id>", echapper_xml(lien));
+    let _ = writeln!(xml, "<link href=\"{}\"/>", echapper_xml(lien));
+    let _ = writeln!(xml, "<updated>{mis_a_jour}</updated>");
+    let _ = writeln!(xml, "<author><name>{}</name></author>", echapper_xml(titre));
+    for a in publiables(actualites) {
+        xml.push_str("<entry>\n");
+        let _ = writeln!(xml, "<title>{}</title>", echapper_xml(&a.titre));
+        let _ = writeln!(xml, "<id>{}</id>", echapper_xml(&a.url));
+        let _ = writeln!(xml, "<link href=\"{}\"/>", echapper_xml(&a.url));
+        let date = jour(&a.date).map_or_else(|| mis_a_jour.clone(), date_atom);
+        let _ = writeln!(xml, "<updated>{date}</updated>");
+        let _ = writeln!(xml, "<summary>{}</summary>", echapper_xml(&a.description));
+        if let Some(categorie) = &a.categorie {
+            let _ = writeln!(xml, "<category term=\"{}\"/>", echapper_xml(categorie));
+        }
+        if let Some(photo) = &a.photo {
+            let _ = writeln!(
+                xml,
+                "<link rel=\"enclosure\" href=\"{}\" type=\"{}\"/>",
+                echapper_xml(photo),
+                type_image(photo)
+            );
+        }
+        xml.push_str("</entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn jour(date: &Date) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(date.annee.into(), date.mois.into(), date.jour.into())
+}
+
+// une date au format RFC 3339, à minuit UTC
+fn date_atom(jour: NaiveDate) -> String {
+    jour.format("%Y-%m-%dT00:00:00Z").to_string()
+}
+
+// le type MIME d'une image, déduit de son extension
+fn type_image(url: &str) -> &'static str {
+    let url = url.to_lowercase();
+    if url.ends_with(".png") {
+        "image/png"
+    } else if url.ends_with(".gif") {
+        "image/gif"
+    } else if url.ends_with(".webp") {
+        "image/webp"
+    } else {
+        "image/jpeg"
+    }
+}
+
+// les actualités pouvant figurer dans un flux : un élément sans lien n'aurait pas d'identifiant
+fn publiables(actualites: &[Actualite]) -> impl Iterator<Item = &Actualite> {
+    actualites.iter().filter(|a| !a.url.is_empty())
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentActualites {
+    #[serde(default)]
+    news: Vec<ActualiteApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// une actualité telle que retournée par l'API
+struct ActualiteApi {
+    date: Date,
+    titre: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    photo: Option<String>,
+    #[serde(default)]
+    categorie: Option<String>,
+}
+
+impl From<ActualiteApi> for Actualite {
+    fn from(a: ActualiteApi) -> Self {
+        Actualite {
+            date: a.date,
+            titre: a.titre,
+            description: a.description,
+            url: a.url,
+            photo: a.photo.filter(|p| !p.is_empty()),
+            categorie: a.categorie.filter(|c| !c.is_empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACTUALITES: &str = r#"<?xml version="1.0" encoding="ISO-8859-1"?>
+<liste>
+    <news><date>2025-03-10</date><titre>Championnats de France</titre><description>Les résultats &amp; les podiums</description><url>https://www.fftt.com/actu/1</url><photo>https://www.fftt.com/photo/1.png</photo><categorie>Compétitions</categorie></news>
+    <news><date>2025-02-20</date><titre>Assemblée générale</titre><description>Compte rendu</description><url>https://www.fftt.com/actu/2</url><photo></photo><categorie></categorie></news>
+</liste>"#;
+
+    #[test]
+    fn test_actualites() {
+        let actualites = Actualite::from_xml(ACTUALITES).unwrap();
+        assert_eq!(actualites.len(), 2);
+        assert_eq!(actualites[0].description, "Les résultats & les podiums");
+        assert_eq!(actualites[1].photo, None);
+        assert_eq!(actualites[1].categorie, None);
+
+        let mars: Date = "2025-03-01".parse().unwrap();
+        assert_eq!(filtrer(&actualites, Some(&mars), None).len(), 1);
+        assert_eq!(
+            filtrer(&actualites, None, Some(&mars))[0].titre,
+            "Assemblée générale"
+        );
+        assert_eq!(filtrer(&actualites, None, None).len(), 2);
+    }
+
+    #[test]
+    fn test_flux() {
+        let actualites = Actualite::from_xml(ACTUALITES).unwrap();
+        let rss = rss(&actualites, "Actualités FFTT", "https://www.fftt.com");
+        assert!(rss.contains("<rss version=\"2.0\">"));
+        assert_eq!(rss.matches("<item>").count(), 2);
+        assert!(rss.contains("<description>Les résultats &amp; les podiums</description>"));
+        assert!(rss.contains("<pubDate>Mon, 10 Mar 2025 00:00:00 +0000</pubDate>"));
+        assert!(rss.contains("type=\"image/png\""));
+
+        let atom = atom(&actualites, "Actualités FFTT", "https://www.fftt.com");
+        assert!(atom.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\""));
+        assert!(atom.contains("<updated>2025-03-10T00:00:00Z</updated>\n<author>"));
+        assert_eq!(atom.matches("<entry>").count(), 2);
+        assert!(atom.contains("<category term=\"Compétitions\"/>"));
+    }
+
+    #[test]
+    fn test_flux_incomplet() {
+        let actualite = |date: Date, url: &str| Actualite {
+            date,
+            titre: "Titre".to_string(),
+            description: String::new(),
+            url: url.to_string(),
+            photo: None,
+            categorie: None,
+        };
+        let actualites = [
+            actualite(Date::new(2025, 3, 10), ""),
+            actualite(Date::new(2025, 2, 30), "https://www.fftt.com/actu/3"),
+            actualite(Date::new(2025, 2, 20), "https://www.fftt.com/actu/2"),
+        ];
+        let rss = rss(&actualites, "Actualités FFTT", "https://www.fftt.com");
+        assert_eq!(rss.matches("<item>").count(), 2);
+        assert!(!rss.contains("<guid></guid>"));
+
+        let atom = atom(&actualites, "Actualités FFTT", "https://www.fftt.com");
+        assert_eq!(atom.matches("<entry>").count(), 2);
+        assert_eq!(
+            atom.matches("<updated>2025-02-20T00:00:00Z</updated>")
+                .count(),
+            3
+        );
+    }
+}
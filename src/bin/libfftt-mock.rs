@@ -0,0 +1,34 @@
+//! Serveur simulant l'API FFTT à partir d'un jeu de données au format JSON
+//!
+//! Le chemin du jeu de données est passé en argument ; sans argument, un jeu de données
+//! fictif est généré. L'option `--generer <chemin>` enregistre ce jeu de données sans lancer
+//! le serveur. L'adresse d'écoute est lue dans la variable d'environnement `LIBFFTT_ADRESSE`
+//! (127.0.0.1:8081 par défaut).
+use std::env;
+
+use libfftt::generateur::{self, Parametres};
+use libfftt::simulateur::{Donnees, Simulateur};
+
+#[tokio::main]
+async fn main() {
+    let arguments: Vec<String> = env::args().skip(1).collect();
+    let donnees = match arguments.as_slice() {
+        [option, chemin] if option == "--generer" => {
+            generateur::generer(&Parametres::default())
+                .enregistrer(chemin)
+                .expect("Impossible d'enregistrer le jeu de données");
+            return;
+        }
+        [chemin] => Donnees::charger(chemin).expect("Impossible de charger le jeu de données"),
+        _ => generateur::generer(&Parametres::default()),
+    };
+    let adresse = env::var("LIBFFTT_ADRESSE").unwrap_or_else(|_| "127.0.0.1:8081".to_string());
+    let adresse = Simulateur::new(donnees)
+        .lancer(&adresse)
+        .await
+        .expect("Impossible de lancer le simulateur");
+    println!("Simulateur à l'écoute sur http://{adresse}");
+    tokio::signal::ctrl_c()
+        .await
+        .expect("Impossible d'attendre l'arrêt");
+}
@@ -0,0 +1,20 @@
+//! Passerelle HTTP/JSON exposant la bibliothèque
+//!
+//! L'adresse d'écoute est lue dans la variable d'environnement `LIBFFTT_ADRESSE`
+//! (127.0.0.1:8080 par défaut), la durée du cache en secondes dans `LIBFFTT_CACHE` (300 par défaut).
+use std::env;
+use std::time::Duration;
+
+use libfftt::serveur;
+
+#[tokio::main]
+async fn main() {
+    let adresse = env::var("LIBFFTT_ADRESSE").unwrap_or_else(|_| "127.0.0.1:8080".to_string());
+    let duree = env::var("LIBFFTT_CACHE")
+        .ok()
+        .and_then(|d| d.parse().ok())
+        .unwrap_or(300);
+    serveur::servir(&adresse, Duration::from_secs(duree))
+        .await
+        .expect("Impossible de lancer la passerelle");
+}
@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::composition::Composition;
+use crate::equipe::Equipe;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// les règles de brûlage d'une épreuve
+pub struct Regles {
+    /// nombre de rencontres disputées dans une équipe (ou cumulées dans des équipes de numéro inférieur)
+    /// à partir duquel un joueur ne peut plus jouer dans une équipe de numéro supérieur
+    pub seuil: u8,
+    /// nombre de joueurs composant une équipe lors d'une rencontre
+    pub joueurs_par_rencontre: u8,
+    /// nombre maximal de joueurs d'une composition ayant déjà joué dans une équipe de numéro inférieur
+    pub max_joueurs_superieurs: Option<u8>,
+}
+
+impl Default for Regles {
+    /// les règles du championnat de France par équipes
+    fn default() -> Self {
+        Regles {
+            seuil: 2,
+            joueurs_par_rencontre: 4,
+            max_joueurs_superieurs: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+/// les règles de brûlage applicables à chaque épreuve
+pub struct ReglesEpreuves {
+    /// règles appliquées aux épreuves non configurées
+    pub defaut: Regles,
+    /// règles spécifiques, indexées par libellé d'épreuve
+    pub epreuves: HashMap<String, Regles>,
+}
+
+impl ReglesEpreuves {
+    /// retourne les règles applicables à l'épreuve donnée
+    pub fn regles(&self, epreuve: &str) -> &Regles {
+        self.epreuves.get(epreuve).unwrap_or(&self.defaut)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// l'état de brûlage d'un joueur
+pub struct EtatBrulage {
+    /// nom et prénom du joueur
+    pub nom: String,
+    /// numéro de licence, s'il est connu
+    pub licence: Option<String>,
+    /// nombre de rencontres disputées par numéro d'équipe
+    pub participations: BTreeMap<u8, u8>,
+    /// numéro de l'équipe dans laquelle le joueur est brûlé :
+    /// il ne peut plus jouer dans une équipe de numéro supérieur
+    pub brule: Option<u8>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une infraction aux règles détectée dans une composition
+pub enum Infraction {
+    /// le joueur est brûlé dans une équipe de numéro inférieur
+    JoueurBrule {
+        /// joueur concerné
+        joueur: String,
+        /// équipe dans laquelle le joueur est brûlé
+        equipe: u8,
+    },
+    /// la composition ne compte pas le bon nombre de joueurs
+    NombreDeJoueurs {
+        /// nombre de joueurs de la composition
+        nombre: u8,
+        /// nombre de joueurs attendu
+        attendu: u8,
+    },
+    /// la composition compte trop de joueurs ayant joué dans une équipe de numéro inférieur
+    TropDeJoueursSuperieurs {
+        /// les joueurs concernés
+        joueurs: Vec<String>,
+        /// nombre maximal autorisé
+        max: u8,
+    },
+}
+
+impl EtatBrulage {
+    /// identifiant du joueur : sa licence si connue, son nom sinon
+    pub fn cle(&self) -> &str {
+        self.licence.as_deref().unwrap_or(&self.nom)
+    }
+
+    /// indique si le joueur peut jouer dans l'équipe donnée
+    pub fn peut_jouer(&self, equipe: u8) -> bool {
+        self.brule.is_none_or(|b| equipe <= b)
+    }
+
+    /// indique si le joueur a déjà joué dans une équipe de numéro inférieur à celle donnée
+    pub fn a_joue_au_dessus(&self, equipe: u8) -> bool {
+        self.participations.range(..equipe).any(|(_, n)| *n > 0)
+    }
+}
+
+/// calcule l'état de brûlage de chaque joueur à partir des compositions des équipes d'une épreuve
+/// chaque composition est associée au numéro de l'équipe concernée
+pub fn etats(compositions: &[(u8, Composition)], regles: &Regles) -> Vec<EtatBrulage> {
+    let mut etats: Vec<EtatBrulage> = vec![];
+    for (numero, c) in compositions {
+        for p in &c.joueurs {
+            let existant = etats
+                .iter_mut()
+                .find(|e| match (&e.licence, &p.joueur.licence) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => e.nom == p.joueur.nom,
+                });
+            let etat = match existant {
+                Some(e) => e,
+                None => {
+                    etats.push(EtatBrulage {
+                        nom: p.joueur.nom.clone(),
+                        licence: p.joueur.licence.clone(),
+                        participations: BTreeMap::new(),
+                        brule: None,
+                    });
+                    etats.last_mut().expect("L'état vient d'être ajouté")
+                }
+            };
+            *etat.participations.entry(*numero).or_default() += 1;
+        }
+    }
+    for e in &mut etats {
+        let mut cumul = 0;
+        e.brule = e.participations.iter().find_map(|(numero, n)| {
+            cumul += n;
+            (cumul >= regles.seuil).then_some(*numero)
+        });
+    }
+    etats
+}
+
+/// valide une composition proposée pour l'équipe donnée
+/// les joueurs sont identifiés par leur licence, ou à défaut leur nom
+pub fn valider(
+    equipe: u8,
+    joueurs: &[&str],
+    etats: &[EtatBrulage],
+    regles: &Regles,
+) -> Vec<Infraction> {
+    let mut infractions = vec![];
+    let nombre = u8::try_from(joueurs.len()).unwrap_or(u8::MAX);
+    if nombre != regles.joueurs_par_rencontre {
+        infractions.push(Infraction::NombreDeJoueurs {
+            nombre,
+            attendu: regles.joueurs_par_rencontre,
+        });
+    }
+    let mut superieurs = vec![];
+    for joueur in joueurs {
+        let Some(etat) = etats
+            .iter()
+            .find(|e| e.cle() == *joueur || e.nom == *joueur)
+        else {
+            continue;
+        };
+        if let Some(brule) = etat.brule.filter(|_| !etat.peut_jouer(equipe)) {
+            infractions.push(Infraction::JoueurBrule {
+                joueur: joueur.to_string(),
+                equipe: brule,
+            });
+        }
+        if etat.a_joue_au_dessus(equipe) {
+            superieurs.push(joueur.to_string());
+        }
+    }
+    if let Some(max) = regles.max_joueurs_superieurs {
+        if superieurs.len() > usize::from(max) {
+            infractions.push(Infraction::TropDeJoueursSuperieurs {
+                joueurs: superieurs,
+                max,
+            });
+        }
+    }
+    infractions
+}
+
+/// calcule l'état de brûlage des joueurs à partir des rencontres jouées par les équipes données,
+/// qui doivent être engagées dans la même épreuve
+pub async fn etats_equipes(equipes: &[Equipe], regles: &Regles) -> Vec<EtatBrulage> {
+    let mut compositions = vec![];
+    for e in equipes {
+        let Some(numero) = e.numero() else {
+            log::warn!("Numéro de l'équipe {} introuvable", e.nom);
+            continue;
+        };
+        for c in e.compositions().await {
+            compositions.push((numero, c));
+        }
+    }
+    etats(&compositions, regles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::composition::Participation;
+    use crate::date::Date;
+    use crate::feuille::JoueurFeuille;
+    use crate::tour::Cote;
+
+    fn composition(tour: u8, joueurs: &[&str]) -> Composition {
+        Composition {
+            tour,
+            date: Date::new(2024, 9, 28),
+            cote: Cote::Domicile,
+            adversaire: "ADVERSAIRE".to_string(),
+            joueurs: joueurs
+                .iter()
+                .map(|j| Participation {
+                    joueur: JoueurFeuille {
+                        nom: j.to_string(),
+                        licence: None,
+                        points: None,
+                    },
+                    victoires: 0,
+                    defaites: 0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_etats() {
+        let compositions = vec![
+            (1, composition(1, &["A", "B", "C", "D"])),
+            (2, composition(1, &["E", "F", "G", "H"])),
+            (1, composition(2, &["A", "B", "C", "E"])),
+            (2, composition(2, &["D", "F", "G", "H"])),
+        ];
+        let etats = etats(&compositions, &Regles::default());
+        let etat = |nom: &str| etats.iter().find(|e| e.nom == nom).unwrap();
+        assert_eq!(etat("A").brule, Some(1));
+        assert!(!etat("A").peut_jouer(2));
+        // une rencontre en équipe 1 et une en équipe 2 : brûlé en équipe 2
+        assert_eq!(etat("D").brule, Some(2));
+        assert_eq!(etat("E").brule, Some(2));
+        assert!(etat("D").peut_jouer(2));
+        assert!(!etat("D").peut_jouer(3));
+    }
+
+    #[test]
+    fn test_valider() {
+        let compositions = vec![
+            (1, composition(1, &["A", "B", "C", "D"])),
+            (1, composition(2, &["A", "B", "C", "E"])),
+        ];
+        let regles = Regles {
+            max_joueurs_superieurs: Some(1),
+            ..Default::default()
+        };
+        let etats = etats(&compositions, &regles);
+        let infractions = valider(2, &["A", "D", "E", "X"], &etats, &regles);
+        assert_eq!(
+            infractions,
+            vec![
+                Infraction::JoueurBrule {
+                    joueur: "A".to_string(),
+                    equipe: 1
+                },
+                Infraction::TropDeJoueursSuperieurs {
+                    joueurs: vec!["A".to_string(), "D".to_string(), "E".to_string()],
+                    max: 1
+                },
+            ]
+        );
+        assert!(valider(1, &["A", "B", "C", "D"], &etats, &regles).is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+
+use crate::club::Club;
+use crate::date::Date;
+use crate::equipe::Equipe;
+use crate::poule::Poule;
+use crate::tour::{Cote, Rencontre, Tour};
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une rencontre du calendrier d'un club
+pub struct Evenement {
+    /// nom de l'équipe du club
+    pub equipe: String,
+    /// numéro de l'équipe au sein du club
+    pub numero_equipe: Option<u8>,
+    /// libellé de l'épreuve
+    pub epreuve: String,
+    /// nom de la division
+    pub division: String,
+    /// poule de l'équipe
+    pub poule: Poule,
+    /// le tour de la rencontre
+    pub tour: u8,
+    /// la date effective de la rencontre
+    pub date: Date,
+    /// indique si l'équipe reçoit ou se déplace
+    pub cote: Cote,
+    /// nom de l'équipe adverse
+    pub adversaire: String,
+    /// le club recevant la rencontre, dont la salle accueille la rencontre
+    pub lieu: Option<Club>,
+    /// la rencontre
+    pub rencontre: Rencontre,
+}
+
+/// retourne les rencontres de l'équipe parmi les tours de sa poule, sans lieu
+pub fn evenements(equipe: &Equipe, tours: &[(u8, Tour)]) -> Vec<Evenement> {
+    let mut evenements = vec![];
+    for (_, tour) in tours {
+        for r in &tour.rencontres.rencontres {
+            let (cote, adversaire) = if equipe.est_nommee(&r.a) {
+                (Cote::Domicile, &r.b)
+            } else if equipe.est_nommee(&r.b) {
+                (Cote::Exterieur, &r.a)
+            } else {
+                continue;
+            };
+            evenements.push(Evenement {
+                equipe: equipe.nom_rencontre().to_string(),
+                numero_equipe: equipe.numero(),
+                epreuve: equipe._epreuve.clone(),
+                division: equipe._division.clone(),
+                poule: equipe.poule.clone(),
+                tour: r.tour,
+                date: r.datereelle.clone().unwrap_or_else(|| r.date.clone()),
+                cote,
+                adversaire: adversaire.clone(),
+                lieu: None,
+                rencontre: r.clone(),
+            });
+        }
+    }
+    evenements
+}
+
+/// trie les évènements par date, puis par numéro d'équipe
+pub fn trier(evenements: &mut [Evenement]) {
+    evenements.sort_by(|a, b| {
+        a.date
+            .cmp(&b.date)
+            .then(a.numero_equipe.cmp(&b.numero_equipe))
+            .then(a.equipe.cmp(&b.equipe))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equipe::EquipeApi;
+    use crate::tour::Rencontres;
+
+    const RENCONTRES: &str = r#"<liste>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>MONTPELLIER TT 3</equa><equb>NIMES ASPTT 2</equb><dateprevue>28/09/2024</dateprevue></tour>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>SETE TT 1</equa><equb>ALES TT 1</equb><dateprevue>28/09/2024</dateprevue></tour>
+    <tour><libelle>Poule 3 - tour n°2 du 12/10/2024</libelle><equa>ALES TT 1</equa><equb>MONTPELLIER TT 3</equb><dateprevue>12/10/2024</dateprevue><datereelle>09/11/2024</datereelle></tour>
+</liste>"#;
+
+    #[test]
+    fn test_evenements() {
+        let equipe: Equipe = serde_json::from_str::<EquipeApi>(
+            r#"{
+                "idequipe": "42",
+                "libequipe": "MONTPELLIER TT 3 - Phase 1",
+                "libepr": "FED_Championnat de France par Equipes Masculin",
+                "libdivision": "Régionale 1",
+                "liendivision": "cx_poule=1234&D1=5678&organisme_pere=11"
+            }"#,
+        )
+        .unwrap()
+        .into();
+        let rencontres = Rencontres::from_xml(RENCONTRES).unwrap();
+        let tours = vec![
+            (
+                1,
+                Tour {
+                    numero: 1,
+                    date: Date::new(2024, 9, 28),
+                    rencontres: Rencontres {
+                        rencontres: rencontres.rencontres[..2].to_vec(),
+                    },
+                },
+            ),
+            (
+                2,
+                Tour {
+                    numero: 2,
+                    date: Date::new(2024, 10, 12),
+                    rencontres: Rencontres {
+                        rencontres: rencontres.rencontres[2..].to_vec(),
+                    },
+                },
+            ),
+        ];
+        let mut evenements = evenements(&equipe, &tours);
+        trier(&mut evenements);
+        assert_eq!(evenements.len(), 2);
+        assert_eq!(evenements[0].cote, Cote::Domicile);
+        assert_eq!(evenements[0].adversaire, "NIMES ASPTT 2");
+        assert_eq!(evenements[0].numero_equipe, Some(3));
+        assert_eq!(evenements[1].cote, Cote::Exterieur);
+        assert_eq!(evenements[1].date, Date::new(2024, 11, 9));
+        assert_eq!(evenements[1].poule.numero, "1234");
+        assert_eq!(evenements[1].poule.division, "5678");
+
+        // un lien de division sans poule est une erreur de désérialisation
+        assert!(serde_json::from_str::<EquipeApi>(
+            r#"{"idequipe": "42", "libequipe": "MONTPELLIER TT 3", "libepr": "",
+                "libdivision": "", "liendivision": "D1=5678"}"#
+        )
+        .is_err());
+    }
+}
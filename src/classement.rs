@@ -0,0 +1,400 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::feuille::FeuilleDeMatch;
+use crate::poule::Classement;
+use crate::tour::{Bareme, Cote, Issue, Rencontre};
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+/// le classement d'une équipe calculé localement à partir des rencontres de la poule
+pub struct LigneClassement {
+    /// rang de l'équipe dans la poule
+    pub rang: u8,
+    /// nom de l'équipe
+    pub equipe: String,
+    /// nombre de rencontres jouées
+    pub joues: u8,
+    /// nombre de points au classement
+    pub points: u16,
+    /// nombre de victoires
+    pub victoires: u8,
+    /// nombre de nuls
+    pub nuls: u8,
+    /// nombre de défaites
+    pub defaites: u8,
+    /// nombre de forfaits
+    pub forfaits: u8,
+    /// nombre de parties gagnées
+    pub parties_gagnees: u16,
+    /// nombre de parties perdues
+    pub parties_perdues: u16,
+    /// nombre de manches gagnées, connu uniquement à partir des feuilles de match
+    pub manches_gagnees: u16,
+    /// nombre de manches perdues, connu uniquement à partir des feuilles de match
+    pub manches_perdues: u16,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une différence entre le classement calculé et le classement officiel
+pub enum Difference {
+    /// l'équipe est absente du classement calculé
+    AbsenteLocalement,
+    /// l'équipe est absente du classement officiel
+    AbsenteOfficiellement,
+    /// le rang diffère (calculé, officiel)
+    Rang(u8, u8),
+    /// le nombre de points diffère (calculé, officiel)
+    Points(u16, u16),
+    /// le nombre de rencontres jouées diffère (calculé, officiel), signe d'un résultat en attente
+    Joues(u8, u8),
+    /// le nombre de parties gagnées et perdues diffère (calculé, officiel)
+    Parties((u16, u16), (u16, u16)),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// l'ensemble des différences constatées pour une équipe
+pub struct Ecart {
+    /// nom de l'équipe
+    pub equipe: String,
+    /// les différences constatées
+    pub differences: Vec<Difference>,
+}
+
+/// calcule le classement de la poule à partir de ses rencontres
+pub fn calculer(rencontres: &[Rencontre], bareme: &Bareme) -> Vec<LigneClassement> {
+    calculer_avec_feuilles(rencontres, &[], bareme)
+}
+
+/// calcule le classement de la poule à partir de ses rencontres,
+/// en utilisant les feuilles de match pour départager au quotient des manches
+pub fn calculer_avec_feuilles(
+    rencontres: &[Rencontre],
+    feuilles: &[FeuilleDeMatch],
+    bareme: &Bareme,
+) -> Vec<LigneClassement> {
+    let mut lignes = bilan(rencontres, feuilles, bareme, None);
+    lignes.sort_by(|a, b| b.points.cmp(&a.points).then(a.equipe.cmp(&b.equipe)));
+
+    // départage des équipes à égalité de points
+    let mut debut = 0;
+    while debut < lignes.len() {
+        let fin = debut
+            + lignes[debut..]
+                .iter()
+                .take_while(|l| l.points == lignes[debut].points)
+                .count();
+        departager_groupe(&mut lignes[debut..fin], rencontres, feuilles, bareme);
+        debut = fin;
+    }
+
+    for (i, l) in lignes.iter_mut().enumerate() {
+        l.rang = u8::try_from(i + 1).expect("Une poule compte moins de 255 équipes");
+    }
+    lignes
+}
+
+/// compare le classement calculé au classement officiel
+/// seules les équipes présentant au moins une différence sont retournées
+pub fn ecarts(calcule: &[LigneClassement], officiel: &[Classement]) -> Vec<Ecart> {
+    let mut ecarts = vec![];
+    for l in calcule {
+        let mut differences = vec![];
+        match officiel.iter().find(|c| c.equipe == l.equipe) {
+            None => differences.push(Difference::AbsenteOfficiellement),
+            Some(c) => {
+                if l.rang != c.classement {
+                    differences.push(Difference::Rang(l.rang, c.classement));
+                }
+                if l.points != u16::from(c.points) {
+                    differences.push(Difference::Points(l.points, u16::from(c.points)));
+                }
+                if l.joues != c.joues {
+                    differences.push(Difference::Joues(l.joues, c.joues));
+                }
+                let parties_officielles =
+                    (u16::from(c.parties_gagnees), u16::from(c.parties_perdues));
+                if (l.parties_gagnees, l.parties_perdues) != parties_officielles {
+                    differences.push(Difference::Parties(
+                        (l.parties_gagnees, l.parties_perdues),
+                        parties_officielles,
+                    ));
+                }
+            }
+        }
+        if !differences.is_empty() {
+            ecarts.push(Ecart {
+                equipe: l.equipe.clone(),
+                differences,
+            });
+        }
+    }
+    for c in officiel {
+        if !calcule.iter().any(|l| l.equipe == c.equipe) {
+            ecarts.push(Ecart {
+                equipe: c.equipe.clone(),
+                differences: vec![Difference::AbsenteLocalement],
+            });
+        }
+    }
+    ecarts
+}
+
+// établit le bilan de chaque équipe, éventuellement restreint aux rencontres entre les équipes données
+fn bilan(
+    rencontres: &[Rencontre],
+    feuilles: &[FeuilleDeMatch],
+    bareme: &Bareme,
+    entre: Option<&[&str]>,
+) -> Vec<LigneClassement> {
+    fn ligne(lignes: &mut Vec<LigneClassement>, equipe: &str) -> usize {
+        match lignes.iter().position(|l| l.equipe == equipe) {
+            Some(i) => i,
+            None => {
+                lignes.push(LigneClassement {
+                    equipe: equipe.to_string(),
+                    ..Default::default()
+                });
+                lignes.len() - 1
+            }
+        }
+    }
+
+    let mut lignes: Vec<LigneClassement> = vec![];
+    // les équipes à départager figurent au bilan même si elles ne se sont pas encore rencontrées
+    for equipe in entre.unwrap_or_default() {
+        ligne(&mut lignes, equipe);
+    }
+    for r in rencontres {
+        if let Some(equipes) = entre {
+            if !equipes.contains(&r.a.as_str()) || !equipes.contains(&r.b.as_str()) {
+                continue;
+            }
+        }
+        let ia = ligne(&mut lignes, &r.a);
+        let ib = ligne(&mut lignes, &r.b);
+        let issue = r.issue();
+        if matches!(issue, Issue::NonJouee | Issue::Reportee) {
+            continue;
+        }
+        let manches = feuilles
+            .iter()
+            .find(|f| f.equipea == r.a && f.equipeb == r.b)
+            .map(FeuilleDeMatch::manches);
+        for (i, cote) in [(ia, Cote::Domicile), (ib, Cote::Exterieur)] {
+            let equipe = if cote == Cote::Domicile { &r.a } else { &r.b };
+            let l = &mut lignes[i];
+            l.joues += 1;
+            l.points += u16::from(r.points(equipe, bareme).unwrap_or_default());
+            match (issue, cote) {
+                (Issue::VictoireA, Cote::Domicile) | (Issue::VictoireB, Cote::Exterieur) => {
+                    l.victoires += 1
+                }
+                (Issue::ForfaitA, Cote::Exterieur) | (Issue::ForfaitB, Cote::Domicile) => {
+                    l.victoires += 1
+                }
+                (Issue::VictoireA, Cote::Exterieur) | (Issue::VictoireB, Cote::Domicile) => {
+                    l.defaites += 1
+                }
+                (Issue::ForfaitA, Cote::Domicile) | (Issue::ForfaitB, Cote::Exterieur) => {
+                    l.forfaits += 1
+                }
+                (Issue::Nul, _) => l.nuls += 1,
+                (Issue::NonJouee | Issue::Reportee, _) => {}
+            }
+            if let Some((pour, contre)) = r.score(equipe) {
+                l.parties_gagnees += u16::from(pour);
+                l.parties_perdues += u16::from(contre);
+            }
+            if let Some((a, b)) = manches {
+                let (pour, contre) = if cote == Cote::Domicile {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                l.manches_gagnees += pour;
+                l.manches_perdues += contre;
+            }
+        }
+    }
+    lignes
+}
+
+// les critères de départage successifs : points, quotient des parties, puis quotient des manches
+const CRITERES: [fn(&LigneClassement, &LigneClassement) -> Ordering; 3] = [
+    |a, b| b.points.cmp(&a.points),
+    |a, b| {
+        quotient(
+            (b.parties_gagnees, b.parties_perdues),
+            (a.parties_gagnees, a.parties_perdues),
+        )
+    },
+    |a, b| {
+        quotient(
+            (b.manches_gagnees, b.manches_perdues),
+            (a.manches_gagnees, a.manches_perdues),
+        )
+    },
+];
+
+// départage deux équipes en appliquant les critères dans l'ordre
+fn departager(a: &LigneClassement, b: &LigneClassement) -> Ordering {
+    CRITERES.iter().fold(Ordering::Equal, |ordre, critere| {
+        ordre.then_with(|| critere(a, b))
+    })
+}
+
+// classe des équipes à égalité de points d'après les seules rencontres qui les ont opposées
+// dès qu'un critère sépare une partie des équipes, le départage reprend depuis le premier critère
+// pour chaque sous-groupe encore à égalité ; si aucun critère ne les sépare, le bilan général
+// puis le nom de l'équipe les départagent
+fn departager_groupe(
+    groupe: &mut [LigneClassement],
+    rencontres: &[Rencontre],
+    feuilles: &[FeuilleDeMatch],
+    bareme: &Bareme,
+) {
+    if groupe.len() < 2 {
+        return;
+    }
+    let equipes: Vec<&str> = groupe.iter().map(|l| l.equipe.as_str()).collect();
+    let particulier: HashMap<String, LigneClassement> =
+        bilan(rencontres, feuilles, bareme, Some(&equipes))
+            .into_iter()
+            .map(|l| (l.equipe.clone(), l))
+            .collect();
+    for critere in CRITERES {
+        let ordre = |a: &LigneClassement, b: &LigneClassement| {
+            critere(&particulier[&a.equipe], &particulier[&b.equipe])
+        };
+        groupe.sort_by(ordre);
+        if ordre(&groupe[0], &groupe[groupe.len() - 1]) == Ordering::Equal {
+            continue;
+        }
+        let mut debut = 0;
+        while debut < groupe.len() {
+            let fin = debut
+                + groupe[debut..]
+                    .iter()
+                    .take_while(|l| ordre(l, &groupe[debut]) == Ordering::Equal)
+                    .count();
+            departager_groupe(&mut groupe[debut..fin], rencontres, feuilles, bareme);
+            debut = fin;
+        }
+        return;
+    }
+    groupe.sort_by(|a, b| departager(a, b).then(a.equipe.cmp(&b.equipe)));
+}
+
+// compare deux quotients gagnés / perdus sans passer par les flottants
+// un quotient avec 0 perdu est considéré infini
+fn quotient((ga, pa): (u16, u16), (gb, pb): (u16, u16)) -> Ordering {
+    match (pa, pb) {
+        (0, 0) => ga.cmp(&gb),
+        (0, _) if ga > 0 => Ordering::Greater,
+        (_, 0) if gb > 0 => Ordering::Less,
+        _ => (u32::from(ga) * u32::from(pb)).cmp(&(u32::from(gb) * u32::from(pa))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Date;
+
+    fn rencontre(a: &str, b: &str, scorea: u8, scoreb: u8) -> Rencontre {
+        Rencontre {
+            tour: 1,
+            a: a.to_string(),
+            b: b.to_string(),
+            scorea: Some(scorea),
+            scoreb: Some(scoreb),
+            forfait: None,
+            date: Date::new(2024, 9, 28),
+            datereelle: None,
+            lien: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_calculer() {
+        // A, B et C finissent à égalité de points : A est dernière au quotient des parties entre elles,
+        // puis B devance C qu'elle a battue
+        let rencontres = vec![
+            rencontre("A", "B", 8, 6),
+            rencontre("B", "C", 9, 5),
+            rencontre("C", "A", 10, 4),
+            rencontre("A", "D", 14, 0),
+            rencontre("B", "D", 13, 1),
+            rencontre("C", "D", 14, 0),
+        ];
+        let classement = calculer(&rencontres, &Bareme::default());
+        let equipes: Vec<&str> = classement.iter().map(|l| l.equipe.as_str()).collect();
+        assert_eq!(equipes, vec!["B", "C", "A", "D"]);
+        assert_eq!(classement[0].points, 7);
+        assert_eq!(classement[3].points, 3);
+        assert_eq!(classement[3].rang, 4);
+        assert_eq!(classement[0].parties_gagnees, 28);
+    }
+
+    #[test]
+    fn test_egalite_sans_rencontre() {
+        // A et B, puis C et D, sont à égalité sans s'être encore rencontrées en cours de phase
+        let rencontres = vec![rencontre("A", "C", 10, 4), rencontre("B", "D", 12, 2)];
+        let classement = calculer(&rencontres, &Bareme::default());
+        let equipes: Vec<&str> = classement.iter().map(|l| l.equipe.as_str()).collect();
+        assert_eq!(equipes, vec!["B", "A", "C", "D"]);
+    }
+
+    #[test]
+    fn test_egalite_a_trois() {
+        // A, B et C se battent en cercle : C est devant au quotient des parties entre elles,
+        // puis A et B, encore à égalité, sont départagées par leur seule rencontre
+        let rencontres = vec![
+            rencontre("A", "B", 9, 5),
+            rencontre("B", "C", 8, 6),
+            rencontre("C", "A", 10, 4),
+            rencontre("A", "D", 8, 6),
+            rencontre("B", "D", 12, 2),
+            rencontre("C", "D", 8, 6),
+        ];
+        let classement = calculer(&rencontres, &Bareme::default());
+        let equipes: Vec<&str> = classement.iter().map(|l| l.equipe.as_str()).collect();
+        assert_eq!(equipes, vec!["C", "A", "B", "D"]);
+    }
+
+    #[test]
+    fn test_quotient() {
+        assert_eq!(quotient((3, 0), (10, 1)), Ordering::Greater);
+        assert_eq!(quotient((0, 0), (0, 3)), Ordering::Equal);
+        assert_eq!(quotient((6, 3), (4, 2)), Ordering::Equal);
+        assert_eq!(quotient((5, 3), (4, 2)), Ordering::Less);
+    }
+
+    #[test]
+    fn test_ecarts() {
+        let rencontres = vec![rencontre("A", "B", 8, 6)];
+        let calcule = calculer(&rencontres, &Bareme::default());
+        let officiel = vec![Classement {
+            numero_poule: 1,
+            classement: 1,
+            equipe: "A".to_string(),
+            joues: 1,
+            points: 3,
+            victoires: 1,
+            defaites: 0,
+            nuls: 0,
+            forfaits: 0,
+            parties_gagnees: 8,
+            parties_perdues: 6,
+            numero: String::new(),
+        }];
+        let ecarts = ecarts(&calcule, &officiel);
+        assert_eq!(ecarts.len(), 1);
+        assert_eq!(ecarts[0].equipe, "B");
+        assert_eq!(
+            ecarts[0].differences,
+            vec![Difference::AbsenteOfficiellement]
+        );
+    }
+}
@@ -1,10 +1,19 @@
+use std::collections::HashMap;
 use std::mem;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{equipe::Equipe, joueur::Joueur, API};
+use crate::{
+    api,
+    calendrier::{self, Evenement},
+    equipe::{normaliser, Equipe, EquipeApi},
+    joueur::Joueur,
+    telecharger,
+    tour::Cote,
+    ErreurApi,
+};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 /// un club de tennis de table
 pub struct Club {
     /// le numéro identifiant le club. Commence généralement par le numéro de département
@@ -32,88 +41,196 @@ pub struct Club {
 impl Club {
     /// Créé le club en interrogeant l'API avec l'id donné
     pub async fn new(idclub: &str) -> Club {
-        let request_url = format!("{API}/proxy/xml_club_detail.php?club={idclub}");
-        let response = reqwest::get(&request_url)
+        Self::try_new(idclub)
             .await
             .expect("Impossible de récupérer les informations du club")
-            .text()
-            .await
-            .expect("Impossible de récupérer les informations du club");
-        let mut doc: DocumentClub =
-            quick_xml::de::from_str(&response).expect("Erreur lors de la désérialisation du club");
-        let club = &mut doc.club[0];
+    }
+
+    /// Créé le club en interrogeant l'API avec l'id donné, en retournant l'erreur éventuelle
+    pub async fn try_new(idclub: &str) -> Result<Club, ErreurApi> {
+        let api = api();
+        let request_url = format!("{api}/proxy/xml_club_detail.php?club={idclub}");
+        let response = telecharger(&request_url).await?;
+        let doc: DocumentClub = quick_xml::de::from_str(&response)?;
+        let mut club = doc.club.into_iter().next().ok_or(ErreurApi::Introuvable)?;
         log::debug!("Club trouvé : {}", club.nom);
         // les clubs en France ont une longitude < latitude, on inverse si erreur de saisie par le club
         if club.latitude < club.longitude {
             mem::swap(&mut club.latitude, &mut club.longitude);
         }
-        Club {
-            nom: club.nom.clone(),
-            numero: club.numero.clone(),
-            adressesalle1: club.adressesalle1.clone(),
-            adressesalle2: club.adressesalle2.clone(),
-            adressesalle3: club.adressesalle3.clone(),
-            codepsalle: club.codepsalle.clone(),
-            latitude: club.latitude.clone(),
-            longitude: club.longitude.clone(),
-            nomsalle: club.nomsalle.clone(),
-            villesalle: club.villesalle.clone(),
-        }
+        Ok(Club {
+            nom: club.nom,
+            numero: club.numero,
+            adressesalle1: club.adressesalle1,
+            adressesalle2: club.adressesalle2,
+            adressesalle3: club.adressesalle3,
+            codepsalle: club.codepsalle,
+            latitude: club.latitude,
+            longitude: club.longitude,
+            nomsalle: club.nomsalle,
+            villesalle: club.villesalle,
+        })
+    }
+
+    /// retourne le numéro du département du club, encodé dans son numéro (ex : "34" pour "11340010")
+    ///
+    /// Les départements d'outre-mer (971 à 976) sont codés sur trois chiffres, suivis d'un numéro
+    /// d'ordre sur trois chiffres (ex : "974" pour "12974001").
+    pub fn departement(&self) -> Option<&str> {
+        let fin = if self.numero.get(2..4) == Some("97") {
+            5
+        } else {
+            4
+        };
+        self.numero
+            .get(2..fin)
+            .filter(|d| d.chars().all(|c| c.is_ascii_alphanumeric()))
     }
 
     /// Retourne l'ensemble des joueurs du club en interrogeant l'API
+    /// un joueur qui ne peut être récupéré est ignoré
     pub async fn api_joueurs(&self) -> Vec<Joueur> {
         log::info!("Récupération des joueurs du club...");
-        let request_url = format!("{API}/proxy/xml_licence_b.php?club={}", self.numero);
-        let response = reqwest::get(&request_url)
-            .await
-            .expect("Impossible de récupérer la liste des joueurs du club")
-            .text()
+        let doc = self
+            .licences()
             .await
             .expect("Impossible de récupérer la liste des joueurs du club");
-        // la réponse est en xml
-        log::info!("Traitement de la réponse...");
-        let doc: Document = quick_xml::de::from_str(&response)
-            .expect("Erreur lors de la désérialisation des joueurs");
 
         // on initialise tous les joueurs
         log::info!("Initialisation des joueurs...");
         let mut joueurs = vec![];
         for x in &doc.licence {
-            if let Ok(j) = Joueur::new(&x.licence).await {
+            if let Ok(j) = x.joueur().await {
                 joueurs.push(j);
             }
         }
         joueurs
     }
 
+    /// Retourne l'ensemble des joueurs du club en interrogeant l'API, en retournant l'erreur éventuelle
+    /// un joueur qui ne peut être récupéré fait échouer l'ensemble
+    pub async fn try_api_joueurs(&self) -> Result<Vec<Joueur>, ErreurApi> {
+        log::info!("Récupération des joueurs du club...");
+        let doc = self.licences().await?;
+        let mut joueurs = vec![];
+        for x in &doc.licence {
+            joueurs.push(x.joueur().await?);
+        }
+        Ok(joueurs)
+    }
+
+    /// retourne le nombre de licenciés du club, sans récupérer chacun des joueurs
+    pub async fn nombre_licencies(&self) -> Result<usize, ErreurApi> {
+        Ok(self.licences().await?.licence.len())
+    }
+
+    // récupère la liste des licences du club
+    async fn licences(&self) -> Result<Document, ErreurApi> {
+        let api = api();
+        let request_url = format!("{api}/proxy/xml_licence_b.php?club={}", self.numero);
+        let response = telecharger(&request_url).await?;
+        // la réponse est en xml
+        Ok(quick_xml::de::from_str(&response)?)
+    }
+
     /// Retourne les équipes du club engagées en compétition en interrogeant l'API
     pub async fn api_equipes(&self) -> Vec<Equipe> {
-        log::info!("Récupération des équipes du club...");
-        let request_url = format!("{API}/club/{}/equipes", self.numero);
-        let response = reqwest::get(&request_url)
+        self.try_api_equipes()
             .await
             .expect("Impossible de récupérer la liste des équipes du club")
-            .text()
-            .await
-            .expect("Impossible de récupérer la liste des équipes du club");
+    }
+
+    /// Retourne les équipes du club engagées en compétition en interrogeant l'API,
+    /// en retournant l'erreur éventuelle
+    pub async fn try_api_equipes(&self) -> Result<Vec<Equipe>, ErreurApi> {
+        log::info!("Récupération des équipes du club...");
+        let api = api();
+        let request_url = format!("{api}/club/{}/equipes", self.numero);
+        let response = telecharger(&request_url).await?;
         // la réponse est en json
-        log::info!("Traitement de la réponse...");
-        serde_json::from_str(&response).expect("Erreur lors du parsing des équipes du club")
+        let equipes: Vec<EquipeApi> = serde_json::from_str(&response)?;
+        Ok(equipes.into_iter().map(Equipe::from).collect())
+    }
+
+    /// retourne le calendrier chronologique des rencontres de toutes les équipes du club,
+    /// avec le club recevant chaque rencontre
+    pub async fn calendrier(&self) -> Vec<Evenement> {
+        self.try_calendrier()
+            .await
+            .expect("Impossible de construire le calendrier du club")
+    }
+
+    /// retourne le calendrier du club comme [`Club::calendrier`], en retournant l'erreur éventuelle
+    pub async fn try_calendrier(&self) -> Result<Vec<Evenement>, ErreurApi> {
+        log::info!("Construction du calendrier du club {}...", self.nom);
+        // les clubs déjà récupérés, `None` si la récupération a échoué
+        let mut clubs: HashMap<String, Option<Club>> = HashMap::new();
+        clubs.insert(self.numero.clone(), Some(self.clone()));
+        let mut evenements = vec![];
+        for equipe in self.try_api_equipes().await? {
+            let tours = equipe.poule.try_get_tours().await?;
+            // les numéros de club des équipes de la poule, par nom normalisé
+            let numeros: HashMap<String, String> = equipe
+                .poule
+                .try_classement()
+                .await?
+                .into_iter()
+                .map(|c| (normaliser(&c.equipe), c.numero))
+                .collect();
+            for mut e in calendrier::evenements(&equipe, &tours) {
+                let numero = match e.cote {
+                    Cote::Domicile => Some(&self.numero),
+                    Cote::Exterieur => numeros.get(&normaliser(&e.adversaire)),
+                };
+                if let Some(numero) = numero {
+                    if !clubs.contains_key(numero) {
+                        // un club adverse introuvable ne prive pas du reste du calendrier
+                        let club = Club::try_new(numero)
+                            .await
+                            .map_err(|err| {
+                                log::error!("Impossible de récupérer le club {numero} : {err:?}")
+                            })
+                            .ok();
+                        clubs.insert(numero.clone(), club);
+                    }
+                    e.lieu = clubs.get(numero).cloned().flatten();
+                }
+                evenements.push(e);
+            }
+        }
+        calendrier::trier(&mut evenements);
+        Ok(evenements)
     }
 }
 
 #[derive(Debug, Deserialize)]
 // représente le document XML retourné par l'API
 struct DocumentClub {
-    // l'ensemble des licences de tous les joueurs
-    club: Vec<Club>,
+    // les clubs correspondant à la recherche
+    #[serde(default)]
+    club: Vec<ClubApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// un club tel que retourné par l'API
+struct ClubApi {
+    numero: String,
+    nom: String,
+    nomsalle: String,
+    adressesalle1: String,
+    adressesalle2: String,
+    adressesalle3: String,
+    codepsalle: String,
+    villesalle: String,
+    latitude: String,
+    longitude: String,
 }
 
 #[derive(Debug, Deserialize)]
 // représente le document XML retourné par l'API
 struct Document {
     // l'ensemble des licences de tous les joueurs
+    #[serde(default)]
     licence: Vec<Licence>,
 }
 
@@ -124,4 +241,18 @@ struct Licence {
     licence: String,
     // son prénom
     // prenom: String,
+    // sa catégorie d'âge
+    #[serde(default)]
+    cat: Option<String>,
+}
+
+impl Licence {
+    // récupère le joueur de la licence, en complétant sa catégorie si l'API ne la donne pas
+    async fn joueur(&self) -> Result<Joueur, ErreurApi> {
+        let mut j = Joueur::try_new(&self.licence).await?;
+        if j.categorie.is_none() {
+            j.categorie = self.cat.clone().filter(|c| !c.is_empty());
+        }
+        Ok(j)
+    }
 }
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+
+use crate::date::Date;
+use crate::feuille::{FeuilleDeMatch, JoueurFeuille};
+use crate::tour::{Cote, Rencontre};
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// la composition d'une équipe lors d'une rencontre jouée
+pub struct Composition {
+    /// le tour de la rencontre
+    pub tour: u8,
+    /// la date de la rencontre
+    pub date: Date,
+    /// le côté de l'équipe lors de la rencontre
+    pub cote: Cote,
+    /// nom de l'équipe adverse
+    pub adversaire: String,
+    /// les joueurs ayant joué pour l'équipe
+    pub joueurs: Vec<Participation>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// la participation d'un joueur à une rencontre
+pub struct Participation {
+    /// le joueur, avec ses points au moment de la rencontre
+    pub joueur: JoueurFeuille,
+    /// nombre de victoires en simple
+    pub victoires: u8,
+    /// nombre de défaites en simple
+    pub defaites: u8,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// le bilan d'un joueur dans une équipe sur la phase
+pub struct BilanJoueur {
+    /// nom et prénom du joueur
+    pub nom: String,
+    /// numéro de licence, s'il est connu
+    pub licence: Option<String>,
+    /// les tours auxquels le joueur a participé
+    pub tours: Vec<u8>,
+    /// points du joueur lors de sa dernière rencontre
+    pub points: Option<u16>,
+    /// nombre de victoires en simple
+    pub victoires: u8,
+    /// nombre de défaites en simple
+    pub defaites: u8,
+}
+
+impl Composition {
+    /// extrait la composition d'une équipe à partir de la rencontre et de sa feuille de match
+    /// retourne None si l'équipe ne joue pas cette rencontre
+    pub fn new(
+        equipe: &str,
+        rencontre: &Rencontre,
+        feuille: &FeuilleDeMatch,
+    ) -> Option<Composition> {
+        let cote = rencontre.cote(equipe)?;
+        let joueurs = match cote {
+            Cote::Domicile => &feuille.joueursa,
+            Cote::Exterieur => &feuille.joueursb,
+        };
+        Some(Composition {
+            tour: rencontre.tour,
+            date: rencontre.date.clone(),
+            cote,
+            adversaire: rencontre.adversaire(equipe)?.to_string(),
+            joueurs: joueurs
+                .iter()
+                .map(|j| {
+                    let (victoires, defaites) = feuille.bilan(&j.nom);
+                    Participation {
+                        joueur: j.clone(),
+                        victoires,
+                        defaites,
+                    }
+                })
+                .collect(),
+        })
+    }
+}
+
+/// agrège les compositions d'une équipe en un bilan par joueur, trié par nombre de participations
+pub fn bilan(compositions: &[Composition]) -> Vec<BilanJoueur> {
+    let mut bilans: Vec<BilanJoueur> = vec![];
+    for c in compositions {
+        for p in &c.joueurs {
+            let existant = bilans
+                .iter_mut()
+                .find(|b| match (&b.licence, &p.joueur.licence) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => b.nom == p.joueur.nom,
+                });
+            match existant {
+                Some(b) => {
+                    b.tours.push(c.tour);
+                    b.points = p.joueur.points.or(b.points);
+                    b.victoires += p.victoires;
+                    b.defaites += p.defaites;
+                }
+                None => bilans.push(BilanJoueur {
+                    nom: p.joueur.nom.clone(),
+                    licence: p.joueur.licence.clone(),
+                    tours: vec![c.tour],
+                    points: p.joueur.points,
+                    victoires: p.victoires,
+                    defaites: p.defaites,
+                }),
+            }
+        }
+    }
+    bilans.sort_by(|a, b| b.tours.len().cmp(&a.tours.len()).then(a.nom.cmp(&b.nom)));
+    bilans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEUILLE: &str = r#"<liste>
+    <resultat><equa>NIMES ASPTT 2</equa><equb>MONTPELLIER TT 3</equb><resa>6</resa><resb>8</resb></resultat>
+    <joueur><xja>DUPONT Jean</xja><xca>M 1845pts</xca><xjb>MARTIN Paul</xjb><xcb>M 1520pts</xcb><xlb>3400001</xlb></joueur>
+    <joueur><xja>DURAND Luc</xja><xca>M 1610pts</xca><xjb>BERNARD Eric</xjb><xcb>M 1480pts</xcb><xlb>3400002</xlb></joueur>
+    <partie><ja>DUPONT Jean</ja><scorea>0</scorea><jb>MARTIN Paul</jb><scoreb>1</scoreb><detail>-5 -7 -9</detail></partie>
+    <partie><ja>DURAND Luc</ja><scorea>1</scorea><jb>BERNARD Eric</jb><scoreb>0</scoreb><detail>5 7 9</detail></partie>
+</liste>"#;
+
+    fn rencontre(tour: u8) -> Rencontre {
+        Rencontre {
+            tour,
+            a: "NIMES ASPTT 2".to_string(),
+            b: "MONTPELLIER TT 3".to_string(),
+            scorea: Some(6),
+            scoreb: Some(8),
+            forfait: None,
+            date: Date::new(2024, 10, 12),
+            datereelle: None,
+            lien: "renc_id=1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_composition() {
+        let feuille = FeuilleDeMatch::from_xml(FEUILLE).unwrap();
+        let c = Composition::new("MONTPELLIER TT 3", &rencontre(2), &feuille).unwrap();
+        assert_eq!(c.cote, Cote::Exterieur);
+        assert_eq!(c.adversaire, "NIMES ASPTT 2");
+        assert_eq!(c.joueurs.len(), 2);
+        assert_eq!(c.joueurs[0].joueur.licence.as_deref(), Some("3400001"));
+        assert_eq!(c.joueurs[0].victoires, 1);
+        assert!(Composition::new("SETE TT 1", &rencontre(2), &feuille).is_none());
+    }
+
+    #[test]
+    fn test_bilan() {
+        let feuille = FeuilleDeMatch::from_xml(FEUILLE).unwrap();
+        let mut c2 = Composition::new("MONTPELLIER TT 3", &rencontre(2), &feuille).unwrap();
+        let c1 = Composition::new("MONTPELLIER TT 3", &rencontre(1), &feuille).unwrap();
+        c2.joueurs.pop();
+        let bilans = bilan(&[c1, c2]);
+        assert_eq!(bilans.len(), 2);
+        assert_eq!(bilans[0].nom, "MARTIN Paul");
+        assert_eq!(bilans[0].tours, vec![1, 2]);
+        assert_eq!(bilans[0].victoires, 2);
+        assert_eq!(bilans[1].defaites, 1);
+    }
+}
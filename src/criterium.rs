@@ -0,0 +1,542 @@
+use serde::{Deserialize, Serialize};
+
+use crate::epreuve::{Division, Epreuve, TypeEpreuve};
+use crate::joueur::Joueur;
+use crate::{api, parametre, recuperer};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+/// le niveau d'une division, du plus haut au plus bas
+pub enum Niveau {
+    /// niveau national, organisé par la fédération
+    National,
+    /// niveau zone, regroupant plusieurs ligues
+    Zone,
+    /// niveau régional, organisé par une ligue
+    Regional,
+    /// niveau départemental, organisé par un comité
+    Departemental,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+/// l'évolution d'un joueur à l'issue d'un tour
+pub enum Evolution {
+    /// le joueur monte dans la division supérieure
+    Montee,
+    /// le joueur se maintient dans sa division
+    Maintien,
+    /// le joueur descend dans la division inférieure
+    Descente,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// le critérium fédéral organisé par un organisme
+pub struct Criterium {
+    /// identifiant de l'organisme (fédération, zone, ligue ou comité)
+    pub organisme: String,
+    /// identifiant de l'épreuve
+    pub epreuve: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une division du critérium fédéral, propre à un niveau et une catégorie
+pub struct DivisionCriterium {
+    /// identifiant de l'épreuve
+    pub epreuve: String,
+    /// identifiant de la division
+    pub id: String,
+    /// libellé de la division (ex : "N1 Messieurs Seniors")
+    pub libelle: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// un groupe (poule ou tableau) d'un tour d'une division
+pub struct Groupe {
+    /// identifiant de l'épreuve
+    pub epreuve: String,
+    /// identifiant de la division
+    pub division: String,
+    /// libellé du groupe (ex : "Tour n°1 - Poule A")
+    pub libelle: String,
+    /// identifiant du tableau
+    pub tableau: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// le classement d'un joueur dans une division ou un groupe
+pub struct ClassementIndividuel {
+    /// rang du joueur
+    pub rang: u16,
+    /// nom et prénom du joueur
+    pub nom: String,
+    /// points de classement du joueur
+    pub points: Option<u16>,
+    /// nom du club du joueur
+    pub club: String,
+    /// points obtenus au critérium
+    pub points_criterium: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une partie disputée dans un groupe
+pub struct PartieIndividuelle {
+    /// libellé de la partie (ex : "Poule A", "1/2 finale")
+    pub libelle: String,
+    /// nom du vainqueur
+    pub vainqueur: String,
+    /// nom du perdant
+    pub perdant: String,
+    /// indique si la partie a été gagnée par forfait
+    pub forfait: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// le résultat d'un joueur lors d'un tour du critérium fédéral
+pub struct ResultatCriterium {
+    /// saison (ex : "2024/2025")
+    pub saison: String,
+    /// numéro du tour
+    pub tour: u8,
+    /// libellé de la division
+    pub division: String,
+    /// rang obtenu dans la division
+    pub rang: u16,
+    /// points obtenus au critérium
+    pub points: f32,
+    /// évolution du joueur pour le tour suivant, si elle est connue
+    pub evolution: Option<Evolution>,
+}
+
+impl Niveau {
+    // retrouve le niveau à partir du premier mot du libellé d'une division,
+    // abrégé (ex : "N1", "PR") ou non (ex : "Régionale")
+    fn depuis_libelle(libelle: &str) -> Option<Niveau> {
+        let premier = libelle.split_whitespace().next()?.to_lowercase();
+        let mut caracteres = premier.chars();
+        let initiale = caracteres.next()?;
+        let abrege = caracteres.all(|c| c.is_ascii_digit());
+        let commence = |prefixes: &[&str]| prefixes.iter().any(|p| premier.starts_with(p));
+        if (abrege && initiale == 'n') || commence(&["nat"]) {
+            Some(Niveau::National)
+        } else if (abrege && initiale == 'z') || commence(&["zon"]) {
+            Some(Niveau::Zone)
+        } else if (abrege && initiale == 'r')
+            || premier == "pr"
+            || commence(&["rég", "reg", "pré", "pre"])
+        {
+            Some(Niveau::Regional)
+        } else if (abrege && initiale == 'd') || commence(&["dép", "dep"]) {
+            Some(Niveau::Departemental)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<Division> for DivisionCriterium {
+    fn from(division: Division) -> Self {
+        DivisionCriterium {
+            epreuve: division.epreuve,
+            id: division.id,
+            libelle: division.libelle,
+        }
+    }
+}
+
+impl DivisionCriterium {
+    /// retourne le niveau de la division, déduit de son libellé
+    pub fn niveau(&self) -> Option<Niveau> {
+        Niveau::depuis_libelle(&self.libelle)
+    }
+
+    /// retourne la catégorie de la division, c'est-à-dire son libellé sans le niveau
+    /// (ex : "Messieurs Seniors" pour "N1 Messieurs Seniors")
+    pub fn categorie(&self) -> &str {
+        match self.niveau() {
+            Some(_) => self
+                .libelle
+                .trim()
+                .split_once(char::is_whitespace)
+                .map_or("", |(_, c)| c.trim()),
+            None => self.libelle.trim(),
+        }
+    }
+
+    /// retourne les groupes (poules et tableaux) de la division
+    pub async fn groupes(&self) -> Vec<Groupe> {
+        let api = api();
+        let request_url = format!(
+            "{api}/proxy/xml_result_indiv.php?action=poule&epr={}&res_division={}",
+            self.epreuve, self.id
+        );
+        let Some(xml) = recuperer(&request_url).await else {
+            return vec![];
+        };
+        Groupe::from_xml(&xml, &self.epreuve, &self.id).unwrap_or_else(|e| {
+            log::error!("Erreur lors de la désérialisation des groupes : {e}");
+            vec![]
+        })
+    }
+
+    /// retourne le classement individuel de la division
+    pub async fn classement(&self) -> Vec<ClassementIndividuel> {
+        let api = api();
+        let request_url = format!(
+            "{api}/proxy/xml_result_indiv.php?action=classement&epr={}&res_division={}",
+            self.epreuve, self.id
+        );
+        classement(&request_url).await
+    }
+}
+
+impl Groupe {
+    /// créé les groupes à partir du document XML retourné par l'API
+    pub fn from_xml(
+        xml: &str,
+        epreuve: &str,
+        division: &str,
+    ) -> Result<Vec<Groupe>, quick_xml::DeError> {
+        let doc: DocumentGroupes = quick_xml::de::from_str(xml)?;
+        Ok(doc
+            .tour
+            .into_iter()
+            .map(|t| Groupe {
+                epreuve: epreuve.to_string(),
+                division: division.to_string(),
+                tableau: parametre(&t.lien, "cx_tableau").unwrap_or_default(),
+                libelle: t.libelle,
+            })
+            .collect())
+    }
+
+    /// retourne le classement individuel du groupe
+    pub async fn classement(&self) -> Vec<ClassementIndividuel> {
+        let api = api();
+        let request_url = format!(
+            "{api}/proxy/xml_result_indiv.php?action=classement&epr={}&res_division={}&cx_tableau={}",
+            self.epreuve, self.division, self.tableau
+        );
+        classement(&request_url).await
+    }
+
+    /// retourne les parties disputées dans le groupe
+    pub async fn parties(&self) -> Vec<PartieIndividuelle> {
+        let api = api();
+        let request_url = format!(
+            "{api}/proxy/xml_result_indiv.php?action=partie&epr={}&res_division={}&cx_tableau={}",
+            self.epreuve, self.division, self.tableau
+        );
+        let Some(xml) = recuperer(&request_url).await else {
+            return vec![];
+        };
+        PartieIndividuelle::from_xml(&xml).unwrap_or_else(|e| {
+            log::error!("Erreur lors de la désérialisation des parties : {e}");
+            vec![]
+        })
+    }
+}
+
+impl ClassementIndividuel {
+    /// créé les classements à partir du document XML retourné par l'API
+    pub fn from_xml(xml: &str) -> Result<Vec<ClassementIndividuel>, quick_xml::DeError> {
+        let doc: DocumentClassements = quick_xml::de::from_str(xml)?;
+        Ok(doc
+            .classement
+            .into_iter()
+            .map(|c| ClassementIndividuel {
+                rang: c.rang.trim().parse().unwrap_or_default(),
+                nom: c.nom,
+                points: c.clt.trim().parse().ok(),
+                club: c.club,
+                points_criterium: nombre(&c.points),
+            })
+            .collect())
+    }
+}
+
+impl PartieIndividuelle {
+    /// créé les parties à partir du document XML retourné par l'API
+    pub fn from_xml(xml: &str) -> Result<Vec<PartieIndividuelle>, quick_xml::DeError> {
+        let doc: DocumentParties = quick_xml::de::from_str(xml)?;
+        Ok(doc
+            .partie
+            .into_iter()
+            .map(|p| PartieIndividuelle {
+                libelle: p.libelle,
+                vainqueur: p.vain,
+                perdant: p.perd,
+                forfait: p.forfait.trim() == "1",
+            })
+            .collect())
+    }
+}
+
+impl ResultatCriterium {
+    /// créé les résultats à partir du document XML retourné par l'API
+    pub fn from_xml(xml: &str) -> Result<Vec<ResultatCriterium>, quick_xml::DeError> {
+        let doc: DocumentResultats = quick_xml::de::from_str(xml)?;
+        Ok(doc
+            .resultat
+            .into_iter()
+            .map(|r| ResultatCriterium {
+                saison: r.saison,
+                tour: r.tour.trim().parse().unwrap_or_default(),
+                division: r.division,
+                rang: r.rang.trim().parse().unwrap_or_default(),
+                points: nombre(&r.points),
+                evolution: match r.mouvement.trim().to_uppercase().as_str() {
+                    "M" | "+" => Some(Evolution::Montee),
+                    "=" => Some(Evolution::Maintien),
+                    "D" | "-" => Some(Evolution::Descente),
+                    _ => None,
+                },
+            })
+            .collect())
+    }
+}
+
+impl Criterium {
+    /// retourne les divisions du critérium, tous niveaux et catégories confondus
+    pub async fn divisions(&self) -> Vec<DivisionCriterium> {
+        self.epreuve()
+            .divisions()
+            .await
+            .into_iter()
+            .map(DivisionCriterium::from)
+            .collect()
+    }
+
+    /// retourne l'épreuve individuelle correspondant au critérium
+    pub fn epreuve(&self) -> Epreuve {
+        Epreuve {
+            id: self.epreuve.clone(),
+            organisme: self.organisme.clone(),
+            libelle: "Critérium fédéral".to_string(),
+            type_epreuve: TypeEpreuve::Individuelle,
+        }
+    }
+
+    /// retourne les divisions d'un niveau donné, éventuellement restreintes à une catégorie
+    /// la catégorie est recherchée dans le libellé sans tenir compte de la casse (ex : "dames")
+    pub async fn divisions_niveau(
+        &self,
+        niveau: Niveau,
+        categorie: Option<&str>,
+    ) -> Vec<DivisionCriterium> {
+        let categorie = categorie.map(str::to_lowercase);
+        self.divisions()
+            .await
+            .into_iter()
+            .filter(|d| d.niveau() == Some(niveau))
+            .filter(|d| {
+                categorie
+                    .as_ref()
+                    .is_none_or(|c| d.categorie().to_lowercase().contains(c.as_str()))
+            })
+            .collect()
+    }
+}
+
+impl Joueur {
+    /// retourne les résultats du joueur au critérium fédéral, avec ses points et ses montées
+    pub async fn api_criterium(&self) -> Vec<ResultatCriterium> {
+        let api = api();
+        let request_url = format!("{api}/proxy/xml_res_cla.php?numlic={}", self.licence);
+        let Some(xml) = recuperer(&request_url).await else {
+            return vec![];
+        };
+        ResultatCriterium::from_xml(&xml).unwrap_or_else(|e| {
+            log::error!("Erreur lors de la désérialisation du critérium : {e}");
+            vec![]
+        })
+    }
+}
+
+async fn classement(request_url: &str) -> Vec<ClassementIndividuel> {
+    let Some(xml) = recuperer(request_url).await else {
+        return vec![];
+    };
+    ClassementIndividuel::from_xml(&xml).unwrap_or_else(|e| {
+        log::error!("Erreur lors de la désérialisation du classement : {e}");
+        vec![]
+    })
+}
+
+// un nombre éventuellement décimal avec une virgule
+fn nombre(s: &str) -> f32 {
+    s.trim().replace(',', ".").parse().unwrap_or_default()
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentGroupes {
+    #[serde(default)]
+    tour: Vec<GroupeApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// un groupe tel que retourné par l'API
+struct GroupeApi {
+    libelle: String,
+    #[serde(default)]
+    lien: String,
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentClassements {
+    #[serde(default)]
+    classement: Vec<ClassementApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// un classement individuel tel que retourné par l'API
+struct ClassementApi {
+    rang: String,
+    nom: String,
+    #[serde(default)]
+    clt: String,
+    #[serde(default)]
+    club: String,
+    #[serde(default)]
+    points: String,
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentParties {
+    #[serde(default)]
+    partie: Vec<PartieApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// une partie telle que retournée par l'API
+struct PartieApi {
+    #[serde(default)]
+    libelle: String,
+    vain: String,
+    perd: String,
+    #[serde(default)]
+    forfait: String,
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentResultats {
+    #[serde(default)]
+    resultat: Vec<ResultatApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// un résultat de critérium tel que retourné par l'API
+struct ResultatApi {
+    #[serde(default)]
+    saison: String,
+    tour: String,
+    division: String,
+    rang: String,
+    #[serde(default)]
+    points: String,
+    #[serde(default)]
+    mouvement: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn division(libelle: &str) -> DivisionCriterium {
+        DivisionCriterium {
+            epreuve: "1234".to_string(),
+            id: "5678".to_string(),
+            libelle: libelle.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_niveau_categorie() {
+        let d = division("N1 Messieurs Seniors");
+        assert_eq!(d.niveau(), Some(Niveau::National));
+        assert_eq!(d.categorie(), "Messieurs Seniors");
+        assert_eq!(division("Z2 Dames -15").niveau(), Some(Niveau::Zone));
+        assert_eq!(
+            division("Régionale 1 Dames").niveau(),
+            Some(Niveau::Regional)
+        );
+        assert_eq!(
+            division("PR Messieurs -13").niveau(),
+            Some(Niveau::Regional)
+        );
+        assert_eq!(
+            division("D3 Messieurs Vétérans").niveau(),
+            Some(Niveau::Departemental)
+        );
+        let d = division("Dames Elite");
+        assert_eq!(d.niveau(), None);
+        assert_eq!(d.categorie(), "Dames Elite");
+    }
+
+    #[test]
+    fn test_divisions() {
+        let criterium = Criterium {
+            organisme: "1008".to_string(),
+            epreuve: "12250".to_string(),
+        };
+        assert_eq!(criterium.epreuve().criterium(), Some(criterium.clone()));
+        let xml = r#"<liste>
+    <division><iddivision>5678</iddivision><libelle>R1 Dames Seniors</libelle></division>
+</liste>"#;
+        let divisions: Vec<DivisionCriterium> = Division::from_xml(xml, "1008", "1234")
+            .unwrap()
+            .into_iter()
+            .map(DivisionCriterium::from)
+            .collect();
+        assert_eq!(divisions, vec![division("R1 Dames Seniors")]);
+    }
+
+    #[test]
+    fn test_groupes_classement_parties() {
+        let xml = r#"<liste>
+    <tour><libelle>Tour n°1 - Poule A</libelle><lien>epr=1234&amp;res_division=5678&amp;cx_tableau=91</lien></tour>
+    <tour><libelle>Tour n°1 - Poule B</libelle><lien>epr=1234&amp;res_division=5678&amp;cx_tableau=92</lien></tour>
+</liste>"#;
+        let groupes = Groupe::from_xml(xml, "1234", "5678").unwrap();
+        assert_eq!(groupes.len(), 2);
+        assert_eq!(groupes[1].tableau, "92");
+        assert_eq!(groupes[1].libelle, "Tour n°1 - Poule B");
+
+        let xml = r#"<liste>
+    <classement><rang>1</rang><nom>DOE John</nom><clt>1520</clt><club>MONTPELLIER TT</club><points>12,5</points></classement>
+    <classement><rang>2</rang><nom>ROE Jane</nom><clt>N°42</clt><club>SETE TT</club><points>10</points></classement>
+</liste>"#;
+        let classement = ClassementIndividuel::from_xml(xml).unwrap();
+        assert_eq!(classement[0].points, Some(1520));
+        assert_eq!(classement[0].points_criterium, 12.5);
+        assert_eq!(classement[1].points, None);
+
+        let xml = r#"<liste>
+    <partie><libelle>Poule A</libelle><vain>DOE John</vain><perd>ROE Jane</perd><forfait>0</forfait></partie>
+    <partie><libelle>Finale</libelle><vain>ROE Jane</vain><perd>POE Jim</perd><forfait>1</forfait></partie>
+</liste>"#;
+        let parties = PartieIndividuelle::from_xml(xml).unwrap();
+        assert!(!parties[0].forfait);
+        assert!(parties[1].forfait);
+        assert_eq!(parties[1].vainqueur, "ROE Jane");
+    }
+
+    #[test]
+    fn test_resultats() {
+        let xml = r#"<liste>
+    <resultat><saison>2024/2025</saison><tour>1</tour><division>R1 Messieurs Seniors</division><rang>2</rang><points>95</points><mouvement>M</mouvement></resultat>
+    <resultat><saison>2024/2025</saison><tour>2</tour><division>N2 Messieurs Seniors</division><rang>14</rang><points>40,5</points><mouvement></mouvement></resultat>
+</liste>"#;
+        let resultats = ResultatCriterium::from_xml(xml).unwrap();
+        assert_eq!(resultats.len(), 2);
+        assert_eq!(resultats[0].evolution, Some(Evolution::Montee));
+        assert_eq!(resultats[1].evolution, None);
+        assert_eq!(resultats[1].points, 40.5);
+        assert_eq!(
+            ResultatCriterium::from_xml("<liste></liste>").unwrap(),
+            vec![]
+        );
+    }
+}
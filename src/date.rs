@@ -1,11 +1,12 @@
 use chrono::{Datelike, Local};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::str::FromStr;
 use std::{cmp::Ordering, fmt};
 
 use super::{DEBSAISON_JOUR, DEBSAISON_MOIS, MISAISON_JOUR, MISAISON_MOIS};
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug)]
+/// une date, sérialisée au format AAAA-MM-JJ
 pub struct Date {
     pub annee: u16,
     pub mois: u8,
@@ -15,13 +16,26 @@ pub struct Date {
 impl FromStr for Date {
     type Err = ();
 
-    /// Crée une date à partir de la chaîne au format JJ/MM/AA
+    /// Crée une date à partir de la chaîne au format JJ/MM/AAAA (format de l'API) ou AAAA-MM-JJ
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let splitted: Vec<&str> = s.split('/').collect();
+        let s = s.trim();
+        let (jour, mois, annee) = if s.contains('-') {
+            let splitted: Vec<&str> = s.split('-').collect();
+            if splitted.len() != 3 {
+                return Err(());
+            }
+            (splitted[2], splitted[1], splitted[0])
+        } else {
+            let splitted: Vec<&str> = s.split('/').collect();
+            if splitted.len() != 3 {
+                return Err(());
+            }
+            (splitted[0], splitted[1], splitted[2])
+        };
         Ok(Date {
-            jour: splitted[0].parse().expect("Le jour doit être un nombre"),
-            mois: splitted[1].parse().expect("Le mois doit être un nombre"),
-            annee: splitted[2].parse().expect("L'année doit être un nombre"),
+            jour: jour.parse().map_err(|_| ())?,
+            mois: mois.parse().map_err(|_| ())?,
+            annee: annee.parse().map_err(|_| ())?,
         })
     }
 }
@@ -31,8 +45,20 @@ impl<'de> Deserialize<'de> for Date {
     where
         D: Deserializer<'de>,
     {
-        let s: &str = Deserialize::deserialize(deserializer)?;
-        Ok(Date::from_str(s).unwrap())
+        let s: String = Deserialize::deserialize(deserializer)?;
+        Date::from_str(&s).map_err(|_| serde::de::Error::custom(format!("Date invalide : {s}")))
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format!(
+            "{:04}-{:02}-{:02}",
+            self.annee, self.mois, self.jour
+        ))
     }
 }
 
@@ -158,6 +184,16 @@ mod tests {
         assert!(d_1 > d_2);
     }
 
+    #[test]
+    fn test_serde() {
+        let d = Date::new(2024, 9, 8);
+        let json = serde_json::to_string(&d).unwrap();
+        assert_eq!(json, "\"2024-09-08\"");
+        assert_eq!(serde_json::from_str::<Date>(&json).unwrap(), d);
+        assert_eq!(serde_json::from_str::<Date>("\"08/09/2024\"").unwrap(), d);
+        assert!(serde_json::from_str::<Date>("\"2024\"").is_err());
+    }
+
     #[test]
     fn bench_order() {
         let d_1 = Date::new(2023, 1, 1);
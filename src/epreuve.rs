@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+
+use crate::criterium::Criterium;
+use crate::organisme::Organisme;
+use crate::poule::Poule;
+use crate::{api, parametre, recuperer};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+/// le type d'une épreuve
+pub enum TypeEpreuve {
+    /// épreuve par équipes
+    Equipes,
+    /// épreuve individuelle
+    Individuelle,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+/// la nature d'une épreuve, déduite de son libellé
+pub enum Nature {
+    /// championnat par équipes
+    Championnat,
+    /// coupe
+    Coupe,
+    /// critérium fédéral
+    Criterium,
+    /// tournoi
+    Tournoi,
+    /// autre épreuve (finales, interclubs...)
+    Autre,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une épreuve organisée par un organisme
+pub struct Epreuve {
+    /// identifiant de l'épreuve
+    pub id: String,
+    /// identifiant de l'organisme organisateur
+    pub organisme: String,
+    /// libellé de l'épreuve
+    pub libelle: String,
+    /// type de l'épreuve
+    pub type_epreuve: TypeEpreuve,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une division d'une épreuve
+pub struct Division {
+    /// identifiant de la division
+    pub id: String,
+    /// identifiant de l'organisme organisateur
+    pub organisme: String,
+    /// identifiant de l'épreuve
+    pub epreuve: String,
+    /// libellé de la division (ex : "Régionale 1")
+    pub libelle: String,
+}
+
+impl TypeEpreuve {
+    /// le code du type attendu par l'API
+    pub fn code(&self) -> char {
+        match self {
+            TypeEpreuve::Equipes => 'E',
+            TypeEpreuve::Individuelle => 'I',
+        }
+    }
+}
+
+impl Epreuve {
+    /// retourne la nature de l'épreuve, déduite de son libellé
+    pub fn nature(&self) -> Nature {
+        let libelle = self.libelle.to_lowercase();
+        if libelle.contains("critérium") || libelle.contains("criterium") {
+            Nature::Criterium
+        } else if libelle.contains("tournoi") {
+            Nature::Tournoi
+        } else if libelle.contains("coupe") {
+            Nature::Coupe
+        } else if libelle.contains("championnat") {
+            Nature::Championnat
+        } else {
+            Nature::Autre
+        }
+    }
+
+    /// retourne le critérium fédéral correspondant à l'épreuve, s'il s'agit d'un critérium
+    pub fn criterium(&self) -> Option<Criterium> {
+        (self.nature() == Nature::Criterium).then(|| Criterium {
+            organisme: self.organisme.clone(),
+            epreuve: self.id.clone(),
+        })
+    }
+
+    /// retourne les divisions de l'épreuve
+    pub async fn divisions(&self) -> Vec<Division> {
+        let api = api();
+        let request_url = format!(
+            "{api}/proxy/xml_division.php?organisme={}&epreuve={}&type={}",
+            self.organisme,
+            self.id,
+            self.type_epreuve.code()
+        );
+        let Some(xml) = recuperer(&request_url).await else {
+            return vec![];
+        };
+        Division::from_xml(&xml, &self.organisme, &self.id).unwrap_or_else(|e| {
+            log::error!("Erreur lors de la désérialisation des divisions : {e}");
+            vec![]
+        })
+    }
+}
+
+impl Division {
+    /// créé les divisions à partir du document XML retourné par l'API
+    pub fn from_xml(
+        xml: &str,
+        organisme: &str,
+        epreuve: &str,
+    ) -> Result<Vec<Division>, quick_xml::DeError> {
+        let doc: DocumentDivisions = quick_xml::de::from_str(xml)?;
+        Ok(doc
+            .division
+            .into_iter()
+            .map(|d| Division {
+                id: d.iddivision,
+                organisme: organisme.to_string(),
+                epreuve: epreuve.to_string(),
+                libelle: d.libelle,
+            })
+            .collect())
+    }
+
+    /// retourne les poules de la division avec leur libellé (ex : "Poule 3"),
+    /// utilisables ensuite avec [`Poule::get_tours`] et [`Poule::classement`]
+    pub async fn poules(&self) -> Vec<(String, Poule)> {
+        let api = api();
+        let request_url = format!("{api}/proxy/xml_result_equ.php?action=poule&D1={}", self.id);
+        let Some(xml) = recuperer(&request_url).await else {
+            return vec![];
+        };
+        poules_from_xml(&xml, &self.id).unwrap_or_else(|e| {
+            log::error!("Erreur lors de la désérialisation des poules : {e}");
+            vec![]
+        })
+    }
+}
+
+impl Organisme {
+    /// retourne les épreuves du type donné organisées par l'organisme
+    pub async fn epreuves(&self, type_epreuve: TypeEpreuve) -> Vec<Epreuve> {
+        let api = api();
+        let request_url = format!(
+            "{api}/proxy/xml_epreuve.php?organisme={}&type={}",
+            self.id,
+            type_epreuve.code()
+        );
+        let Some(xml) = recuperer(&request_url).await else {
+            return vec![];
+        };
+        epreuves_from_xml(&xml, &self.id, type_epreuve).unwrap_or_else(|e| {
+            log::error!("Erreur lors de la désérialisation des épreuves : {e}");
+            vec![]
+        })
+    }
+}
+
+// créé les épreuves à partir du document XML retourné par l'API
+fn epreuves_from_xml(
+    xml: &str,
+    organisme: &str,
+    type_epreuve: TypeEpreuve,
+) -> Result<Vec<Epreuve>, quick_xml::DeError> {
+    let doc: DocumentEpreuves = quick_xml::de::from_str(xml)?;
+    Ok(doc
+        .epreuve
+        .into_iter()
+        .map(|e| Epreuve {
+            id: e.idepreuve,
+            organisme: e
+                .idorga
+                .filter(|o| !o.is_empty())
+                .unwrap_or_else(|| organisme.to_string()),
+            libelle: e.libelle,
+            type_epreuve,
+        })
+        .collect())
+}
+
+// créé les poules à partir du document XML retourné par l'API
+// une poule dont le lien ne précise pas le numéro est ignorée
+fn poules_from_xml(xml: &str, division: &str) -> Result<Vec<(String, Poule)>, quick_xml::DeError> {
+    let doc: DocumentPoules = quick_xml::de::from_str(xml)?;
+    Ok(doc
+        .poule
+        .into_iter()
+        .filter_map(|p| {
+            let poule = Poule {
+                division: parametre(&p.lien, "D1").unwrap_or_else(|| division.to_string()),
+                numero: parametre(&p.lien, "cx_poule")?,
+            };
+            Some((p.libelle, poule))
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentEpreuves {
+    #[serde(default)]
+    epreuve: Vec<EpreuveApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// une épreuve telle que retournée par l'API
+struct EpreuveApi {
+    idepreuve: String,
+    #[serde(default)]
+    idorga: Option<String>,
+    libelle: String,
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentDivisions {
+    #[serde(default)]
+    division: Vec<DivisionApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// une division telle que retournée par l'API
+struct DivisionApi {
+    iddivision: String,
+    libelle: String,
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentPoules {
+    #[serde(default)]
+    poule: Vec<PouleApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// une poule telle que retournée par l'API
+struct PouleApi {
+    libelle: String,
+    lien: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epreuves() {
+        let xml = r#"<liste>
+    <epreuve><idepreuve>12245</idepreuve><idorga>1008</idorga><libelle>Championnat de France par Equipes Masculin</libelle><typepreuve>C</typepreuve></epreuve>
+    <epreuve><idepreuve>12250</idepreuve><idorga></idorga><libelle>Critérium fédéral</libelle><typepreuve>I</typepreuve></epreuve>
+    <epreuve><idepreuve>12260</idepreuve><libelle>Coupe Vétérans</libelle></epreuve>
+</liste>"#;
+        let epreuves = epreuves_from_xml(xml, "1008", TypeEpreuve::Equipes).unwrap();
+        assert_eq!(epreuves.len(), 3);
+        assert_eq!(epreuves[0].nature(), Nature::Championnat);
+        assert_eq!(epreuves[1].organisme, "1008");
+        assert_eq!(epreuves[1].nature(), Nature::Criterium);
+        assert_eq!(epreuves[1].criterium().unwrap().epreuve, "12250");
+        assert_eq!(epreuves[2].nature(), Nature::Coupe);
+        assert!(epreuves[2].criterium().is_none());
+    }
+
+    #[test]
+    fn test_divisions_poules() {
+        let xml = r#"<liste>
+    <division><iddivision>5678</iddivision><libelle>Régionale 1</libelle></division>
+</liste>"#;
+        let divisions = Division::from_xml(xml, "1008", "12245").unwrap();
+        assert_eq!(divisions[0].id, "5678");
+        assert_eq!(divisions[0].epreuve, "12245");
+
+        let xml = r#"<liste>
+    <poule><libelle>Poule 1</libelle><lien>cx_poule=1233&amp;D1=5678&amp;organisme_pere=1008</lien></poule>
+    <poule><libelle>Poule 2</libelle><lien>cx_poule=1234&amp;D1=5678&amp;organisme_pere=1008</lien></poule>
+    <poule><libelle>Sans lien</libelle><lien></lien></poule>
+</liste>"#;
+        let poules = poules_from_xml(xml, "5678").unwrap();
+        assert_eq!(poules.len(), 2);
+        assert_eq!(
+            poules[1],
+            (
+                "Poule 2".to_string(),
+                Poule {
+                    division: "5678".to_string(),
+                    numero: "1234".to_string(),
+                }
+            )
+        );
+    }
+}
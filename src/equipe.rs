@@ -1,56 +1,130 @@
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::composition::Composition;
 use crate::poule::Poule;
+use crate::ErreurApi;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 /// une équipe engagée en compétition
 pub struct Equipe {
     /// numéro de l'équipe
-    #[serde(rename = "idequipe")]
     pub id: String,
 
     /// nom de l'équipe
-    #[serde(rename = "libequipe")]
     pub nom: String,
 
     /// libellé de l'épreuve
-    #[serde(rename = "libepr")]
+    #[serde(rename = "epreuve")]
     pub _epreuve: String,
 
     /// nom de la division
-    #[serde(rename = "libdivision")]
+    #[serde(rename = "division")]
     pub _division: String,
 
     /// poule de l'équipe
-    #[serde(rename = "liendivision", deserialize_with = "deserialize_poule")]
     pub poule: Poule,
 }
 
+#[derive(Debug, Deserialize)]
+// une équipe telle que retournée par l'API
+pub(crate) struct EquipeApi {
+    idequipe: String,
+    libequipe: String,
+    libepr: String,
+    libdivision: String,
+    #[serde(deserialize_with = "deserialize_poule")]
+    liendivision: Poule,
+}
+
+impl From<EquipeApi> for Equipe {
+    fn from(e: EquipeApi) -> Self {
+        Equipe {
+            poule: e.liendivision,
+            id: e.idequipe,
+            nom: e.libequipe,
+            _epreuve: e.libepr,
+            _division: e.libdivision,
+        }
+    }
+}
+
+impl Equipe {
+    /// retourne le nom de l'équipe tel qu'il apparaît dans les rencontres,
+    /// sans le suffixe de phase éventuel (ex : "MONTPELLIER TT 1 - Phase 1")
+    pub fn nom_rencontre(&self) -> &str {
+        self.nom.split(" - ").next().unwrap_or(&self.nom).trim()
+    }
+
+    /// retourne le numéro de l'équipe au sein du club (ex : 3 pour "MONTPELLIER TT 3")
+    pub fn numero(&self) -> Option<u8> {
+        self.nom_rencontre().split_whitespace().last()?.parse().ok()
+    }
+
+    /// indique si le nom donné, issu d'une rencontre, désigne cette équipe
+    pub fn est_nommee(&self, nom: &str) -> bool {
+        normaliser(self.nom_rencontre()) == normaliser(nom)
+    }
+
+    /// retourne la composition de l'équipe pour chaque rencontre jouée de sa poule
+    pub async fn compositions(&self) -> Vec<Composition> {
+        self.try_compositions()
+            .await
+            .expect("Impossible de récupérer les compositions de l'équipe")
+    }
+
+    /// retourne les compositions de l'équipe comme [`Equipe::compositions`],
+    /// en retournant l'erreur éventuelle de récupération de la poule
+    /// une rencontre dont la feuille de match ne peut être récupérée est ignorée
+    pub async fn try_compositions(&self) -> Result<Vec<Composition>, ErreurApi> {
+        log::info!("Récupération des compositions de l'équipe {}...", self.nom);
+        let mut compositions = vec![];
+        for (_, tour) in self.poule.try_get_tours().await? {
+            for r in &tour.rencontres.rencontres {
+                let nom = if self.est_nommee(&r.a) {
+                    &r.a
+                } else if self.est_nommee(&r.b) {
+                    &r.b
+                } else {
+                    continue;
+                };
+                if !r.est_jouee() {
+                    continue;
+                }
+                if let Some(feuille) = r.details().await {
+                    if let Some(c) = Composition::new(nom, r, &feuille) {
+                        compositions.push(c);
+                    }
+                }
+            }
+        }
+        Ok(compositions)
+    }
+}
+
+// normalise un nom d'équipe pour la comparaison : majuscules et espaces uniques
+pub(crate) fn normaliser(nom: &str) -> String {
+    nom.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+// extrait la poule du lien de division de la forme "cx_poule=...&D1=...&..."
 fn deserialize_poule<'de, D>(deserializer: D) -> Result<Poule, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s: &str = Deserialize::deserialize(deserializer)?;
-    let s = s.to_string();
-    let fields: Vec<&str> = s.split('&').collect();
-    let division = fields
-        .iter()
-        .filter(|x| x.starts_with("D1"))
-        .last()
-        .unwrap()
-        .split('=')
-        .last()
-        .unwrap();
-    let cx_poule = fields
-        .iter()
-        .filter(|x| x.starts_with("cx_poule"))
-        .last()
-        .unwrap()
-        .split('=')
-        .last()
-        .unwrap();
-    Ok(Poule {
-        division: division.to_string(),
-        numero: cx_poule.to_string(),
-    })
+    let lien: String = Deserialize::deserialize(deserializer)?;
+    let champ = |nom: &str| {
+        lien.split('&')
+            .rfind(|x| x.starts_with(nom))
+            .and_then(|x| x.split('=').next_back())
+            .map(str::to_string)
+    };
+    match (champ("D1"), champ("cx_poule")) {
+        (Some(division), Some(numero)) => Ok(Poule { division, numero }),
+        _ => Err(serde::de::Error::custom(format!(
+            "Lien de division invalide : {lien}"
+        ))),
+    }
 }
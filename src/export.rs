@@ -0,0 +1,589 @@
+#[cfg(any(feature = "xlsx", feature = "ods"))]
+use std::collections::HashSet;
+
+use crate::joueur::Joueur;
+use crate::poule::Classement;
+
+#[cfg(feature = "ods")]
+use crate::echapper_xml;
+#[cfg(any(feature = "xlsx", feature = "ods"))]
+use crate::equipe::Equipe;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// la langue des en-têtes et le format des nombres
+pub enum Langue {
+    /// en-têtes en français, séparateur `;` et virgule décimale
+    #[default]
+    Francais,
+    /// en-têtes en anglais, séparateur `,` et point décimal
+    Anglais,
+}
+
+impl Langue {
+    /// le séparateur de champs CSV habituel pour la langue
+    pub fn separateur(&self) -> char {
+        match self {
+            Langue::Francais => ';',
+            Langue::Anglais => ',',
+        }
+    }
+
+    // formate un nombre avec le séparateur décimal de la langue
+    fn nombre(&self, n: f64) -> String {
+        let texte = n.to_string();
+        match self {
+            Langue::Francais => texte.replace('.', ","),
+            Langue::Anglais => texte,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+// la valeur d'une cellule
+enum Valeur {
+    Texte(String),
+    Nombre(f64),
+}
+
+// les points des joueurs sont en simple précision : on les arrondit au centième
+// pour ne pas exporter l'erreur d'arrondi (1150,3 - 1100 donnant 50,30005)
+fn points(p: f32) -> Valeur {
+    Valeur::Nombre((f64::from(p) * 100.0).round() / 100.0)
+}
+
+impl Valeur {
+    fn csv(&self, langue: Langue) -> String {
+        match self {
+            Valeur::Texte(t) => t.clone(),
+            Valeur::Nombre(n) => langue.nombre(*n),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// une colonne de la liste des joueurs
+pub enum ColonneJoueur {
+    Licence,
+    Nom,
+    Prenom,
+    Categorie,
+    /// points de début de saison
+    PointsInitiaux,
+    /// points de début de phase
+    Points,
+    /// points virtuels actuels
+    PointsVirtuels,
+    /// progression depuis le début de la phase
+    ProgressionPhase,
+    /// progression depuis le début de la saison
+    ProgressionSaison,
+}
+
+impl ColonneJoueur {
+    /// toutes les colonnes, dans l'ordre par défaut
+    pub const TOUTES: [ColonneJoueur; 9] = [
+        ColonneJoueur::Licence,
+        ColonneJoueur::Nom,
+        ColonneJoueur::Prenom,
+        ColonneJoueur::Categorie,
+        ColonneJoueur::PointsInitiaux,
+        ColonneJoueur::Points,
+        ColonneJoueur::PointsVirtuels,
+        ColonneJoueur::ProgressionPhase,
+        ColonneJoueur::ProgressionSaison,
+    ];
+
+    /// l'en-tête de la colonne dans la langue donnée
+    pub fn entete(&self, langue: Langue) -> &'static str {
+        let (fr, en) = match self {
+            ColonneJoueur::Licence => ("Licence", "Licence"),
+            ColonneJoueur::Nom => ("Nom", "Last name"),
+            ColonneJoueur::Prenom => ("Prénom", "First name"),
+            ColonneJoueur::Categorie => ("Catégorie", "Category"),
+            ColonneJoueur::PointsInitiaux => ("Points début de saison", "Season start points"),
+            ColonneJoueur::Points => ("Points", "Points"),
+            ColonneJoueur::PointsVirtuels => ("Points virtuels", "Virtual points"),
+            ColonneJoueur::ProgressionPhase => ("Progression phase", "Phase progression"),
+            ColonneJoueur::ProgressionSaison => ("Progression saison", "Season progression"),
+        };
+        match langue {
+            Langue::Francais => fr,
+            Langue::Anglais => en,
+        }
+    }
+
+    fn valeur(&self, j: &Joueur) -> Valeur {
+        match self {
+            ColonneJoueur::Licence => Valeur::Texte(j.licence.clone()),
+            ColonneJoueur::Nom => Valeur::Texte(j.nom.clone()),
+            ColonneJoueur::Prenom => Valeur::Texte(j.prenom.clone()),
+            ColonneJoueur::Categorie => Valeur::Texte(j.categorie.clone().unwrap_or_default()),
+            ColonneJoueur::PointsInitiaux => points(j.points_init),
+            ColonneJoueur::Points => points(j.point),
+            ColonneJoueur::PointsVirtuels => points(j.r#_virtual),
+            ColonneJoueur::ProgressionPhase => points(j.progression_phase()),
+            ColonneJoueur::ProgressionSaison => points(j.progression_saison()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// une colonne du classement d'une poule
+pub enum ColonneClassement {
+    Rang,
+    Equipe,
+    Joues,
+    Points,
+    Victoires,
+    Nuls,
+    Defaites,
+    Forfaits,
+    PartiesGagnees,
+    PartiesPerdues,
+    /// numéro du club de l'équipe
+    NumeroClub,
+}
+
+impl ColonneClassement {
+    /// toutes les colonnes, dans l'ordre par défaut
+    pub const TOUTES: [ColonneClassement; 11] = [
+        ColonneClassement::Rang,
+        ColonneClassement::Equipe,
+        ColonneClassement::Joues,
+        ColonneClassement::Points,
+        ColonneClassement::Victoires,
+        ColonneClassement::Nuls,
+        ColonneClassement::Defaites,
+        ColonneClassement::Forfaits,
+        ColonneClassement::PartiesGagnees,
+        ColonneClassement::PartiesPerdues,
+        ColonneClassement::NumeroClub,
+    ];
+
+    /// l'en-tête de la colonne dans la langue donnée
+    pub fn entete(&self, langue: Langue) -> &'static str {
+        let (fr, en) = match self {
+            ColonneClassement::Rang => ("Rang", "Rank"),
+            ColonneClassement::Equipe => ("Équipe", "Team"),
+            ColonneClassement::Joues => ("Joués", "Played"),
+            ColonneClassement::Points => ("Points", "Points"),
+            ColonneClassement::Victoires => ("Victoires", "Wins"),
+            ColonneClassement::Nuls => ("Nuls", "Draws"),
+            ColonneClassement::Defaites => ("Défaites", "Losses"),
+            ColonneClassement::Forfaits => ("Forfaits", "Forfeits"),
+            ColonneClassement::PartiesGagnees => ("Parties gagnées", "Games won"),
+            ColonneClassement::PartiesPerdues => ("Parties perdues", "Games lost"),
+            ColonneClassement::NumeroClub => ("N° club", "Club number"),
+        };
+        match langue {
+            Langue::Francais => fr,
+            Langue::Anglais => en,
+        }
+    }
+
+    fn valeur(&self, c: &Classement) -> Valeur {
+        match self {
+            ColonneClassement::Rang => Valeur::Nombre(c.classement.into()),
+            ColonneClassement::Equipe => Valeur::Texte(c.equipe.clone()),
+            ColonneClassement::Joues => Valeur::Nombre(c.joues.into()),
+            ColonneClassement::Points => Valeur::Nombre(c.points.into()),
+            ColonneClassement::Victoires => Valeur::Nombre(c.victoires.into()),
+            ColonneClassement::Nuls => Valeur::Nombre(c.nuls.into()),
+            ColonneClassement::Defaites => Valeur::Nombre(c.defaites.into()),
+            ColonneClassement::Forfaits => Valeur::Nombre(c.forfaits.into()),
+            ColonneClassement::PartiesGagnees => Valeur::Nombre(c.parties_gagnees.into()),
+            ColonneClassement::PartiesPerdues => Valeur::Nombre(c.parties_perdues.into()),
+            ColonneClassement::NumeroClub => Valeur::Texte(c.numero.clone()),
+        }
+    }
+}
+
+/// exporte la liste des joueurs au format CSV, avec les colonnes données
+pub fn joueurs_csv(joueurs: &[Joueur], colonnes: &[ColonneJoueur], langue: Langue) -> String {
+    csv(
+        colonnes.iter().map(|c| c.entete(langue)),
+        joueurs
+            .iter()
+            .map(|j| colonnes.iter().map(|c| c.valeur(j)).collect()),
+        langue,
+    )
+}
+
+/// exporte le classement d'une poule au format CSV, avec les colonnes données
+pub fn classement_csv(
+    classements: &[Classement],
+    colonnes: &[ColonneClassement],
+    langue: Langue,
+) -> String {
+    csv(
+        colonnes.iter().map(|c| c.entete(langue)),
+        classements
+            .iter()
+            .map(|l| colonnes.iter().map(|c| c.valeur(l)).collect()),
+        langue,
+    )
+}
+
+// écrit un document CSV (RFC 4180) avec le séparateur de la langue
+fn csv<'a>(
+    entetes: impl Iterator<Item = &'a str>,
+    lignes: impl Iterator<Item = Vec<Valeur>>,
+    langue: Langue,
+) -> String {
+    let separateur = langue.separateur();
+    let champ = |texte: &str| {
+        if texte.contains([separateur, '"', '\n', '\r']) {
+            format!("\"{}\"", texte.replace('"', "\"\""))
+        } else {
+            texte.to_string()
+        }
+    };
+    let mut document = entetes
+        .map(champ)
+        .collect::<Vec<_>>()
+        .join(&separateur.to_string());
+    document.push_str("\r\n");
+    for ligne in lignes {
+        let ligne: Vec<String> = ligne.iter().map(|v| champ(&v.csv(langue))).collect();
+        document.push_str(&ligne.join(&separateur.to_string()));
+        document.push_str("\r\n");
+    }
+    document
+}
+
+#[cfg(any(feature = "xlsx", feature = "ods"))]
+// une feuille de classeur : son nom et ses lignes, la première étant l'en-tête,
+// chacune indiquant si elle est en gras
+struct Feuille {
+    nom: String,
+    lignes: Vec<(Vec<Valeur>, bool)>,
+}
+
+#[cfg(any(feature = "xlsx", feature = "ods"))]
+// les feuilles d'un classeur : les joueurs du club, puis une feuille par équipe
+// avec le classement de sa poule, où la ligne de l'équipe est en gras
+fn feuilles(
+    joueurs: &[Joueur],
+    colonnes_joueurs: &[ColonneJoueur],
+    equipes: &[(Equipe, Vec<Classement>)],
+    colonnes_classement: &[ColonneClassement],
+    langue: Langue,
+) -> Vec<Feuille> {
+    let entete = |entetes: Vec<&str>| {
+        let valeurs = entetes
+            .into_iter()
+            .map(|e| Valeur::Texte(e.to_string()))
+            .collect();
+        (valeurs, true)
+    };
+    let mut noms = HashSet::new();
+
+    let mut lignes = vec![entete(
+        colonnes_joueurs.iter().map(|c| c.entete(langue)).collect(),
+    )];
+    lignes.extend(joueurs.iter().map(|j| {
+        (
+            colonnes_joueurs.iter().map(|c| c.valeur(j)).collect(),
+            false,
+        )
+    }));
+    let nom = match langue {
+        Langue::Francais => "Joueurs",
+        Langue::Anglais => "Players",
+    };
+    let mut feuilles = vec![Feuille {
+        nom: nom_feuille(nom, &mut noms),
+        lignes,
+    }];
+
+    for (equipe, classements) in equipes {
+        let mut lignes = vec![entete(
+            colonnes_classement
+                .iter()
+                .map(|c| c.entete(langue))
+                .collect(),
+        )];
+        lignes.extend(classements.iter().map(|c| {
+            let valeurs = colonnes_classement
+                .iter()
+                .map(|col| col.valeur(c))
+                .collect();
+            (valeurs, equipe.est_nommee(&c.equipe))
+        }));
+        feuilles.push(Feuille {
+            nom: nom_feuille(equipe.nom_rencontre(), &mut noms),
+            lignes,
+        });
+    }
+    feuilles
+}
+
+#[cfg(feature = "xlsx")]
+/// exporte un classeur `.xlsx` contenant une feuille des joueurs du club,
+/// puis une feuille par équipe avec le classement de sa poule, où la ligne de l'équipe est en gras
+pub fn classeur(
+    joueurs: &[Joueur],
+    colonnes_joueurs: &[ColonneJoueur],
+    equipes: &[(Equipe, Vec<Classement>)],
+    colonnes_classement: &[ColonneClassement],
+    langue: Langue,
+) -> Result<Vec<u8>, rust_xlsxwriter::XlsxError> {
+    use rust_xlsxwriter::{Format, Workbook};
+
+    let gras = Format::new().set_bold();
+    let normal = Format::new();
+    let mut classeur = Workbook::new();
+    for f in feuilles(
+        joueurs,
+        colonnes_joueurs,
+        equipes,
+        colonnes_classement,
+        langue,
+    ) {
+        let feuille = classeur.add_worksheet();
+        feuille.set_name(f.nom)?;
+        for (ligne, (valeurs, en_gras)) in (0u32..).zip(&f.lignes) {
+            let format = if *en_gras { &gras } else { &normal };
+            for (colonne, v) in (0u16..).zip(valeurs) {
+                match v {
+                    Valeur::Texte(t) => {
+                        feuille.write_string_with_format(ligne, colonne, t, format)?
+                    }
+                    Valeur::Nombre(n) => {
+                        feuille.write_number_with_format(ligne, colonne, *n, format)?
+                    }
+                };
+            }
+        }
+        feuille.set_freeze_panes(1, 0)?;
+    }
+    classeur.save_to_buffer()
+}
+
+#[cfg(feature = "ods")]
+/// exporte un classeur OpenDocument `.ods` contenant les mêmes feuilles que [`classeur`]
+pub fn classeur_ods(
+    joueurs: &[Joueur],
+    colonnes_joueurs: &[ColonneJoueur],
+    equipes: &[(Equipe, Vec<Classement>)],
+    colonnes_classement: &[ColonneClassement],
+    langue: Langue,
+) -> zip::result::ZipResult<Vec<u8>> {
+    use std::fmt::Write as _;
+    use std::io::Write as _;
+    use zip::write::SimpleFileOptions;
+    use zip::CompressionMethod;
+
+    let mut contenu = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+        r#"<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0""#,
+        r#" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0""#,
+        r#" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0""#,
+        r#" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0""#,
+        r#" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" office:version="1.2">"#,
+        r#"<office:automatic-styles><style:style style:name="gras" style:family="table-cell">"#,
+        r#"<style:text-properties fo:font-weight="bold"/></style:style></office:automatic-styles>"#,
+        "<office:body><office:spreadsheet>",
+    ));
+    for f in feuilles(
+        joueurs,
+        colonnes_joueurs,
+        equipes,
+        colonnes_classement,
+        langue,
+    ) {
+        let _ = write!(
+            contenu,
+            "<table:table table:name=\"{}\">",
+            echapper_xml(&f.nom)
+        );
+        for (valeurs, en_gras) in &f.lignes {
+            let style = if *en_gras {
+                " table:style-name=\"gras\""
+            } else {
+                ""
+            };
+            contenu.push_str("<table:table-row>");
+            for v in valeurs {
+                let _ = match v {
+                    Valeur::Texte(t) => write!(
+                        contenu,
+                        "<table:table-cell{style} office:value-type=\"string\"><text:p>{}</text:p></table:table-cell>",
+                        echapper_xml(t)
+                    ),
+                    Valeur::Nombre(n) => write!(
+                        contenu,
+                        "<table:table-cell{style} office:value-type=\"float\" office:value=\"{n}\"><text:p>{n}</text:p></table:table-cell>"
+                    ),
+                };
+            }
+            contenu.push_str("</table:table-row>");
+        }
+        contenu.push_str("</table:table>");
+    }
+    contenu.push_str("</office:spreadsheet></office:body></office:document-content>");
+
+    let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    // le type MIME est le premier fichier de l'archive, sans compression
+    archive.start_file(
+        "mimetype",
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+    )?;
+    archive.write_all(b"application/vnd.oasis.opendocument.spreadsheet")?;
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    archive.start_file("META-INF/manifest.xml", options)?;
+    archive.write_all(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            r#"<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.2">"#,
+            r#"<manifest:file-entry manifest:full-path="/" manifest:version="1.2" manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/>"#,
+            r#"<manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>"#,
+            "</manifest:manifest>",
+        )
+        .as_bytes(),
+    )?;
+    archive.start_file("content.xml", options)?;
+    archive.write_all(contenu.as_bytes())?;
+    Ok(archive.finish()?.into_inner())
+}
+
+#[cfg(any(feature = "xlsx", feature = "ods"))]
+// un nom de feuille valide (31 caractères au plus, sans caractère interdit) et unique dans le classeur
+fn nom_feuille(nom: &str, noms: &mut HashSet<String>) -> String {
+    let base: String = nom
+        .chars()
+        .map(|c| if "[]:*?/\\".contains(c) { '_' } else { c })
+        .take(31)
+        .collect();
+    let mut candidat = base.clone();
+    let mut n = 2;
+    while !noms.insert(candidat.to_lowercase()) {
+        let suffixe = format!(" ({n})");
+        candidat = base
+            .chars()
+            .take(31 - suffixe.chars().count())
+            .chain(suffixe.chars())
+            .collect();
+        n += 1;
+    }
+    candidat
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joueur() -> Joueur {
+        Joueur {
+            licence: "12345".to_string(),
+            nom: "DOE".to_string(),
+            prenom: "John; Junior".to_string(),
+            points_init: 1000.0,
+            point: 1100.0,
+            r#_virtual: 1150.3,
+            categorie: Some("V1".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_joueurs_csv() {
+        let csv = joueurs_csv(
+            &[joueur()],
+            &[
+                ColonneJoueur::Nom,
+                ColonneJoueur::Prenom,
+                ColonneJoueur::Categorie,
+                ColonneJoueur::ProgressionPhase,
+            ],
+            Langue::Francais,
+        );
+        assert_eq!(
+            csv,
+            "Nom;Prénom;Catégorie;Progression phase\r\nDOE;\"John; Junior\";V1;50,3\r\n"
+        );
+        let csv = joueurs_csv(
+            &[joueur()],
+            &[ColonneJoueur::Licence, ColonneJoueur::PointsVirtuels],
+            Langue::Anglais,
+        );
+        assert_eq!(csv, "Licence,Virtual points\r\n12345,1150.3\r\n");
+    }
+
+    #[test]
+    fn test_classement_csv() {
+        let classement: Classement = serde_json::from_value(serde_json::json!({
+            "numero_poule": 3,
+            "classement": 1,
+            "equipe": "MONTPELLIER TT 3",
+            "joues": 2,
+            "points": 6,
+            "victoires": 2,
+            "defaites": 0,
+            "nuls": 0,
+            "forfaits": 0,
+            "parties_gagnees": 20,
+            "parties_perdues": 8,
+            "numero": "11340010"
+        }))
+        .unwrap();
+        let csv = classement_csv(&[classement], &ColonneClassement::TOUTES, Langue::Anglais);
+        let lignes: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lignes[0],
+            "Rank,Team,Played,Points,Wins,Draws,Losses,Forfeits,Games won,Games lost,Club number"
+        );
+        assert_eq!(lignes[1], "1,MONTPELLIER TT 3,2,6,2,0,0,0,20,8,11340010");
+    }
+
+    #[cfg(any(feature = "xlsx", feature = "ods"))]
+    #[test]
+    fn test_nom_feuille() {
+        let mut noms = HashSet::new();
+        assert_eq!(nom_feuille("A/B", &mut noms), "A_B");
+        assert_eq!(nom_feuille("a/b", &mut noms), "a_b (2)");
+        let long = nom_feuille(&"X".repeat(40), &mut noms);
+        assert_eq!(long.chars().count(), 31);
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn test_classeur() {
+        let classeur = classeur(
+            &[joueur()],
+            &ColonneJoueur::TOUTES,
+            &[],
+            &ColonneClassement::TOUTES,
+            Langue::Francais,
+        )
+        .unwrap();
+        assert!(classeur.starts_with(b"PK"));
+    }
+
+    #[cfg(feature = "ods")]
+    #[test]
+    fn test_classeur_ods() {
+        use std::io::Read;
+
+        let classeur = classeur_ods(
+            &[joueur()],
+            &[ColonneJoueur::Nom, ColonneJoueur::ProgressionPhase],
+            &[],
+            &ColonneClassement::TOUTES,
+            Langue::Francais,
+        )
+        .unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(classeur)).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+        let mut contenu = String::new();
+        archive
+            .by_name("content.xml")
+            .unwrap()
+            .read_to_string(&mut contenu)
+            .unwrap();
+        assert!(contenu.contains("<table:table table:name=\"Joueurs\">"));
+        assert!(contenu.contains("office:value=\"50.3\""));
+        assert!(contenu.contains(
+            "<table:table-cell table:style-name=\"gras\" office:value-type=\"string\"><text:p>Nom</text:p>"
+        ));
+    }
+}
@@ -0,0 +1,284 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// la feuille de match détaillée d'une rencontre
+pub struct FeuilleDeMatch {
+    /// nom de l'équipe A (receveur)
+    pub equipea: String,
+    /// nom de l'équipe B (extérieur)
+    pub equipeb: String,
+    /// score final de l'équipe A
+    pub scorea: Option<u8>,
+    /// score final de l'équipe B
+    pub scoreb: Option<u8>,
+    /// composition de l'équipe A
+    pub joueursa: Vec<JoueurFeuille>,
+    /// composition de l'équipe B
+    pub joueursb: Vec<JoueurFeuille>,
+    /// l'ensemble des parties (simples et doubles) de la rencontre
+    pub parties: Vec<PartieFeuille>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// un joueur inscrit sur la feuille de match
+pub struct JoueurFeuille {
+    /// nom et prénom du joueur
+    pub nom: String,
+    /// numéro de licence, s'il est fourni par l'API
+    pub licence: Option<String>,
+    /// points du joueur au moment de la rencontre
+    pub points: Option<u16>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une partie individuelle ou de double disputée lors de la rencontre
+pub struct PartieFeuille {
+    /// joueur(s) de l'équipe A
+    pub joueura: String,
+    /// joueur(s) de l'équipe B
+    pub joueurb: String,
+    /// indique si la partie est un double
+    pub double: bool,
+    /// vainqueur de la partie : `Some(true)` si le joueur A gagne, `None` si non disputée
+    pub victoirea: Option<bool>,
+    /// le détail des manches
+    pub manches: Vec<Manche>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+/// une manche d'une partie
+pub struct Manche {
+    /// indique si la manche est gagnée par le joueur A
+    pub gagneea: bool,
+    /// points marqués par le perdant de la manche
+    pub points_perdant: u8,
+}
+
+impl Manche {
+    /// retourne le score de la manche sous la forme (points de A, points de B)
+    pub fn score(&self) -> (u8, u8) {
+        let gagnant = if self.points_perdant >= 10 {
+            self.points_perdant + 2
+        } else {
+            11
+        };
+        if self.gagneea {
+            (gagnant, self.points_perdant)
+        } else {
+            (self.points_perdant, gagnant)
+        }
+    }
+}
+
+impl FeuilleDeMatch {
+    /// créé la feuille de match à partir du document XML retourné par l'API
+    pub fn from_xml(xml: &str) -> Result<FeuilleDeMatch, quick_xml::DeError> {
+        let doc: DocumentFeuille = quick_xml::de::from_str(xml)?;
+        Ok(doc.into())
+    }
+
+    /// retourne le bilan (victoires, défaites) en simple d'un joueur sur la rencontre
+    pub fn bilan(&self, nom: &str) -> (u8, u8) {
+        self.parties
+            .iter()
+            .filter(|p| !p.double)
+            .fold((0, 0), |(v, d), p| match p.victoirea {
+                Some(victoirea) if p.joueura == nom => {
+                    if victoirea {
+                        (v + 1, d)
+                    } else {
+                        (v, d + 1)
+                    }
+                }
+                Some(victoirea) if p.joueurb == nom => {
+                    if victoirea {
+                        (v, d + 1)
+                    } else {
+                        (v + 1, d)
+                    }
+                }
+                _ => (v, d),
+            })
+    }
+
+    /// retourne le nombre de manches gagnées par chaque équipe (A, B)
+    pub fn manches(&self) -> (u16, u16) {
+        self.parties
+            .iter()
+            .flat_map(|p| &p.manches)
+            .fold(
+                (0, 0),
+                |(a, b), m| {
+                    if m.gagneea {
+                        (a + 1, b)
+                    } else {
+                        (a, b + 1)
+                    }
+                },
+            )
+    }
+}
+
+impl From<DocumentFeuille> for FeuilleDeMatch {
+    fn from(doc: DocumentFeuille) -> Self {
+        let mut joueursa = vec![];
+        let mut joueursb = vec![];
+        for j in doc.joueur {
+            if !j.xja.is_empty() {
+                joueursa.push(JoueurFeuille {
+                    points: points_classement(&j.xca),
+                    nom: j.xja,
+                    licence: j.xla.filter(|l| !l.is_empty()),
+                });
+            }
+            if !j.xjb.is_empty() {
+                joueursb.push(JoueurFeuille {
+                    points: points_classement(&j.xcb),
+                    nom: j.xjb,
+                    licence: j.xlb.filter(|l| !l.is_empty()),
+                });
+            }
+        }
+        let parties = doc
+            .partie
+            .into_iter()
+            .map(|p| PartieFeuille {
+                double: p.ja.contains(" et ") || p.jb.contains(" et "),
+                victoirea: match (p.scorea.trim(), p.scoreb.trim()) {
+                    ("1", _) => Some(true),
+                    (_, "1") => Some(false),
+                    _ => None,
+                },
+                manches: p
+                    .detail
+                    .split_whitespace()
+                    .filter_map(parse_manche)
+                    .collect(),
+                joueura: p.ja,
+                joueurb: p.jb,
+            })
+            .collect();
+        FeuilleDeMatch {
+            scorea: doc.resultat.resa.trim().parse().ok(),
+            scoreb: doc.resultat.resb.trim().parse().ok(),
+            equipea: doc.resultat.equa,
+            equipeb: doc.resultat.equb,
+            joueursa,
+            joueursb,
+            parties,
+        }
+    }
+}
+
+// extrait les points d'un classement de la forme "M 1245pts"
+fn points_classement(classement: &str) -> Option<u16> {
+    classement
+        .split_whitespace()
+        .find_map(|x| x.strip_suffix("pts"))
+        .and_then(|x| x.parse().ok())
+}
+
+// une manche est notée par les points du perdant, précédés d'un "-" si elle est perdue par A
+fn parse_manche(s: &str) -> Option<Manche> {
+    let (gagneea, points) = match s.strip_prefix('-') {
+        Some(points) => (false, points),
+        None => (true, s),
+    };
+    Some(Manche {
+        gagneea,
+        points_perdant: points.parse().ok()?,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentFeuille {
+    // le résultat global de la rencontre
+    resultat: ResultatFeuille,
+    // les joueurs des deux équipes, ligne par ligne
+    #[serde(default)]
+    joueur: Vec<LigneJoueurs>,
+    // les parties disputées
+    #[serde(default)]
+    partie: Vec<LignePartie>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultatFeuille {
+    equa: String,
+    equb: String,
+    #[serde(default)]
+    resa: String,
+    #[serde(default)]
+    resb: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LigneJoueurs {
+    #[serde(default)]
+    xja: String,
+    #[serde(default)]
+    xca: String,
+    #[serde(default)]
+    xla: Option<String>,
+    #[serde(default)]
+    xjb: String,
+    #[serde(default)]
+    xcb: String,
+    #[serde(default)]
+    xlb: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LignePartie {
+    #[serde(default)]
+    ja: String,
+    #[serde(default)]
+    scorea: String,
+    #[serde(default)]
+    jb: String,
+    #[serde(default)]
+    scoreb: String,
+    #[serde(default)]
+    detail: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEUILLE: &str = r#"<?xml version="1.0" encoding="ISO-8859-1"?>
+<liste>
+    <resultat><equa>MONTPELLIER TT 1</equa><equb>NIMES ASPTT 2</equb><resa>8</resa><resb>6</resb></resultat>
+    <joueur><xja>LEBRUN Felix</xja><xca>M 3540pts</xca><xla>3421810</xla><xjb>DUPONT Jean</xjb><xcb>M 1845pts</xcb><xlb>3012345</xlb></joueur>
+    <joueur><xja>MARTIN Paul</xja><xca>M 1520pts</xca><xjb>DURAND Luc</xjb><xcb>M 1610pts</xcb></joueur>
+    <partie><ja>LEBRUN Felix</ja><scorea>1</scorea><jb>DUPONT Jean</jb><scoreb>0</scoreb><detail>3 5 -0</detail></partie>
+    <partie><ja>MARTIN Paul</ja><scorea>0</scorea><jb>DURAND Luc</jb><scoreb>1</scoreb><detail>-9 12 -8 -13</detail></partie>
+    <partie><ja>LEBRUN F. et MARTIN P.</ja><scorea>1</scorea><jb>DUPONT J. et DURAND L.</jb><scoreb>0</scoreb><detail>7 8 9</detail></partie>
+</liste>"#;
+
+    #[test]
+    fn test_from_xml() {
+        let feuille = FeuilleDeMatch::from_xml(FEUILLE).unwrap();
+        assert_eq!(feuille.equipea, "MONTPELLIER TT 1");
+        assert_eq!(feuille.scorea, Some(8));
+        assert_eq!(feuille.scoreb, Some(6));
+        assert_eq!(feuille.joueursa.len(), 2);
+        assert_eq!(feuille.joueursa[0].licence.as_deref(), Some("3421810"));
+        assert_eq!(feuille.joueursa[0].points, Some(3540));
+        assert_eq!(feuille.joueursb[1].licence, None);
+        assert_eq!(feuille.parties.len(), 3);
+        assert!(feuille.parties[2].double);
+        assert_eq!(feuille.parties[0].manches[2].score(), (0, 11));
+        assert_eq!(feuille.parties[1].manches[3].score(), (13, 15));
+        assert_eq!(feuille.manches(), (6, 4));
+    }
+
+    #[test]
+    fn test_bilan() {
+        let feuille = FeuilleDeMatch::from_xml(FEUILLE).unwrap();
+        assert_eq!(feuille.bilan("LEBRUN Felix"), (1, 0));
+        assert_eq!(feuille.bilan("DURAND Luc"), (1, 0));
+        assert_eq!(feuille.bilan("MARTIN Paul"), (0, 1));
+    }
+}
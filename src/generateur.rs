@@ -0,0 +1,448 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::HashMap;
+
+use crate::classement;
+use crate::club::Club;
+use crate::date::Date;
+use crate::equipe::Equipe;
+use crate::joueur::Joueur;
+use crate::journee::{Journee, Match};
+use crate::poule::{Classement, Poule};
+use crate::prediction::{self, points_partie, Format};
+use crate::simulateur::{Donnees, EquipeSimulee, JoueurSimule, PouleSimulee};
+use crate::simulation::SplitMix64;
+use crate::tour::{Bareme, Rencontre};
+
+const VILLES: [&str; 16] = [
+    "ALBI", "ANGERS", "BESANCON", "BREST", "CAEN", "COLMAR", "DIJON", "GAP", "LAVAL", "LIMOGES",
+    "METZ", "NIORT", "PAU", "REIMS", "TROYES", "VANNES",
+];
+
+const NOMS: [&str; 16] = [
+    "BERNARD", "DUBOIS", "DURAND", "FAURE", "FONTAINE", "GARNIER", "GIRARD", "LAMBERT", "LEROY",
+    "MARTIN", "MOREAU", "PETIT", "RICHARD", "ROUSSEAU", "SIMON", "THOMAS",
+];
+
+const PRENOMS: [&str; 16] = [
+    "Alice", "Camille", "Chloé", "Emma", "Inès", "Léa", "Manon", "Sarah", "Arthur", "Hugo",
+    "Jules", "Louis", "Lucas", "Nathan", "Paul", "Théo",
+];
+
+const CATEGORIES: [&str; 8] = ["C2", "M1", "J1", "J3", "S", "S", "V1", "V3"];
+
+const DIVISIONS: [&str; 6] = [
+    "Nationale 3",
+    "Régionale 1",
+    "Régionale 2",
+    "Régionale 3",
+    "Départementale 1",
+    "Départementale 2",
+];
+
+#[derive(Clone, Debug)]
+/// les paramètres de génération d'un jeu de données
+pub struct Parametres {
+    /// graine du générateur aléatoire : une même graine produit le même jeu de données
+    pub graine: u64,
+    /// nombre de clubs
+    pub clubs: usize,
+    /// nombre de joueurs par club
+    pub joueurs_par_club: usize,
+    /// nombre d'équipes par club, l'équipe 1 jouant dans la division la plus haute
+    pub equipes_par_club: usize,
+    /// nombre maximal d'équipes par poule
+    pub equipes_par_poule: usize,
+    /// nombre de tours déjà joués, les suivants n'ont pas encore de résultat
+    pub tours_joues: usize,
+    /// année de début de la saison
+    pub annee: u16,
+    /// format des rencontres
+    pub format: Format,
+}
+
+impl Default for Parametres {
+    fn default() -> Self {
+        Parametres {
+            graine: 0x5EED,
+            clubs: 8,
+            joueurs_par_club: 12,
+            equipes_par_club: 2,
+            equipes_par_poule: 8,
+            tours_joues: 7,
+            annee: 2024,
+            format: Format::default(),
+        }
+    }
+}
+
+// répartit les clubs dans le plus petit nombre de poules d'au plus `maximum` équipes,
+// les tailles des poules ne différant que d'une équipe au plus
+fn repartir(clubs: &[usize], maximum: usize) -> impl Iterator<Item = &[usize]> {
+    let poules = clubs.len().div_ceil(maximum.max(2));
+    let (taille, reste) = (
+        clubs.len().checked_div(poules).unwrap_or_default(),
+        clubs.len().checked_rem(poules).unwrap_or_default(),
+    );
+    (0..poules).map(move |p| {
+        let debut = p * taille + p.min(reste);
+        &clubs[debut..debut + taille + usize::from(p < reste)]
+    })
+}
+
+/// génère un jeu de données cohérent, sans données personnelles réelles
+///
+/// Les équipes de même rang des différents clubs sont réparties en poules d'une même division.
+/// Les parties des tours joués sont tirées selon les points des joueurs, l'historique de chaque
+/// joueur et ses points virtuels suivent la grille fédérale et le classement de chaque poule
+/// correspond à ses rencontres.
+pub fn generer(parametres: &Parametres) -> Donnees {
+    let mut alea = SplitMix64(parametres.graine);
+    let mut donnees = Donnees::default();
+
+    // les joueurs de chaque club, du mieux classé au moins bien classé
+    let mut effectifs: Vec<Vec<usize>> = vec![];
+    for c in 0..parametres.clubs {
+        let club = club(c, &mut alea);
+        let mut effectif = vec![];
+        for j in 0..parametres.joueurs_par_club {
+            let points = (500.0 + 1500.0 * alea.suivant() * alea.suivant()).round() as f32;
+            effectif.push(donnees.joueurs.len());
+            donnees.joueurs.push(JoueurSimule {
+                joueur: Joueur {
+                    licence: format!("9{c:03}{j:03}"),
+                    nom: choisir(&NOMS, &mut alea).to_string(),
+                    prenom: choisir(&PRENOMS, &mut alea).to_string(),
+                    points_init: points,
+                    point: points,
+                    r#_virtual: points,
+                    categorie: Some(choisir(&CATEGORIES, &mut alea).to_string()),
+                },
+                club: club.numero.clone(),
+                journees: vec![],
+            });
+        }
+        effectif.sort_by(|a, b| {
+            let points = |i: &usize| donnees.joueurs[*i].joueur.points_init;
+            points(b).total_cmp(&points(a))
+        });
+        effectifs.push(effectif);
+        donnees.clubs.push(club);
+    }
+
+    let taille_equipe = parametres
+        .format
+        .simples
+        .iter()
+        .map(|(a, b)| a.max(b) + 1)
+        .max()
+        .unwrap_or_default();
+    let bareme = Bareme::default();
+    for niveau in 0..parametres.equipes_par_club {
+        let division = DIVISIONS
+            .get(niveau)
+            .map(|d| d.to_string())
+            .unwrap_or_else(|| format!("Départementale {}", niveau - DIVISIONS.len() + 3));
+        // les clubs ayant assez de joueurs pour aligner l'équipe
+        let clubs: Vec<usize> = (0..parametres.clubs)
+            .filter(|c| effectifs[*c].len() >= (niveau + 1) * taille_equipe)
+            .collect();
+        for (p, membres) in repartir(&clubs, parametres.equipes_par_poule).enumerate() {
+            let poule = Poule {
+                division: format!("{}", 1000 + niveau),
+                numero: format!("{}", 2000 + 100 * niveau + p),
+            };
+            // une équipe : son nom, son club et ses joueurs
+            let equipes: Vec<(String, usize, Vec<usize>)> = membres
+                .iter()
+                .map(|c| {
+                    let joueurs = effectifs[*c][niveau * taille_equipe..][..taille_equipe].to_vec();
+                    (
+                        format!("{} {}", donnees.clubs[*c].nom, niveau + 1),
+                        *c,
+                        joueurs,
+                    )
+                })
+                .collect();
+            for (nom, c, _) in &equipes {
+                donnees.equipes.push(EquipeSimulee {
+                    equipe: Equipe {
+                        id: format!("{}{}", donnees.clubs[*c].numero, niveau + 1),
+                        nom: nom.clone(),
+                        _epreuve: "FED_Championnat de France par Equipes Mixte".to_string(),
+                        _division: format!("{division} Poule {}", p + 1),
+                        poule: poule.clone(),
+                    },
+                    club: donnees.clubs[*c].numero.clone(),
+                });
+            }
+
+            let mut rencontres = vec![];
+            for (t, appariements) in calendrier(equipes.len()).into_iter().enumerate() {
+                let date = date_tour(parametres.annee, t);
+                for (a, b) in appariements {
+                    let mut rencontre = Rencontre {
+                        tour: u8::try_from(t + 1).expect("Une poule compte moins de 255 tours"),
+                        a: equipes[a].0.clone(),
+                        b: equipes[b].0.clone(),
+                        scorea: None,
+                        scoreb: None,
+                        forfait: None,
+                        date: date.clone(),
+                        datereelle: None,
+                        lien: String::new(),
+                    };
+                    if t < parametres.tours_joues {
+                        let (scorea, scoreb) = jouer(
+                            &mut donnees.joueurs,
+                            &equipes[a].2,
+                            &equipes[b].2,
+                            &date,
+                            &parametres.format,
+                            &mut alea,
+                        );
+                        rencontre.scorea = Some(scorea);
+                        rencontre.scoreb = Some(scoreb);
+                    }
+                    rencontres.push(rencontre);
+                }
+            }
+
+            let numeros: HashMap<&str, &str> = equipes
+                .iter()
+                .map(|(nom, c, _)| (nom.as_str(), donnees.clubs[*c].numero.as_str()))
+                .collect();
+            let classements = classement::calculer(&rencontres, &bareme)
+                .into_iter()
+                .map(|l| Classement {
+                    numero_poule: u8::try_from(p + 1).unwrap_or(u8::MAX),
+                    classement: l.rang,
+                    numero: numeros[l.equipe.as_str()].to_string(),
+                    equipe: l.equipe,
+                    joues: l.joues,
+                    points: u8::try_from(l.points).unwrap_or(u8::MAX),
+                    victoires: l.victoires,
+                    defaites: l.defaites,
+                    nuls: l.nuls,
+                    forfaits: l.forfaits,
+                    parties_gagnees: u8::try_from(l.parties_gagnees).unwrap_or(u8::MAX),
+                    parties_perdues: u8::try_from(l.parties_perdues).unwrap_or(u8::MAX),
+                })
+                .collect();
+            donnees.poules.push(PouleSimulee {
+                poule,
+                rencontres,
+                classements,
+            });
+        }
+    }
+
+    for j in &mut donnees.joueurs {
+        j.journees.sort();
+    }
+    donnees
+}
+
+// retourne un élément tiré au hasard
+fn choisir<'a>(elements: &[&'a str], alea: &mut SplitMix64) -> &'a str {
+    elements[(alea.suivant() * elements.len() as f64) as usize]
+}
+
+// génère le club d'indice donné, avec sa salle
+fn club(indice: usize, alea: &mut SplitMix64) -> Club {
+    let ville = VILLES[indice % VILLES.len()];
+    let nom = if indice < VILLES.len() {
+        format!("TT {ville}")
+    } else {
+        format!("TT {ville} {}", indice / VILLES.len() + 1)
+    };
+    // une position en France métropolitaine, la latitude étant supérieure à la longitude
+    let latitude = 43.0 + 6.0 * alea.suivant();
+    let longitude = -1.0 + 8.0 * alea.suivant();
+    Club {
+        numero: format!("0999{indice:04}"),
+        nom,
+        nomsalle: format!("Gymnase {}", choisir(&NOMS, alea)),
+        adressesalle1: format!("{} rue du Sport", 1 + (alea.suivant() * 99.0) as u8),
+        adressesalle2: String::new(),
+        adressesalle3: String::new(),
+        codepsalle: format!("{:02}000", 1 + indice % 95),
+        villesalle: ville.to_string(),
+        latitude: format!("{latitude:.6}"),
+        longitude: format!("{longitude:.6}"),
+    }
+}
+
+// calendrier aller d'une poule par la méthode du cercle : les appariements de chaque tour
+fn calendrier(equipes: usize) -> Vec<Vec<(usize, usize)>> {
+    // une équipe fictive exempte une équipe à chaque tour lorsque leur nombre est impair
+    let n = equipes + equipes % 2;
+    let mut ordre: Vec<usize> = (0..n).collect();
+    let mut tours = vec![];
+    for t in 0..n.saturating_sub(1) {
+        let mut appariements = vec![];
+        for i in 0..n / 2 {
+            let (a, b) = (ordre[i], ordre[n - 1 - i]);
+            if a >= equipes || b >= equipes {
+                continue;
+            }
+            // alterne la réception entre les tours
+            appariements.push(if (t + i) % 2 == 0 { (a, b) } else { (b, a) });
+        }
+        tours.push(appariements);
+        ordre[1..].rotate_right(1);
+    }
+    tours
+}
+
+// date du tour d'indice donné : un samedi sur deux à partir de fin septembre
+fn date_tour(annee: u16, tour: usize) -> Date {
+    let mut debut = NaiveDate::from_ymd_opt(i32::from(annee), 9, 20).expect("Date valide");
+    while debut.weekday() != Weekday::Sat {
+        debut += Duration::days(1);
+    }
+    let date = debut + Duration::weeks(2 * tour as i64);
+    Date {
+        annee: u16::try_from(date.year()).expect("Année valide"),
+        mois: date.month() as u8,
+        jour: date.day() as u8,
+    }
+}
+
+// joue une rencontre entre deux équipes, en mettant à jour l'historique et les points
+// virtuels de leurs joueurs, et retourne le score
+fn jouer(
+    joueurs: &mut [JoueurSimule],
+    a: &[usize],
+    b: &[usize],
+    date: &Date,
+    format: &Format,
+    alea: &mut SplitMix64,
+) -> (u8, u8) {
+    let points = |joueurs: &[JoueurSimule], i: usize| joueurs[i].joueur.point;
+    let mut matchs: HashMap<usize, Vec<Match>> = HashMap::new();
+    let (mut scorea, mut scoreb) = (0, 0);
+    for (ia, ib) in &format.simples {
+        let (ja, jb) = (a[*ia], b[*ib]);
+        let (pa, pb) = (points(joueurs, ja), points(joueurs, jb));
+        let victoire = alea.suivant() < prediction::probabilite(f64::from(pa), f64::from(pb));
+        if victoire {
+            scorea += 1;
+        } else {
+            scoreb += 1;
+        }
+        for (j, adversaire, p, pa, gagne) in
+            [(ja, jb, pa, pb, victoire), (jb, ja, pb, pa, !victoire)]
+        {
+            let ex = points_partie(p, pa, gagne);
+            let adversaire = &joueurs[adversaire].joueur;
+            matchs.entry(j).or_default().push(Match {
+                nom: format!("{} {}", adversaire.nom, adversaire.prenom),
+                coeff: 1.0,
+                ex,
+            });
+        }
+    }
+    // les doubles ne comptent pas pour les points des joueurs
+    for ((a1, a2), (b1, b2)) in &format.doubles {
+        let paire = |equipe: &[usize], i: usize, j: usize| {
+            f64::from(points(joueurs, equipe[i]) + points(joueurs, equipe[j])) / 2.0
+        };
+        let probabilite = prediction::probabilite(paire(a, *a1, *a2), paire(b, *b1, *b2));
+        if alea.suivant() < probabilite {
+            scorea += 1;
+        } else {
+            scoreb += 1;
+        }
+    }
+    for (j, matchs) in matchs {
+        let journee = Journee::new(date.clone(), matchs);
+        joueurs[j].joueur.r#_virtual += journee.ex();
+        joueurs[j].journees.push(journee);
+    }
+    (scorea, scoreb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendrier() {
+        for n in 2..10 {
+            let tours = calendrier(n);
+            let mut rencontres: Vec<(usize, usize)> = tours
+                .iter()
+                .flatten()
+                .map(|(a, b)| (*a.min(b), *a.max(b)))
+                .collect();
+            rencontres.sort();
+            rencontres.dedup();
+            // chaque équipe rencontre une fois chacune des autres
+            assert_eq!(rencontres.len(), n * (n - 1) / 2);
+            for tour in &tours {
+                let mut equipes: Vec<usize> = tour.iter().flat_map(|(a, b)| [*a, *b]).collect();
+                equipes.sort();
+                equipes.dedup();
+                assert_eq!(equipes.len(), 2 * tour.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_repartir() {
+        let clubs: Vec<usize> = (0..9).collect();
+        let tailles: Vec<usize> = repartir(&clubs, 8).map(<[usize]>::len).collect();
+        assert_eq!(tailles, vec![5, 4]);
+        let tailles: Vec<usize> = repartir(&clubs, 4).map(<[usize]>::len).collect();
+        assert_eq!(tailles, vec![3, 3, 3]);
+        assert_eq!(repartir(&clubs, 1).count(), 5);
+        assert_eq!(repartir(&clubs, 1).flatten().count(), 9);
+        assert_eq!(repartir(&[], 8).count(), 0);
+    }
+
+    #[test]
+    fn test_generer() {
+        let parametres = Parametres {
+            clubs: 10,
+            tours_joues: 4,
+            ..Parametres::default()
+        };
+        let donnees = generer(&parametres);
+        assert_eq!(donnees, generer(&parametres));
+        assert_ne!(
+            donnees,
+            generer(&Parametres {
+                graine: 1,
+                ..parametres.clone()
+            })
+        );
+        assert_eq!(donnees.clubs.len(), 10);
+        assert_eq!(donnees.joueurs.len(), 120);
+        // 2 niveaux de 10 équipes : deux poules de 5 par niveau
+        assert_eq!(donnees.equipes.len(), 20);
+        assert_eq!(donnees.poules.len(), 4);
+
+        let bareme = Bareme::default();
+        for p in &donnees.poules {
+            let calcule = classement::calculer(&p.rencontres, &bareme);
+            assert!(classement::ecarts(&calcule, &p.classements).is_empty());
+            assert!(p.rencontres.iter().all(|r| r.tour > 4 || r.est_jouee()));
+            assert!(p.rencontres.iter().all(|r| r.tour <= 4 || !r.est_jouee()));
+        }
+        let poule = &donnees.poules[0];
+        assert_eq!(poule.classements.len(), 5);
+        assert!(poule
+            .rencontres
+            .iter()
+            .filter(|r| r.est_jouee())
+            .all(|r| r.scorea.unwrap() + r.scoreb.unwrap() == 14));
+
+        for j in &donnees.joueurs {
+            let ex: f32 = j.journees.iter().map(Journee::ex).sum();
+            assert_eq!(j.joueur.r#_virtual, j.joueur.point + ex);
+            assert!(j.journees.len() <= 4);
+        }
+        let json = serde_json::to_string(&donnees).unwrap();
+        assert_eq!(serde_json::from_str::<Donnees>(&json).unwrap(), donnees);
+    }
+}
@@ -0,0 +1,388 @@
+use chrono::NaiveDate;
+use std::fmt::Write;
+
+use crate::classement;
+use crate::date::Date;
+use crate::echapper_xml;
+use crate::joueur::Joueur;
+use crate::partie::Partie;
+use crate::tour::{Bareme, Tour};
+
+/// les couleurs attribuées successivement aux séries
+const COULEURS: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+/// les dimensions d'un graphique, en pixels
+pub struct Dimensions {
+    /// largeur totale
+    pub largeur: u32,
+    /// hauteur totale
+    pub hauteur: u32,
+    /// marge autour de la zone de tracé, où sont écrits le titre et les axes
+    pub marge: u32,
+}
+
+impl Default for Dimensions {
+    fn default() -> Self {
+        Dimensions {
+            largeur: 800,
+            hauteur: 400,
+            marge: 60,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// l'évolution du rang d'une équipe dans sa poule
+pub struct Positions {
+    /// nom de l'équipe
+    pub equipe: String,
+    /// le rang de l'équipe à l'issue de chaque tour, sous la forme (tour, rang)
+    pub rangs: Vec<(u8, u8)>,
+}
+
+/// retourne les points virtuels du joueur à l'issue de chacune de ses journées
+///
+/// Les points sont reconstitués à rebours depuis ses points virtuels actuels,
+/// en retranchant les points gagnés lors des journées suivantes.
+pub fn historique_points(joueur: &Joueur, partie: &Partie) -> Vec<(Date, f32)> {
+    let mut journees = partie.journees.clone();
+    journees.sort();
+    let mut points = joueur.r#_virtual;
+    let mut historique: Vec<(Date, f32)> = journees
+        .iter()
+        .rev()
+        .map(|j| {
+            let apres = points;
+            points -= j.ex();
+            (j.date.clone(), apres)
+        })
+        .collect();
+    historique.reverse();
+    historique
+}
+
+/// calcule le rang de chaque équipe de la poule à l'issue de chaque tour
+pub fn positions_par_tour(tours: &[(u8, Tour)], bareme: &Bareme) -> Vec<Positions> {
+    let mut tours: Vec<&(u8, Tour)> = tours.iter().collect();
+    tours.sort_by_key(|(numero, _)| *numero);
+    let mut positions: Vec<Positions> = vec![];
+    let mut rencontres = vec![];
+    for (numero, tour) in tours {
+        if !tour.rencontres.rencontres.iter().any(|r| r.est_jouee()) {
+            continue;
+        }
+        rencontres.extend(tour.rencontres.rencontres.iter().cloned());
+        for ligne in classement::calculer(&rencontres, bareme) {
+            match positions.iter_mut().find(|p| p.equipe == ligne.equipe) {
+                Some(p) => p.rangs.push((*numero, ligne.rang)),
+                None => positions.push(Positions {
+                    equipe: ligne.equipe,
+                    rangs: vec![(*numero, ligne.rang)],
+                }),
+            }
+        }
+    }
+    positions
+}
+
+/// trace au format SVG la courbe des points d'un joueur au cours du temps
+pub fn courbe_points(points: &[(Date, f32)], titre: &str, dimensions: &Dimensions) -> String {
+    let mut svg = entete(titre, dimensions);
+    let jours: Vec<(i64, f32, &Date)> = points
+        .iter()
+        .filter_map(|(d, p)| Some((jour(d)?, *p, d)))
+        .collect();
+    if jours.is_empty() {
+        svg.push_str("</svg>\n");
+        return svg;
+    }
+    let (xmin, xmax) = bornes(jours.iter().map(|(j, _, _)| *j as f64));
+    let (ymin, ymax) = bornes(jours.iter().map(|(_, p, _)| f64::from(*p)));
+    // on arrondit l'axe des points à la dizaine
+    let (ymin, ymax) = ((ymin / 10.0).floor() * 10.0, (ymax / 10.0).ceil() * 10.0);
+    let zone = Zone::new(dimensions, (xmin, xmax), (ymin, ymax));
+
+    zone.axes(&mut svg);
+    for i in 0..=4 {
+        let valeur = ymin + (ymax - ymin) * f64::from(i) / 4.0;
+        let y = zone.y(valeur);
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{:.1}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="#ddd"/><text x="{:.1}" y="{:.1}" text-anchor="end" font-size="12">{valeur:.0}</text>"##,
+            zone.gauche,
+            zone.droite,
+            zone.gauche - 5.0,
+            y + 4.0
+        );
+    }
+    let premier = jours[0].2;
+    let dernier = jours[jours.len() - 1].2;
+    for (date, ancre, x) in [
+        (premier, "start", zone.gauche),
+        (dernier, "end", zone.droite),
+    ] {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{x:.1}" y="{:.1}" text-anchor="{ancre}" font-size="12">{date}</text>"#,
+            zone.bas + 18.0
+        );
+    }
+
+    let trace: Vec<String> = jours
+        .iter()
+        .map(|(j, p, _)| format!("{:.1},{:.1}", zone.x(*j as f64), zone.y(f64::from(*p))))
+        .collect();
+    let _ = writeln!(
+        svg,
+        r#"<polyline fill="none" stroke="{}" stroke-width="2" points="{}"/>"#,
+        COULEURS[0],
+        trace.join(" ")
+    );
+    for (j, p, date) in &jours {
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="{}"><title>{date} : {p:.0}</title></circle>"#,
+            zone.x(*j as f64),
+            zone.y(f64::from(*p)),
+            COULEURS[0]
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// trace au format SVG l'évolution du rang des équipes d'une poule au fil des tours
+pub fn courbe_classements(positions: &[Positions], titre: &str, dimensions: &Dimensions) -> String {
+    let mut svg = entete(titre, dimensions);
+    let tours: Vec<u8> = positions
+        .iter()
+        .flat_map(|p| p.rangs.iter().map(|(t, _)| *t))
+        .collect();
+    let equipes = positions
+        .iter()
+        .flat_map(|p| p.rangs.iter().map(|(_, r)| *r))
+        .max()
+        .unwrap_or(1);
+    if tours.is_empty() {
+        svg.push_str("</svg>\n");
+        return svg;
+    }
+    let (xmin, xmax) = bornes(tours.iter().map(|t| f64::from(*t)));
+    // le premier est en haut : l'axe des rangs est inversé
+    let mut zone = Zone::new(dimensions, (xmin, xmax), (f64::from(equipes), 1.0));
+    // on réserve la marge droite au nom des équipes
+    zone.droite -= f64::from(dimensions.largeur) / 5.0;
+
+    zone.axes(&mut svg);
+    for tour in xmin as u8..=xmax as u8 {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" font-size="12">T{tour}</text>"#,
+            zone.x(f64::from(tour)),
+            zone.bas + 18.0
+        );
+    }
+    for rang in 1..=equipes {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="end" font-size="12">{rang}</text>"#,
+            zone.gauche - 5.0,
+            zone.y(f64::from(rang)) + 4.0
+        );
+    }
+    for (i, p) in positions.iter().enumerate() {
+        let couleur = COULEURS[i % COULEURS.len()];
+        let trace: Vec<String> = p
+            .rangs
+            .iter()
+            .map(|(t, r)| format!("{:.1},{:.1}", zone.x(f64::from(*t)), zone.y(f64::from(*r))))
+            .collect();
+        let _ = writeln!(
+            svg,
+            r#"<polyline fill="none" stroke="{couleur}" stroke-width="3" points="{}"><title>{}</title></polyline>"#,
+            trace.join(" "),
+            echapper_xml(&p.equipe)
+        );
+        if let Some((t, r)) = p.rangs.last() {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" font-size="12" fill="{couleur}">{}</text>"#,
+                zone.x(f64::from(*t)) + 8.0,
+                zone.y(f64::from(*r)) + 4.0,
+                echapper_xml(&p.equipe)
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// la zone de tracé et la conversion des valeurs en coordonnées
+struct Zone {
+    gauche: f64,
+    droite: f64,
+    haut: f64,
+    bas: f64,
+    x: (f64, f64),
+    y: (f64, f64),
+}
+
+impl Zone {
+    fn new(dimensions: &Dimensions, x: (f64, f64), y: (f64, f64)) -> Self {
+        let marge = f64::from(dimensions.marge);
+        Zone {
+            gauche: marge,
+            droite: f64::from(dimensions.largeur) - marge / 2.0,
+            haut: marge,
+            bas: f64::from(dimensions.hauteur) - marge,
+            x,
+            y,
+        }
+    }
+
+    fn x(&self, valeur: f64) -> f64 {
+        self.gauche + echelle(valeur, self.x) * (self.droite - self.gauche)
+    }
+
+    fn y(&self, valeur: f64) -> f64 {
+        self.bas - echelle(valeur, self.y) * (self.bas - self.haut)
+    }
+
+    fn axes(&self, svg: &mut String) {
+        let _ = writeln!(
+            svg,
+            r##"<path d="M{:.1},{:.1} V{:.1} H{:.1}" fill="none" stroke="#333"/>"##,
+            self.gauche, self.haut, self.bas, self.droite
+        );
+    }
+}
+
+// position relative de la valeur entre les bornes, au milieu si les bornes sont égales
+fn echelle(valeur: f64, (min, max): (f64, f64)) -> f64 {
+    if (max - min).abs() < f64::EPSILON {
+        0.5
+    } else {
+        (valeur - min) / (max - min)
+    }
+}
+
+fn bornes(valeurs: impl Iterator<Item = f64>) -> (f64, f64) {
+    valeurs.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+        (min.min(v), max.max(v))
+    })
+}
+
+fn entete(titre: &str, dimensions: &Dimensions) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{l}" height="{h}" viewBox="0 0 {l} {h}" font-family="sans-serif">
+<rect width="100%" height="100%" fill="white"/>
+<text x="{}" y="{}" text-anchor="middle" font-size="16">{}</text>
+"#,
+        dimensions.largeur / 2,
+        dimensions.marge / 2,
+        echapper_xml(titre),
+        l = dimensions.largeur,
+        h = dimensions.hauteur,
+    )
+}
+
+// le nombre de jours depuis l'origine du calendrier
+fn jour(date: &Date) -> Option<i64> {
+    NaiveDate::from_ymd_opt(date.annee.into(), date.mois.into(), date.jour.into())
+        .map(|d| i64::from(chrono::Datelike::num_days_from_ce(&d)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tour::Rencontres;
+
+    const RENCONTRES: &str = r#"<liste>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>A</equa><equb>B</equb><scorea>10</scorea><scoreb>4</scoreb><dateprevue>28/09/2024</dateprevue></tour>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>C</equa><equb>D</equb><scorea>8</scorea><scoreb>6</scoreb><dateprevue>28/09/2024</dateprevue></tour>
+    <tour><libelle>Poule 3 - tour n°2 du 12/10/2024</libelle><equa>B</equa><equb>C</equb><scorea>12</scorea><scoreb>2</scoreb><dateprevue>12/10/2024</dateprevue></tour>
+    <tour><libelle>Poule 3 - tour n°2 du 12/10/2024</libelle><equa>D</equa><equb>A</equb><scorea>5</scorea><scoreb>9</scoreb><dateprevue>12/10/2024</dateprevue></tour>
+    <tour><libelle>Poule 3 - tour n°3 du 09/11/2024</libelle><equa>A</equa><equb>C</equb><dateprevue>09/11/2024</dateprevue></tour>
+</liste>"#;
+
+    fn tours() -> Vec<(u8, Tour)> {
+        let rencontres = Rencontres::from_xml(RENCONTRES).unwrap().rencontres;
+        (1..=3)
+            .map(|numero| {
+                let rencontres: Vec<_> = rencontres
+                    .iter()
+                    .filter(|r| r.tour == numero)
+                    .cloned()
+                    .collect();
+                (
+                    numero,
+                    Tour {
+                        numero,
+                        date: rencontres[0].date.clone(),
+                        rencontres: Rencontres { rencontres },
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_historique_points() {
+        let joueur = Joueur {
+            licence: "12345".to_string(),
+            nom: "DOE".to_string(),
+            prenom: "John".to_string(),
+            points_init: 1000.0,
+            point: 1000.0,
+            r#_virtual: 1015.0,
+            categorie: None,
+        };
+        let partie: Partie = serde_json::from_value(serde_json::json!({
+            "journees": [
+                {"date": "12/10/2024", "matchs": [{"nom": "X", "coeff": 1.0, "ex": -5.0}]},
+                {"date": "28/09/2024", "matchs": [
+                    {"nom": "Y", "coeff": 1.0, "ex": 12.0},
+                    {"nom": "Z", "coeff": 1.0, "ex": 8.0}
+                ]}
+            ]
+        }))
+        .unwrap();
+        let historique = historique_points(&joueur, &partie);
+        assert_eq!(historique.len(), 2);
+        assert_eq!(historique[0].0.to_string(), "28/09/2024");
+        assert_eq!(historique[0].1, 1020.0);
+        assert_eq!(historique[1].1, 1015.0);
+
+        let svg = courbe_points(&historique, "John DOE", &Dimensions::default());
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains("<polyline"));
+        assert!(svg.contains("<title>28/09/2024 : 1020</title>"));
+    }
+
+    #[test]
+    fn test_positions_par_tour() {
+        let positions = positions_par_tour(&tours(), &Bareme::default());
+        let rangs = |equipe: &str| {
+            positions
+                .iter()
+                .find(|p| p.equipe == equipe)
+                .unwrap()
+                .rangs
+                .clone()
+        };
+        // le tour 3 n'est pas joué
+        assert_eq!(rangs("A"), vec![(1, 1), (2, 1)]);
+        assert_eq!(rangs("B"), vec![(1, 4), (2, 2)]);
+        assert_eq!(rangs("D"), vec![(1, 3), (2, 4)]);
+
+        let svg = courbe_classements(&positions, "Poule 3 <R1>", &Dimensions::default());
+        assert!(svg.contains("Poule 3 &lt;R1&gt;"));
+        assert_eq!(svg.matches("<polyline").count(), 4);
+        assert!(svg.contains(">T2</text>"));
+    }
+}
@@ -0,0 +1,246 @@
+use chrono::{NaiveDate, Utc};
+use std::fmt::Write;
+
+use crate::calendrier::Evenement;
+use crate::date::Date;
+use crate::tour::{Issue, Rencontre};
+
+/// longueur maximale d'une ligne iCalendar, en octets
+const LONGUEUR_LIGNE: usize = 75;
+
+/// exporte les évènements au format iCalendar (RFC 5545)
+///
+/// Chaque rencontre a un identifiant stable, construit à partir de la poule, du tour et des équipes :
+/// un calendrier abonné met à jour l'évènement existant lorsque sa date change.
+pub fn exporter(evenements: &[Evenement], nom: &str) -> String {
+    let horodatage = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lignes = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//libfftt//Calendrier FFTT//FR".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", echapper(nom)),
+    ];
+    for e in evenements {
+        lignes.extend(evenement(e, &horodatage));
+    }
+    lignes.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for l in lignes {
+        ics.push_str(&plier(&l));
+    }
+    ics
+}
+
+/// exporte au format iCalendar les seuls évènements de l'équipe donnée
+pub fn exporter_equipe(evenements: &[Evenement], equipe: &str) -> String {
+    let evenements: Vec<Evenement> = evenements
+        .iter()
+        .filter(|e| e.equipe == equipe)
+        .cloned()
+        .collect();
+    exporter(&evenements, equipe)
+}
+
+// les lignes d'un évènement
+fn evenement(e: &Evenement, horodatage: &str) -> Vec<String> {
+    let r = &e.rencontre;
+    let debut = date_ical(&e.date);
+    let fin = NaiveDate::from_ymd_opt(e.date.annee.into(), e.date.mois.into(), e.date.jour.into())
+        .and_then(|d| d.succ_opt())
+        .map(|d| d.format("%Y%m%d").to_string())
+        .unwrap_or_else(|| debut.clone());
+    let sequence = sequence(r);
+
+    let mut description = format!(
+        "{}\n{} - poule {}\nTour n°{}",
+        e.epreuve, e.division, e.poule.numero, e.tour
+    );
+    if let (Some(a), Some(b)) = (r.scorea, r.scoreb) {
+        let _ = write!(description, "\nScore : {a} - {b}");
+    }
+    if matches!(r.issue(), Issue::Reportee) {
+        let _ = write!(
+            description,
+            "\nRencontre reportée (date prévue : {})",
+            r.date
+        );
+    }
+
+    let mut lignes = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid(e)),
+        format!("DTSTAMP:{horodatage}"),
+        format!("DTSTART;VALUE=DATE:{debut}"),
+        format!("DTEND;VALUE=DATE:{fin}"),
+        format!("SEQUENCE:{sequence}"),
+        format!("SUMMARY:{}", echapper(&format!("{} - {}", r.a, r.b))),
+        format!("DESCRIPTION:{}", echapper(&description)),
+    ];
+    if let Some(club) = &e.lieu {
+        let adresse = [
+            club.nomsalle.as_str(),
+            club.adressesalle1.as_str(),
+            club.adressesalle2.as_str(),
+            club.adressesalle3.as_str(),
+            &format!("{} {}", club.codepsalle, club.villesalle),
+        ]
+        .iter()
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+        lignes.push(format!("LOCATION:{}", echapper(&adresse)));
+        if let (Ok(latitude), Ok(longitude)) = (
+            club.latitude.trim().parse::<f64>(),
+            club.longitude.trim().parse::<f64>(),
+        ) {
+            lignes.push(format!("GEO:{latitude};{longitude}"));
+        }
+    }
+    lignes.push("STATUS:CONFIRMED".to_string());
+    lignes.push("TRANSP:OPAQUE".to_string());
+    lignes.push("END:VEVENT".to_string());
+    lignes
+}
+
+// numéro de révision d'une rencontre
+//
+// Aucune révision n'est conservée d'un export à l'autre : la séquence est le nombre de jours
+// entre le 1er janvier 1970 et la date effective de la rencontre (sa date réelle si elle est
+// connue, sinon sa date prévue). Elle est identique d'un export à l'autre tant que la rencontre
+// n'est pas déplacée, et augmente lorsqu'elle est reportée.
+fn sequence(r: &Rencontre) -> i64 {
+    let date = r.datereelle.as_ref().unwrap_or(&r.date);
+    let epoque = NaiveDate::from_ymd_opt(1970, 1, 1).expect("Date valide");
+    NaiveDate::from_ymd_opt(date.annee.into(), date.mois.into(), date.jour.into())
+        .map(|d| (d - epoque).num_days().max(0))
+        .unwrap_or_default()
+}
+
+// identifiant stable d'une rencontre, indépendant de sa date
+fn uid(e: &Evenement) -> String {
+    let r = &e.rencontre;
+    let equipes: String = format!("{}-{}", r.a, r.b)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!(
+        "{}-{}-{}-{}@libfftt",
+        e.poule.division, e.poule.numero, r.tour, equipes
+    )
+}
+
+fn date_ical(date: &Date) -> String {
+    format!("{:04}{:02}{:02}", date.annee, date.mois, date.jour)
+}
+
+// échappe les caractères spéciaux d'une valeur texte
+fn echapper(texte: &str) -> String {
+    texte
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// plie une ligne trop longue en lignes de continuation, sans couper de caractère
+fn plier(ligne: &str) -> String {
+    let mut resultat = String::new();
+    let mut longueur = 0;
+    for c in ligne.chars() {
+        if longueur + c.len_utf8() > LONGUEUR_LIGNE {
+            resultat.push_str("\r\n ");
+            longueur = 1;
+        }
+        resultat.push(c);
+        longueur += c.len_utf8();
+    }
+    resultat.push_str("\r\n");
+    resultat
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::club::Club;
+    use crate::poule::Poule;
+    use crate::tour::Cote;
+
+    fn evenement() -> Evenement {
+        Evenement {
+            equipe: "MONTPELLIER TT 3".to_string(),
+            numero_equipe: Some(3),
+            epreuve: "FED_Championnat de France par Equipes Masculin".to_string(),
+            division: "Régionale 1".to_string(),
+            poule: Poule {
+                division: "5678".to_string(),
+                numero: "1234".to_string(),
+            },
+            tour: 2,
+            date: Date::new(2024, 11, 9),
+            cote: Cote::Exterieur,
+            adversaire: "ALES TT 1".to_string(),
+            lieu: Some(Club {
+                numero: "11300001".to_string(),
+                nom: "ALES TT".to_string(),
+                nomsalle: "Gymnase Jean Moulin".to_string(),
+                adressesalle1: "12, rue des Sports".to_string(),
+                adressesalle2: String::new(),
+                adressesalle3: String::new(),
+                codepsalle: "30100".to_string(),
+                villesalle: "ALES".to_string(),
+                latitude: "44.1250".to_string(),
+                longitude: "4.0810".to_string(),
+            }),
+            rencontre: Rencontre {
+                tour: 2,
+                a: "ALES TT 1".to_string(),
+                b: "MONTPELLIER TT 3".to_string(),
+                scorea: None,
+                scoreb: None,
+                forfait: None,
+                date: Date::new(2024, 10, 12),
+                datereelle: Some(Date::new(2024, 11, 9)),
+                lien: String::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_exporter() {
+        let ics = exporter(&[evenement()], "MONTPELLIER TT");
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("UID:5678-1234-2-ALES_TT_1_MONTPELLIER_TT_3@libfftt\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20241109\r\n"));
+        assert!(ics.contains("DTEND;VALUE=DATE:20241110\r\n"));
+        assert!(ics.contains("SEQUENCE:20036\r\n"));
+        assert!(ics.contains("GEO:44.125;4.081\r\n"));
+        assert!(ics.contains("LOCATION:Gymnase Jean Moulin\\, 12\\, rue des Sports\\, 30100 ALES"));
+        assert!(ics.lines().all(|l| l.len() <= LONGUEUR_LIGNE));
+    }
+
+    #[test]
+    fn test_sequence() {
+        let mut rencontre = evenement().rencontre;
+        // la séquence ne dépend que de la date effective (09/11/2024)
+        assert_eq!(sequence(&rencontre), 20036);
+        // sans date réelle, la date prévue (12/10/2024) est retenue
+        rencontre.datereelle = None;
+        assert_eq!(sequence(&rencontre), 20008);
+        // un report augmente la séquence
+        rencontre.datereelle = Some(Date::new(2024, 11, 23));
+        assert!(sequence(&rencontre) > 20036);
+    }
+
+    #[test]
+    fn test_plier() {
+        let ligne = "é".repeat(50);
+        let plie = plier(&ligne);
+        assert!(plie.split("\r\n").all(|l| l.len() <= LONGUEUR_LIGNE));
+        assert_eq!(plie.replace("\r\n ", "").trim_end(), ligne);
+    }
+}
@@ -0,0 +1,293 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::{fs, io};
+
+use crate::club::Club;
+use crate::joueur::Joueur;
+use crate::poule::{Classement, Poule};
+use crate::tour::Rencontre;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// l'état d'une poule à un instant donné
+pub struct InstantanePoule {
+    /// la poule
+    pub poule: Poule,
+    /// son classement officiel
+    pub classements: Vec<Classement>,
+    /// ses rencontres
+    pub rencontres: Vec<Rencontre>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// l'état des joueurs et des poules d'un club à un instant donné
+pub struct Instantane {
+    /// date et heure de l'instantané, au format RFC 3339
+    pub horodatage: String,
+    /// numéro du club
+    pub club: String,
+    /// les joueurs du club
+    pub joueurs: Vec<Joueur>,
+    /// les poules des équipes du club
+    pub poules: Vec<InstantanePoule>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// l'évolution des points d'un joueur
+pub struct ChangementPoints {
+    /// numéro de licence
+    pub licence: String,
+    /// nom et prénom du joueur
+    pub nom: String,
+    /// points virtuels lors du premier instantané
+    pub avant: f32,
+    /// points virtuels lors du second instantané
+    pub apres: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// le changement de rang d'une équipe dans sa poule
+pub struct Mouvement {
+    /// la poule
+    pub poule: Poule,
+    /// nom de l'équipe
+    pub equipe: String,
+    /// rang lors du premier instantané, None si l'équipe n'était pas classée
+    pub avant: Option<u8>,
+    /// rang lors du second instantané
+    pub apres: u8,
+}
+
+impl Mouvement {
+    /// indique si l'équipe a gagné des places
+    pub fn monte(&self) -> bool {
+        self.avant.is_some_and(|avant| self.apres < avant)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+/// les différences entre deux instantanés
+pub struct Differences {
+    /// les joueurs dont les points virtuels ont changé
+    pub points: Vec<ChangementPoints>,
+    /// les joueurs apparus dans le club
+    pub nouvelles_licences: Vec<Joueur>,
+    /// les joueurs ayant quitté le club
+    pub licences_perdues: Vec<Joueur>,
+    /// les équipes ayant changé de rang
+    pub mouvements: Vec<Mouvement>,
+    /// les rencontres dont le résultat a été saisi entre les deux instantanés
+    pub nouveaux_scores: Vec<Rencontre>,
+}
+
+impl Differences {
+    /// indique si rien n'a changé
+    pub fn est_vide(&self) -> bool {
+        self.points.is_empty()
+            && self.nouvelles_licences.is_empty()
+            && self.licences_perdues.is_empty()
+            && self.mouvements.is_empty()
+            && self.nouveaux_scores.is_empty()
+    }
+}
+
+impl Instantane {
+    /// construit un instantané horodaté à l'heure courante
+    pub fn new(club: &str, joueurs: Vec<Joueur>, poules: Vec<InstantanePoule>) -> Self {
+        Instantane {
+            horodatage: Local::now().to_rfc3339(),
+            club: club.to_string(),
+            joueurs,
+            poules,
+        }
+    }
+
+    /// prend un instantané du club en interrogeant l'API
+    pub async fn prendre(club: &Club) -> Self {
+        log::info!("Instantané du club {}...", club.nom);
+        let joueurs = club.api_joueurs().await;
+        let mut poules: Vec<InstantanePoule> = vec![];
+        for equipe in club.api_equipes().await {
+            if poules.iter().any(|p| p.poule == equipe.poule) {
+                continue;
+            }
+            let rencontres = equipe
+                .poule
+                .get_tours()
+                .await
+                .into_iter()
+                .flat_map(|(_, t)| t.rencontres.rencontres)
+                .collect();
+            poules.push(InstantanePoule {
+                classements: equipe.poule.classement().await,
+                rencontres,
+                poule: equipe.poule,
+            });
+        }
+        Instantane::new(&club.numero, joueurs, poules)
+    }
+
+    /// enregistre l'instantané au format JSON
+    pub fn enregistrer(&self, chemin: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(chemin, serde_json::to_string_pretty(self)?)
+    }
+
+    /// charge un instantané enregistré au format JSON
+    pub fn charger(chemin: impl AsRef<Path>) -> io::Result<Instantane> {
+        Ok(serde_json::from_str(&fs::read_to_string(chemin)?)?)
+    }
+
+    /// calcule les différences entre cet instantané et un instantané plus récent
+    pub fn differences(&self, recent: &Instantane) -> Differences {
+        let mut differences = Differences::default();
+        for j in &recent.joueurs {
+            match self.joueurs.iter().find(|a| a.licence == j.licence) {
+                None => differences.nouvelles_licences.push(j.clone()),
+                Some(a) if a.r#_virtual != j.r#_virtual => {
+                    differences.points.push(ChangementPoints {
+                        licence: j.licence.clone(),
+                        nom: j.display(),
+                        avant: a.r#_virtual,
+                        apres: j.r#_virtual,
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        differences.licences_perdues = self
+            .joueurs
+            .iter()
+            .filter(|a| !recent.joueurs.iter().any(|j| j.licence == a.licence))
+            .cloned()
+            .collect();
+
+        for p in &recent.poules {
+            let ancienne = self.poules.iter().find(|a| a.poule == p.poule);
+            for c in &p.classements {
+                let avant = ancienne
+                    .and_then(|a| a.classements.iter().find(|x| x.equipe == c.equipe))
+                    .map(|x| x.classement);
+                if avant != Some(c.classement) {
+                    differences.mouvements.push(Mouvement {
+                        poule: p.poule.clone(),
+                        equipe: c.equipe.clone(),
+                        avant,
+                        apres: c.classement,
+                    });
+                }
+            }
+            for r in p.rencontres.iter().filter(|r| r.est_jouee()) {
+                let deja_jouee = ancienne.is_some_and(|a| {
+                    a.rencontres
+                        .iter()
+                        .any(|x| x.tour == r.tour && x.a == r.a && x.b == r.b && x.est_jouee())
+                });
+                if !deja_jouee {
+                    differences.nouveaux_scores.push(r.clone());
+                }
+            }
+        }
+        differences
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tour::Rencontres;
+
+    const AVANT: &str = r#"<liste>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>A</equa><equb>B</equb><scorea>10</scorea><scoreb>4</scoreb><dateprevue>28/09/2024</dateprevue></tour>
+    <tour><libelle>Poule 3 - tour n°2 du 12/10/2024</libelle><equa>B</equa><equb>A</equb><dateprevue>12/10/2024</dateprevue></tour>
+</liste>"#;
+
+    const APRES: &str = r#"<liste>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>A</equa><equb>B</equb><scorea>10</scorea><scoreb>4</scoreb><dateprevue>28/09/2024</dateprevue></tour>
+    <tour><libelle>Poule 3 - tour n°2 du 12/10/2024</libelle><equa>B</equa><equb>A</equb><scorea>12</scorea><scoreb>2</scoreb><dateprevue>12/10/2024</dateprevue></tour>
+</liste>"#;
+
+    fn joueur(licence: &str, virtuel: f32) -> Joueur {
+        Joueur {
+            licence: licence.to_string(),
+            nom: licence.to_string(),
+            prenom: String::new(),
+            points_init: 1000.0,
+            point: 1000.0,
+            r#_virtual: virtuel,
+            categorie: None,
+        }
+    }
+
+    fn classement(equipe: &str, rang: u8) -> Classement {
+        serde_json::from_value(serde_json::json!({
+            "numero_poule": 3,
+            "classement": rang,
+            "equipe": equipe,
+            "joues": 1,
+            "points": 3,
+            "victoires": 1,
+            "defaites": 0,
+            "nuls": 0,
+            "forfaits": 0,
+            "parties_gagnees": 9,
+            "parties_perdues": 5,
+            "numero": "11340010"
+        }))
+        .unwrap()
+    }
+
+    fn instantane(
+        joueurs: Vec<Joueur>,
+        classements: Vec<Classement>,
+        rencontres: &str,
+    ) -> Instantane {
+        Instantane::new(
+            "11340010",
+            joueurs,
+            vec![InstantanePoule {
+                poule: Poule {
+                    division: "5678".to_string(),
+                    numero: "1234".to_string(),
+                },
+                classements,
+                rencontres: Rencontres::from_xml(rencontres).unwrap().rencontres,
+            }],
+        )
+    }
+
+    #[test]
+    fn test_differences() {
+        let avant = instantane(
+            vec![joueur("1", 1000.0), joueur("2", 1000.0), joueur("3", 800.0)],
+            vec![classement("A", 1), classement("B", 2)],
+            AVANT,
+        );
+        let apres = instantane(
+            vec![joueur("1", 1012.5), joueur("2", 1000.0), joueur("4", 500.0)],
+            vec![classement("B", 1), classement("A", 2)],
+            APRES,
+        );
+        assert!(avant.differences(&avant).est_vide());
+
+        let d = avant.differences(&apres);
+        assert_eq!(d.points.len(), 1);
+        assert_eq!(d.points[0].licence, "1");
+        assert_eq!(d.points[0].apres, 1012.5);
+        assert_eq!(d.nouvelles_licences[0].licence, "4");
+        assert_eq!(d.licences_perdues[0].licence, "3");
+        assert_eq!(d.mouvements.len(), 2);
+        assert!(d.mouvements.iter().any(|m| m.equipe == "B" && m.monte()));
+        assert_eq!(d.nouveaux_scores.len(), 1);
+        assert_eq!(d.nouveaux_scores[0].tour, 2);
+    }
+
+    #[test]
+    fn test_enregistrer() {
+        let instantane = instantane(vec![joueur("1", 1000.0)], vec![classement("A", 1)], AVANT);
+        let chemin = std::env::temp_dir().join(format!("libfftt-{}.json", std::process::id()));
+        instantane.enregistrer(&chemin).unwrap();
+        let charge = Instantane::charger(&chemin).unwrap();
+        fs::remove_file(&chemin).unwrap();
+        assert_eq!(charge, instantane);
+    }
+}
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{partie::Partie, API};
+use crate::partie::{Partie, PartieApi};
+use crate::{api, telecharger, ErreurApi};
 
 #[derive(Debug)]
 pub enum JoueurError {
@@ -10,7 +11,7 @@ pub enum JoueurError {
     AucunePartie,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 /// Représente un joueur
 pub struct Joueur {
     /// numéro de licence
@@ -20,22 +21,59 @@ pub struct Joueur {
     /// prénom du joueur
     pub prenom: String,
     // ses points de début de saison
-    #[serde(rename = "initm")]
     pub points_init: f32,
     // ses points en début de phase
     pub point: f32,
     // ses points actuels
-    #[serde(rename = "virtual")]
+    #[serde(rename = "virtuel")]
     pub r#_virtual: f32,
+    /// catégorie d'âge du joueur (S, V1, J2...), si elle est connue
+    #[serde(default)]
+    pub categorie: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+// un joueur tel que retourné par l'API
+struct JoueurApi {
+    licence: String,
+    nom: String,
+    prenom: String,
+    initm: f32,
+    point: f32,
+    #[serde(rename = "virtual")]
+    virtuel: f32,
+    #[serde(default)]
+    cat: Option<String>,
+}
+
+impl From<JoueurApi> for Joueur {
+    fn from(j: JoueurApi) -> Self {
+        Joueur {
+            licence: j.licence,
+            nom: j.nom,
+            prenom: j.prenom,
+            points_init: j.initm,
+            point: j.point,
+            r#_virtual: j.virtuel,
+            categorie: j.cat.filter(|c| !c.is_empty()),
+        }
+    }
 }
 
 impl Joueur {
     /// créé le joueur en récupérant les données depuis SPID
     /// licence représente le numéro de licence du joueur
     pub async fn new(licence: &str) -> Result<Joueur, JoueurError> {
+        Self::try_new(licence)
+            .await
+            .map_err(|_| JoueurError::NotFound)
+    }
+
+    /// créé le joueur comme [`Joueur::new`], en retournant le détail de l'erreur éventuelle
+    pub async fn try_new(licence: &str) -> Result<Joueur, ErreurApi> {
         let j = Self::api_joueur(licence).await.map_err(|e| {
             log::error!("Erreur de récupération du joueur N°{licence} : {e}");
-            JoueurError::NotFound
+            e
         })?;
 
         log::debug!("Joueur N°{licence}: {}", j.display());
@@ -46,37 +84,46 @@ impl Joueur {
         format!("{} {}", self.prenom, self.nom)
     }
 
+    /// progression des points virtuels depuis le début de la phase
+    pub fn progression_phase(&self) -> f32 {
+        self.r#_virtual - self.point
+    }
+
+    /// progression des points virtuels depuis le début de la saison
+    pub fn progression_saison(&self) -> f32 {
+        self.r#_virtual - self.points_init
+    }
+
     /// récupère un joueur via l'API à partir de son numéro de licence
-    async fn api_joueur(licence: &str) -> Result<Joueur, reqwest::Error> {
-        let request_url = format!("{API}/joueur/{licence}");
-        let response = reqwest::get(&request_url).await?;
-        let response = response.text().await?;
-        let joueur =
-            serde_json::from_str(&response).expect("Erreur lors de la désérialisation du joueur");
-        Ok(joueur)
+    async fn api_joueur(licence: &str) -> Result<Joueur, ErreurApi> {
+        let api = api();
+        let request_url = format!("{api}/joueur/{licence}");
+        let response = telecharger(&request_url).await?;
+        let joueur: JoueurApi = serde_json::from_str(&response)?;
+        Ok(joueur.into())
     }
 
     /// récupère les parties du joueur
     pub async fn api_parties(&self) -> Option<Partie> {
-        let request_url = format!("{API}/parties/{}", self.licence);
-        let response = match reqwest::get(&request_url).await {
-            Ok(r) => r,
-            Err(e) => {
-                log::error!("Impossible de récupérer les parties : {e}");
-                return None;
-            }
-        };
-        let response = match response.text().await {
-            Ok(r) => r,
-            Err(e) => {
-                log::error!("Impossible de récupérer les parties : {e}");
-                return None;
-            }
-        };
-        let parties: HashMap<String, Vec<Partie>> =
-            serde_json::from_str(&response).expect("Erreur lors de la désérialisation des parties");
-        let parties = Partie::sum_parties(&parties["list"]);
-        parties.regrouper_journees()
+        self.try_api_parties().await.unwrap_or_else(|e| {
+            log::error!("Impossible de récupérer les parties : {e}");
+            None
+        })
+    }
+
+    /// récupère les parties du joueur, en retournant l'erreur éventuelle
+    pub async fn try_api_parties(&self) -> Result<Option<Partie>, ErreurApi> {
+        let api = api();
+        let request_url = format!("{api}/parties/{}", self.licence);
+        let response = telecharger(&request_url).await?;
+        let mut parties: HashMap<String, Vec<PartieApi>> = serde_json::from_str(&response)?;
+        let parties: Vec<Partie> = parties
+            .remove("list")
+            .ok_or_else(|| ErreurApi::Document("liste des parties absente".to_string()))?
+            .into_iter()
+            .map(Partie::from)
+            .collect();
+        Ok(Partie::sum_parties(&parties).regrouper_journees())
     }
 }
 
@@ -100,12 +147,15 @@ mod tests {
         }"#;
 
         // Remplacez `reqwest::get` par un mock dans les tests réels.
-        let joueur: Joueur = serde_json::from_str(joueur_data).unwrap();
+        let joueur: Joueur = serde_json::from_str::<JoueurApi>(joueur_data)
+            .unwrap()
+            .into();
         assert_eq!(joueur.nom, "Doe");
         assert_eq!(joueur.prenom, "John");
         assert_eq!(joueur.points_init, 1000.0);
         assert_eq!(joueur.point, 1100.0);
         assert_eq!(joueur.r#_virtual, 1200.0);
+        assert_eq!(joueur.categorie, None);
     }
 
     #[tokio::test]
@@ -142,9 +192,25 @@ mod tests {
             ]
         }"#;
 
-        let parties: HashMap<String, Vec<Partie>> = serde_json::from_str(partie_data).unwrap();
+        let mut parties: HashMap<String, Vec<PartieApi>> =
+            serde_json::from_str(partie_data).unwrap();
         assert!(parties.contains_key("list"));
-        assert_eq!(parties["list"].len(), 2);
+        let parties: Vec<Partie> = parties
+            .remove("list")
+            .unwrap()
+            .into_iter()
+            .map(Partie::from)
+            .collect();
+        assert_eq!(parties.len(), 2);
+        let partie = Partie::sum_parties(&parties);
+        assert_eq!(partie.journees.len(), 1);
+        assert_eq!(partie.journees[0].matchs().len(), 2);
+        assert_eq!(partie.journees[0].ex(), -1.0);
+        // les champs propres à l'API ne figurent pas dans le schéma de la bibliothèque
+        let json = serde_json::to_value(&partie).unwrap();
+        assert!(json.get("processed").is_none());
+        assert!(json.get("num_matchs").is_none());
+        assert!(json["journees"][0].get("ex").is_none());
     }
 
     #[tokio::test]
@@ -167,7 +233,27 @@ mod tests {
             points_init: 1000.0,
             point: 1100.0,
             r#_virtual: 1200.0,
+            categorie: None,
         };
         assert_eq!(joueur.display(), "John Doe");
+        assert_eq!(joueur.progression_phase(), 100.0);
+        assert_eq!(joueur.progression_saison(), 200.0);
+    }
+
+    #[test]
+    fn test_serde() {
+        let joueur = Joueur {
+            licence: "12345".to_string(),
+            nom: "Doe".to_string(),
+            prenom: "John".to_string(),
+            points_init: 1000.0,
+            point: 1100.0,
+            r#_virtual: 1200.5,
+            categorie: Some("V1".to_string()),
+        };
+        let json = serde_json::to_value(&joueur).unwrap();
+        assert_eq!(json["points_init"], 1000.0);
+        assert_eq!(json["virtuel"], 1200.5);
+        assert_eq!(serde_json::from_value::<Joueur>(json).unwrap(), joueur);
     }
 }
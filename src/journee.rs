@@ -1,19 +1,22 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::ops::Add;
 
 use crate::date::Date;
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 /// Une journée représente un ensemble de matchs à une date donnée
 pub struct Journee {
     /// la date de la rencontre
     pub date: Date,
+    /// le libellé de l'épreuve (ex : "FED_Championnat de France par Equipes Masculin"), s'il est connu
+    #[serde(default)]
+    pub epreuve: Option<String>,
     /// l'ensemble des matchs individuels
     matchs: Vec<Match>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 /// représente une match individuel
 pub struct Match {
     /// le nom de l'adversaire
@@ -24,13 +27,65 @@ pub struct Match {
     pub ex: f32,
 }
 
+#[derive(Debug, Deserialize)]
+// une journée telle que retournée par l'API
+pub(crate) struct JourneeApi {
+    date: Date,
+    #[serde(default)]
+    epreuve: Option<String>,
+    #[serde(default)]
+    matchs: Vec<MatchApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// un match tel que retourné par l'API
+struct MatchApi {
+    nom: String,
+    coeff: f32,
+    ex: f32,
+}
+
+impl From<JourneeApi> for Journee {
+    fn from(j: JourneeApi) -> Self {
+        Journee {
+            date: j.date,
+            epreuve: j.epreuve,
+            matchs: j.matchs.into_iter().map(Match::from).collect(),
+        }
+    }
+}
+
+impl From<MatchApi> for Match {
+    fn from(m: MatchApi) -> Self {
+        Match {
+            nom: m.nom,
+            coeff: m.coeff,
+            ex: m.ex,
+        }
+    }
+}
+
 impl Journee {
+    /// créé la journée à partir de ses matchs
+    pub fn new(date: Date, matchs: Vec<Match>) -> Self {
+        Journee {
+            date,
+            epreuve: None,
+            matchs,
+        }
+    }
+
     /// indique si une rencontre fait partie de l'année donnée
     pub fn est_saison(&self, annee: u16) -> bool {
         (self.date.annee == annee && self.date.mois >= 9)
             || (self.date.annee - 1 == annee && self.date.mois <= 6)
     }
 
+    /// retourne les matchs individuels de la journée
+    pub fn matchs(&self) -> &[Match] {
+        &self.matchs
+    }
+
     /// calcule les points obtenus durant une rencontre
     pub fn ex(&self) -> f32 {
         self.matchs.iter().fold(0.0, |x, y| x + y.ex)
@@ -39,7 +94,7 @@ impl Journee {
 
 impl Ord for Journee {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.date.cmp(&other.date)
     }
 }
 
@@ -47,7 +102,7 @@ impl Eq for Journee {}
 
 impl PartialOrd for Journee {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.date.cmp(&other.date))
+        Some(self.cmp(other))
     }
 }
 
@@ -63,6 +118,7 @@ impl Add for Journee {
     fn add(self, other: Journee) -> Self {
         Journee {
             date: self.date,
+            epreuve: self.epreuve.or(other.epreuve),
             matchs: self.matchs.into_iter().chain(other.matchs).collect(),
         }
     }
@@ -74,24 +74,222 @@
 //! # })
 //! ```
 //!
+//! # Schéma JSON
+//! Tous les types publics implémentent `Serialize` et `Deserialize` selon un schéma propre à la
+//! bibliothèque, indépendant du format des API interrogées : un objet sérialisé peut être
+//! stocké puis relu à l'identique, quelles que soient les évolutions des API.
+//!
+//! Les règles du schéma sont les suivantes :
+//! * les champs portent le nom du champ Rust, à l'exception de `Joueur::_virtual` (`virtuel`),
+//!   `Equipe::_epreuve` (`epreuve`) et `Equipe::_division` (`division`) ;
+//! * les dates sont des chaînes au format `AAAA-MM-JJ` ;
+//! * les énumérations sont sérialisées par le nom de leur variante (ex : `"Domicile"`) ;
+//! * les valeurs absentes (`Option`) sont sérialisées en `null`.
+//!
+//! ```
+//! use libfftt::joueur::Joueur;
+//!
+//! let json = r#"{
+//!     "licence": "3421810",
+//!     "nom": "LEBRUN",
+//!     "prenom": "Felix",
+//!     "points_init": 3540.0,
+//!     "point": 3540.0,
+//!     "virtuel": 3562.5
+//! }"#;
+//! let felix: Joueur = serde_json::from_str(json).unwrap();
+//! assert_eq!(felix.r#_virtual, 3562.5);
+//! ```
+//!
+/// Actualités de la fédération et leur export RSS et Atom
+pub mod actualite;
+/// Règles de brûlage des joueurs en championnat par équipes
+pub mod brulage;
+/// Calendrier des rencontres d'un club
+pub mod calendrier;
+/// Calcul local du classement d'une poule
+pub mod classement;
 /// Club
 pub mod club;
+/// Composition des équipes lors des rencontres
+pub mod composition;
+/// Critérium fédéral : divisions, groupes et classements individuels
+pub mod criterium;
 /// Objet date permettant les comparaisons
 pub mod date;
+/// Catalogue des épreuves, divisions et poules d'un organisme
+pub mod epreuve;
 /// Équipe en compétition
 pub mod equipe;
+/// Export des joueurs et classements aux formats CSV et tableur
+pub mod export;
+/// Feuille de match détaillée d'une rencontre
+pub mod feuille;
+/// Génération de jeux de données fictifs pour le simulateur
+#[cfg(feature = "mock")]
+pub mod generateur;
+/// Graphiques SVG de l'évolution des points et des classements
+pub mod graphique;
+/// Export des calendriers au format iCalendar
+pub mod ical;
+/// Instantanés des joueurs et classements d'un club et leurs différences
+pub mod instantane;
 /// Joueur
 pub mod joueur;
 /// Journée de compétition
 pub mod journee;
+/// Proposition de compositions d'équipes
+pub mod optimisation;
+/// Organismes de la fédération (zones, ligues, comités)
+pub mod organisme;
 /// Ensemble des rencontres d'une saison
 pub mod partie;
 /// Poule de l'équipe en compétition
 pub mod poule;
+/// Prédiction des résultats et force des équipes
+pub mod prediction;
+/// Rapport HTML et Markdown d'un club
+pub mod rapport;
+/// Passerelle HTTP/JSON exposant la bibliothèque
+#[cfg(feature = "server")]
+pub mod serveur;
+/// Serveur simulant l'API FFTT à partir d'un jeu de données
+#[cfg(feature = "mock")]
+pub mod simulateur;
+/// Simulation de fin de phase
+pub mod simulation;
+/// Stockage local des données dans une base SQLite
+#[cfg(feature = "store")]
+pub mod store;
+/// Surveillance des résultats et notification des changements
+pub mod surveillance;
 /// Tour d'une phase
 pub mod tour;
+/// Tournois homologués, leurs tableaux et leurs résultats
+pub mod tournoi;
 /// Base des endpoints d'API
 pub const API: &str = "http://fftt.dafunker.com/v1";
+/// Variable d'environnement remplaçant la base des endpoints d'API
+pub const VARIABLE_API: &str = "LIBFFTT_API";
+
+// base des endpoints d'API définie par l'application pour tout le processus
+static BASE_API: std::sync::RwLock<Option<String>> = std::sync::RwLock::new(None);
+
+tokio::task_local! {
+    // base des endpoints d'API propre à une tâche, définie par `avec_api`
+    static BASE_TACHE: String;
+}
+
+/// retourne la base des endpoints d'API : celle de la tâche courante définie par [`avec_api`],
+/// sinon celle définie par [`definir_api`], sinon celle de la variable d'environnement
+/// `LIBFFTT_API`, sinon [`API`]
+pub fn api() -> String {
+    if let Ok(base) = BASE_TACHE.try_with(String::clone) {
+        return base;
+    }
+    if let Some(base) = BASE_API.read().expect("Base d'API corrompue").as_ref() {
+        return base.clone();
+    }
+    std::env::var(VARIABLE_API).unwrap_or_else(|_| API.to_string())
+}
+
+/// exécute le futur donné en interrogeant la base des endpoints d'API donnée,
+/// par exemple un serveur simulé, sans affecter les autres tâches du processus
+///
+/// La base ne s'applique qu'au futur lui-même : les tâches qu'il lance avec `tokio::spawn`
+/// utilisent la base du processus.
+pub async fn avec_api<F: std::future::Future>(base: &str, futur: F) -> F::Output {
+    BASE_TACHE
+        .scope(base.trim_end_matches('/').to_string(), futur)
+        .await
+}
+
+/// remplace la base des endpoints d'API pour tout le processus
+///
+/// Cette base est globale : elle s'applique à toutes les tâches, y compris celles d'autres
+/// tests exécutés en parallèle. Elle est destinée aux tests isolés dans leur propre exécutable
+/// et aux applications n'interrogeant qu'une seule API ; pour cibler une API le temps d'un
+/// traitement, utiliser [`avec_api`].
+pub fn definir_api(base: &str) {
+    *BASE_API.write().expect("Base d'API corrompue") = Some(base.trim_end_matches('/').to_string());
+}
+
+#[derive(Debug)]
+/// une erreur lors de l'interrogation de l'API
+pub enum ErreurApi {
+    /// la ressource demandée n'existe pas
+    Introuvable,
+    /// l'API est injoignable ou a retourné un statut d'erreur
+    Requete(reqwest::Error),
+    /// le document retourné par l'API est invalide
+    Document(String),
+}
+
+impl std::fmt::Display for ErreurApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErreurApi::Introuvable => write!(f, "ressource introuvable"),
+            ErreurApi::Requete(e) => write!(f, "{e}"),
+            ErreurApi::Document(e) => write!(f, "document invalide : {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ErreurApi {}
+
+impl From<reqwest::Error> for ErreurApi {
+    fn from(e: reqwest::Error) -> Self {
+        if e.status() == Some(reqwest::StatusCode::NOT_FOUND) {
+            ErreurApi::Introuvable
+        } else {
+            ErreurApi::Requete(e)
+        }
+    }
+}
+
+impl From<quick_xml::DeError> for ErreurApi {
+    fn from(e: quick_xml::DeError) -> Self {
+        ErreurApi::Document(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for ErreurApi {
+    fn from(e: serde_json::Error) -> Self {
+        ErreurApi::Document(e.to_string())
+    }
+}
+
+// récupère le document retourné par l'endpoint donné
+pub(crate) async fn telecharger(request_url: &str) -> Result<String, ErreurApi> {
+    log::debug!("Appel API: {request_url}");
+    let response = reqwest::get(request_url).await?.error_for_status()?;
+    Ok(response.text().await?)
+}
+
+// récupère le document retourné par l'endpoint donné, en journalisant les erreurs
+pub(crate) async fn recuperer(request_url: &str) -> Option<String> {
+    telecharger(request_url)
+        .await
+        .map_err(|e| log::error!("Erreur lors de l'appel à {request_url} : {e}"))
+        .ok()
+}
+
+// retourne la valeur d'un paramètre d'un lien de l'API (ex : "cx_tableau=12&...")
+pub(crate) fn parametre(lien: &str, nom: &str) -> Option<String> {
+    lien.split(['&', '?'])
+        .find_map(|p| p.strip_prefix(nom)?.strip_prefix('='))
+        .map(str::to_string)
+}
+
+// échappe les caractères spéciaux d'un texte inséré dans un document XML, HTML ou SVG
+pub(crate) fn echapper_xml(texte: &str) -> String {
+    texte
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 /// Mois séparant la phase 1 de la phase 2
 pub const MISAISON_MOIS: u8 = 1;
 /// Jour du mois séparant la phase 1 de la phase 2
@@ -115,6 +313,13 @@ mod tests {
         assert_eq!(joueur.licence, "3421810");
     }
 
+    #[tokio::test]
+    async fn test_avec_api() {
+        let base = crate::avec_api("http://localhost:1234/", async { crate::api() }).await;
+        assert_eq!(base, "http://localhost:1234");
+        assert_ne!(crate::api(), base);
+    }
+
     #[tokio::test]
     async fn test_club() {
         let club = Club::new("11340010").await;
@@ -0,0 +1,397 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::brulage::{EtatBrulage, Regles};
+use crate::date::Date;
+use crate::equipe::Equipe;
+use crate::joueur::Joueur;
+use crate::prediction::{self, Format};
+use crate::ErreurApi;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une équipe du club à composer
+pub struct EquipeAComposer {
+    /// numéro de l'équipe au sein du club
+    pub numero: u8,
+    /// nom de l'adversaire, s'il est connu
+    pub adversaire: Option<String>,
+    /// points de la composition probable de l'adversaire, par ordre décroissant, si elle est connue
+    pub composition_adverse: Option<Vec<f32>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// la stratégie de composition des équipes
+pub enum Strategie {
+    /// les meilleurs joueurs disponibles sont placés dans l'équipe 1, puis dans l'équipe 2, etc.
+    PlusForteDabord,
+    /// maximise la somme des probabilités de victoire des rencontres des équipes
+    /// face à la composition probable de leurs adversaires
+    MaximiserResultats,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// un joueur proposé dans une composition
+pub struct JoueurPropose {
+    /// numéro de licence
+    pub licence: String,
+    /// nom et prénom du joueur
+    pub nom: String,
+    /// points du joueur
+    pub points: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// la composition proposée pour une équipe
+pub struct CompositionProposee {
+    /// numéro de l'équipe
+    pub numero: u8,
+    /// les joueurs proposés
+    pub joueurs: Vec<JoueurPropose>,
+    /// nombre de joueurs manquant pour compléter l'équipe
+    pub manquants: u8,
+    /// probabilité de victoire de la rencontre, si la composition de l'adversaire est connue
+    pub probabilite: Option<f64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// la proposition de composition de toutes les équipes
+pub struct Proposition {
+    /// la composition de chaque équipe, par numéro croissant
+    pub compositions: Vec<CompositionProposee>,
+    /// les joueurs disponibles non retenus
+    pub remplacants: Vec<JoueurPropose>,
+}
+
+/// propose une composition pour les équipes données
+/// `disponibles` contient les licences des joueurs disponibles, `etats` leur état de brûlage,
+/// `format` le format des rencontres servant à prédire leur résultat
+pub fn composer(
+    joueurs: &[Joueur],
+    disponibles: &HashSet<String>,
+    equipes: &[EquipeAComposer],
+    etats: &[EtatBrulage],
+    regles: &Regles,
+    format: &Format,
+    strategie: Strategie,
+) -> Proposition {
+    let mut equipes: Vec<&EquipeAComposer> = equipes.iter().collect();
+    equipes.sort_by_key(|e| e.numero);
+    let mut candidats: Vec<&Joueur> = joueurs
+        .iter()
+        .filter(|j| disponibles.contains(&j.licence))
+        .collect();
+    candidats.sort_by(|a, b| b.point.total_cmp(&a.point));
+    let etat = |j: &Joueur| {
+        etats
+            .iter()
+            .find(|e| e.licence.as_deref() == Some(&j.licence))
+    };
+    let autorise = |j: &Joueur, numero: u8| etat(j).is_none_or(|e| e.peut_jouer(numero));
+    let superieur = |j: &Joueur, numero: u8| etat(j).is_some_and(|e| e.a_joue_au_dessus(numero));
+    let taille = usize::from(regles.joueurs_par_rencontre);
+
+    // répartition gloutonne : les meilleurs joueurs autorisés dans les premières équipes
+    let mut affectation: Vec<Vec<&Joueur>> = vec![vec![]; equipes.len()];
+    let mut restants = candidats.clone();
+    for (i, e) in equipes.iter().enumerate() {
+        let mut superieurs = 0;
+        restants.retain(|j| {
+            if affectation[i].len() >= taille || !autorise(j, e.numero) {
+                return true;
+            }
+            if superieur(j, e.numero) {
+                if regles
+                    .max_joueurs_superieurs
+                    .is_some_and(|max| superieurs >= usize::from(max))
+                {
+                    return true;
+                }
+                superieurs += 1;
+            }
+            affectation[i].push(j);
+            false
+        });
+    }
+
+    // amélioration locale : échange de joueurs entre équipes, ou avec un remplaçant,
+    // tant que le résultat attendu progresse
+    if strategie == Strategie::MaximiserResultats {
+        let valide = |joueurs: &[&Joueur], numero: u8| {
+            joueurs.iter().all(|j| autorise(j, numero))
+                && regles.max_joueurs_superieurs.is_none_or(|max| {
+                    joueurs.iter().filter(|j| superieur(j, numero)).count() <= usize::from(max)
+                })
+        };
+        let total = |affectation: &[Vec<&Joueur>]| -> f64 {
+            equipes
+                .iter()
+                .zip(affectation)
+                .filter_map(|(e, j)| probabilite(j, e.composition_adverse.as_deref(), format))
+                .sum()
+        };
+        let mut meilleur = total(&affectation);
+        let mut progres = true;
+        while progres {
+            progres = false;
+            for a in 0..equipes.len() {
+                for b in a + 1..equipes.len() {
+                    for ia in 0..affectation[a].len() {
+                        for ib in 0..affectation[b].len() {
+                            let mut essai = affectation.clone();
+                            let x = essai[a][ia];
+                            essai[a][ia] = essai[b][ib];
+                            essai[b][ib] = x;
+                            if !valide(&essai[a], equipes[a].numero)
+                                || !valide(&essai[b], equipes[b].numero)
+                            {
+                                continue;
+                            }
+                            let valeur = total(&essai);
+                            if valeur > meilleur + 1e-9 {
+                                meilleur = valeur;
+                                affectation = essai;
+                                progres = true;
+                            }
+                        }
+                    }
+                }
+                for ia in 0..affectation[a].len() {
+                    for remplacant in &mut restants {
+                        let mut essai = affectation.clone();
+                        essai[a][ia] = remplacant;
+                        if !valide(&essai[a], equipes[a].numero) {
+                            continue;
+                        }
+                        let valeur = total(&essai);
+                        if valeur > meilleur + 1e-9 {
+                            meilleur = valeur;
+                            *remplacant = affectation[a][ia];
+                            affectation = essai;
+                            progres = true;
+                        }
+                    }
+                }
+            }
+        }
+        restants.sort_by(|a, b| b.point.total_cmp(&a.point));
+    }
+
+    let propose = |j: &Joueur| JoueurPropose {
+        licence: j.licence.clone(),
+        nom: j.display(),
+        points: j.point,
+    };
+    Proposition {
+        compositions: equipes
+            .iter()
+            .zip(affectation)
+            .map(|(e, mut joueurs)| {
+                joueurs.sort_by(|a, b| b.point.total_cmp(&a.point));
+                CompositionProposee {
+                    numero: e.numero,
+                    manquants: u8::try_from(taille.saturating_sub(joueurs.len()))
+                        .unwrap_or(u8::MAX),
+                    probabilite: probabilite(&joueurs, e.composition_adverse.as_deref(), format),
+                    joueurs: joueurs.into_iter().map(propose).collect(),
+                }
+            })
+            .collect(),
+        remplacants: restants.into_iter().map(propose).collect(),
+    }
+}
+
+/// retourne les équipes du club jouant à la date donnée, avec leur adversaire et sa composition
+/// probable, établie d'après les rencontres déjà jouées de la poule
+pub async fn equipes_du_jour(
+    equipes: &[Equipe],
+    date: &Date,
+    format: &Format,
+) -> Result<Vec<EquipeAComposer>, ErreurApi> {
+    let mut a_composer = vec![];
+    for e in equipes {
+        let Some(numero) = e.numero() else {
+            continue;
+        };
+        for (_, tour) in e.poule.try_get_tours().await? {
+            let rencontre = tour.rencontres.rencontres.iter().find(|r| {
+                (r.datereelle.as_ref().unwrap_or(&r.date) == date)
+                    && (e.est_nommee(&r.a) || e.est_nommee(&r.b))
+            });
+            if let Some(r) = rencontre {
+                let adversaire = if e.est_nommee(&r.a) { &r.b } else { &r.a };
+                let equipe_adverse = Equipe {
+                    id: String::new(),
+                    nom: adversaire.clone(),
+                    _epreuve: e._epreuve.clone(),
+                    _division: e._division.clone(),
+                    poule: e.poule.clone(),
+                };
+                let composition =
+                    prediction::try_composition_equipe(&equipe_adverse, format).await?;
+                a_composer.push(EquipeAComposer {
+                    numero,
+                    adversaire: Some(adversaire.clone()),
+                    composition_adverse: (!composition.is_empty()).then_some(composition),
+                });
+            }
+        }
+    }
+    Ok(a_composer)
+}
+
+// probabilité de victoire d'une équipe face à la composition probable de son adversaire
+fn probabilite(joueurs: &[&Joueur], adverse: Option<&[f32]>, format: &Format) -> Option<f64> {
+    let adverse = adverse?;
+    let mut points: Vec<f32> = joueurs.iter().map(|j| j.point).collect();
+    points.sort_by(|a, b| b.total_cmp(a));
+    Some(prediction::predire(&points, adverse, format).victoire)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn joueur(licence: &str, point: f32) -> Joueur {
+        Joueur {
+            licence: licence.to_string(),
+            nom: licence.to_string(),
+            prenom: String::new(),
+            points_init: point,
+            point,
+            r#_virtual: point,
+            categorie: None,
+        }
+    }
+
+    // une équipe à composer, dont l'adversaire aligne 4 joueurs de même force
+    fn equipe(numero: u8, force_adversaire: Option<f32>) -> EquipeAComposer {
+        EquipeAComposer {
+            numero,
+            adversaire: None,
+            composition_adverse: force_adversaire.map(|f| vec![f; 4]),
+        }
+    }
+
+    #[test]
+    fn test_plus_forte_dabord() {
+        let joueurs: Vec<Joueur> = (1..=9)
+            .map(|i| joueur(&i.to_string(), 2000.0 - 100.0 * i as f32))
+            .collect();
+        let disponibles: HashSet<String> = (1..=9).map(|i| i.to_string()).collect();
+        // le joueur 5 est brûlé en équipe 1
+        let etats = vec![EtatBrulage {
+            nom: "5".to_string(),
+            licence: Some("5".to_string()),
+            participations: BTreeMap::from([(1, 2)]),
+            brule: Some(1),
+        }];
+        let proposition = composer(
+            &joueurs,
+            &disponibles,
+            &[equipe(2, None), equipe(1, None)],
+            &etats,
+            &Regles::default(),
+            &Format::default(),
+            Strategie::PlusForteDabord,
+        );
+        let licences = |i: usize| -> Vec<&str> {
+            proposition.compositions[i]
+                .joueurs
+                .iter()
+                .map(|j| j.licence.as_str())
+                .collect()
+        };
+        assert_eq!(licences(0), vec!["1", "2", "3", "4"]);
+        assert_eq!(licences(1), vec!["6", "7", "8", "9"]);
+        assert_eq!(proposition.remplacants.len(), 1);
+        assert_eq!(proposition.remplacants[0].licence, "5");
+    }
+
+    #[test]
+    fn test_maximiser_resultats() {
+        let joueurs: Vec<Joueur> = (1..=8)
+            .map(|i| joueur(&i.to_string(), 2000.0 - 100.0 * i as f32))
+            .collect();
+        let disponibles: HashSet<String> = (1..=8).map(|i| i.to_string()).collect();
+        // l'équipe 1 rencontre un adversaire bien plus fort, l'équipe 2 un adversaire à sa portée
+        let equipes = [equipe(1, Some(2500.0)), equipe(2, Some(1500.0))];
+        let glouton = composer(
+            &joueurs,
+            &disponibles,
+            &equipes,
+            &[],
+            &Regles::default(),
+            &Format::default(),
+            Strategie::PlusForteDabord,
+        );
+        let optimise = composer(
+            &joueurs,
+            &disponibles,
+            &equipes,
+            &[],
+            &Regles::default(),
+            &Format::default(),
+            Strategie::MaximiserResultats,
+        );
+        let total =
+            |p: &Proposition| -> f64 { p.compositions.iter().filter_map(|c| c.probabilite).sum() };
+        assert!(total(&optimise) > total(&glouton));
+        assert_eq!(optimise.compositions[0].joueurs.len(), 4);
+        assert_eq!(optimise.compositions[1].manquants, 0);
+    }
+
+    #[test]
+    fn test_remplacants() {
+        // B1 et B2 sont brûlés en équipe 1 et ne peuvent compléter que celle-ci
+        let joueurs = vec![
+            joueur("1", 2000.0),
+            joueur("2", 1950.0),
+            joueur("3", 1900.0),
+            joueur("4", 1850.0),
+            joueur("B1", 1800.0),
+            joueur("B2", 1780.0),
+            joueur("7", 1500.0),
+            joueur("8", 1450.0),
+            joueur("9", 1400.0),
+            joueur("10", 1350.0),
+        ];
+        let disponibles: HashSet<String> = joueurs.iter().map(|j| j.licence.clone()).collect();
+        let etats: Vec<EtatBrulage> = ["B1", "B2"]
+            .iter()
+            .map(|l| EtatBrulage {
+                nom: l.to_string(),
+                licence: Some(l.to_string()),
+                participations: BTreeMap::from([(1, 2)]),
+                brule: Some(1),
+            })
+            .collect();
+        // l'équipe 1 l'emporte facilement, l'équipe 2 a besoin des meilleurs joueurs :
+        // ceux-ci passent en équipe 2 et les remplaçants brûlés complètent l'équipe 1
+        let optimise = composer(
+            &joueurs,
+            &disponibles,
+            &[equipe(1, Some(1300.0)), equipe(2, Some(1700.0))],
+            &etats,
+            &Regles::default(),
+            &Format::default(),
+            Strategie::MaximiserResultats,
+        );
+        let licences = |i: usize| -> Vec<&str> {
+            optimise.compositions[i]
+                .joueurs
+                .iter()
+                .map(|j| j.licence.as_str())
+                .collect()
+        };
+        assert!(licences(0).contains(&"B1") && licences(0).contains(&"B2"));
+        assert_eq!(licences(1), vec!["1", "2", "3", "4"]);
+        let remplacants: Vec<&str> = optimise
+            .remplacants
+            .iter()
+            .map(|j| j.licence.as_str())
+            .collect();
+        assert_eq!(remplacants.len(), 2);
+        assert!(!remplacants.contains(&"B1") && !remplacants.contains(&"B2"));
+    }
+}
@@ -0,0 +1,348 @@
+use serde::{Deserialize, Serialize};
+
+use crate::club::Club;
+use crate::{api, recuperer, ErreurApi};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+/// le type d'un organisme de la fédération
+pub enum TypeOrganisme {
+    /// la fédération
+    Federation,
+    /// une zone, regroupant plusieurs ligues
+    Zone,
+    /// une ligue régionale
+    Ligue,
+    /// un comité départemental
+    Comite,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// un organisme de la fédération, organisant des épreuves
+pub struct Organisme {
+    /// identifiant de l'organisme
+    pub id: String,
+    /// code de l'organisme (ex : "L08" pour une ligue, "D34" pour un comité)
+    pub code: String,
+    /// nom de l'organisme
+    pub libelle: String,
+    /// type de l'organisme
+    pub type_organisme: TypeOrganisme,
+    /// identifiant de l'organisme parent, None pour la fédération
+    pub parent: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+/// l'ensemble des organismes de la fédération, reliés à leur parent
+pub struct Hierarchie {
+    /// les organismes, tous types confondus
+    pub organismes: Vec<Organisme>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// le nombre de joueurs et d'équipes d'un club
+pub struct BilanClub {
+    /// le club
+    pub club: Club,
+    /// nombre de licenciés
+    pub joueurs: usize,
+    /// nombre d'équipes engagées
+    pub equipes: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// les clubs, joueurs et équipes relevant d'un organisme
+pub struct Statistiques {
+    /// l'organisme
+    pub organisme: Organisme,
+    /// nombre de clubs
+    pub clubs: usize,
+    /// nombre de joueurs
+    pub joueurs: usize,
+    /// nombre d'équipes
+    pub equipes: usize,
+}
+
+impl TypeOrganisme {
+    /// tous les types d'organismes, du plus haut au plus bas
+    pub const TOUS: [TypeOrganisme; 4] = [
+        TypeOrganisme::Federation,
+        TypeOrganisme::Zone,
+        TypeOrganisme::Ligue,
+        TypeOrganisme::Comite,
+    ];
+
+    /// le code du type attendu par l'API
+    pub fn code(&self) -> char {
+        match self {
+            TypeOrganisme::Federation => 'F',
+            TypeOrganisme::Zone => 'Z',
+            TypeOrganisme::Ligue => 'L',
+            TypeOrganisme::Comite => 'D',
+        }
+    }
+}
+
+impl Organisme {
+    /// créé les organismes du type donné à partir du document XML retourné par l'API
+    pub fn from_xml(
+        xml: &str,
+        type_organisme: TypeOrganisme,
+    ) -> Result<Vec<Organisme>, quick_xml::DeError> {
+        let doc: DocumentOrganismes = quick_xml::de::from_str(xml)?;
+        Ok(doc
+            .organisme
+            .into_iter()
+            .map(|o| Organisme {
+                id: o.id,
+                code: o.code,
+                libelle: o.libelle,
+                type_organisme,
+                parent: o.id_pere.filter(|p| !p.is_empty() && p != "0"),
+            })
+            .collect())
+    }
+
+    /// retourne les organismes du type donné en interrogeant l'API
+    pub async fn lister(type_organisme: TypeOrganisme) -> Vec<Organisme> {
+        let api = api();
+        let request_url = format!(
+            "{api}/proxy/xml_organisme.php?type={}",
+            type_organisme.code()
+        );
+        let Some(xml) = recuperer(&request_url).await else {
+            return vec![];
+        };
+        Organisme::from_xml(&xml, type_organisme).unwrap_or_else(|e| {
+            log::error!("Erreur lors de la désérialisation des organismes : {e}");
+            vec![]
+        })
+    }
+}
+
+impl Hierarchie {
+    /// récupère les organismes de tous types en interrogeant l'API
+    pub async fn charger() -> Self {
+        let mut organismes = vec![];
+        for type_organisme in TypeOrganisme::TOUS {
+            organismes.extend(Organisme::lister(type_organisme).await);
+        }
+        Hierarchie { organismes }
+    }
+
+    /// retourne l'organisme d'identifiant donné
+    pub fn organisme(&self, id: &str) -> Option<&Organisme> {
+        self.organismes.iter().find(|o| o.id == id)
+    }
+
+    /// retourne le parent de l'organisme
+    pub fn parent(&self, organisme: &Organisme) -> Option<&Organisme> {
+        self.organisme(organisme.parent.as_deref()?)
+    }
+
+    /// retourne les organismes dont l'organisme donné est le parent
+    pub fn enfants(&self, organisme: &Organisme) -> Vec<&Organisme> {
+        self.organismes
+            .iter()
+            .filter(|o| o.parent.as_deref() == Some(organisme.id.as_str()))
+            .collect()
+    }
+
+    /// retourne l'organisme du type donné dont relève l'organisme, en remontant ses parents
+    /// (l'organisme lui-même s'il est de ce type)
+    pub fn ancetre(
+        &self,
+        organisme: &Organisme,
+        type_organisme: TypeOrganisme,
+    ) -> Option<&Organisme> {
+        let mut courant = self.organisme(&organisme.id)?;
+        // la profondeur est bornée pour se prémunir d'un cycle dans les données
+        for _ in 0..TypeOrganisme::TOUS.len() {
+            if courant.type_organisme == type_organisme {
+                return Some(courant);
+            }
+            courant = self.parent(courant)?;
+        }
+        None
+    }
+
+    /// retourne le comité départemental du club
+    pub fn comite(&self, club: &Club) -> Option<&Organisme> {
+        let code = format!("D{}", club.departement()?);
+        self.organismes
+            .iter()
+            .find(|o| o.type_organisme == TypeOrganisme::Comite && o.code == code)
+    }
+
+    /// retourne la ligue du club, parent de son comité
+    pub fn ligue(&self, club: &Club) -> Option<&Organisme> {
+        self.ancetre(self.comite(club)?, TypeOrganisme::Ligue)
+    }
+
+    /// agrège les bilans des clubs par organisme du type donné (comités ou ligues par exemple)
+    /// les clubs dont l'organisme est inconnu sont ignorés, les organismes sans club sont absents
+    pub fn statistiques(
+        &self,
+        bilans: &[BilanClub],
+        type_organisme: TypeOrganisme,
+    ) -> Vec<Statistiques> {
+        let mut statistiques: Vec<Statistiques> = vec![];
+        for bilan in bilans {
+            let Some(organisme) = self
+                .comite(&bilan.club)
+                .and_then(|c| self.ancetre(c, type_organisme))
+            else {
+                log::warn!("Organisme inconnu pour le club {}", bilan.club.numero);
+                continue;
+            };
+            let index = match statistiques
+                .iter()
+                .position(|s| s.organisme.id == organisme.id)
+            {
+                Some(index) => index,
+                None => {
+                    statistiques.push(Statistiques {
+                        organisme: organisme.clone(),
+                        clubs: 0,
+                        joueurs: 0,
+                        equipes: 0,
+                    });
+                    statistiques.len() - 1
+                }
+            };
+            let s = &mut statistiques[index];
+            s.clubs += 1;
+            s.joueurs += bilan.joueurs;
+            s.equipes += bilan.equipes;
+        }
+        statistiques.sort_by(|a, b| a.organisme.code.cmp(&b.organisme.code));
+        statistiques
+    }
+}
+
+impl BilanClub {
+    /// collecte le nombre de licenciés et d'équipes du club en interrogeant l'API
+    /// (deux requêtes par club, les joueurs n'étant pas récupérés un à un)
+    pub async fn collecter(club: Club) -> Result<Self, ErreurApi> {
+        Ok(BilanClub {
+            joueurs: club.nombre_licencies().await?,
+            equipes: club.try_api_equipes().await?.len(),
+            club,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentOrganismes {
+    #[serde(default)]
+    organisme: Vec<OrganismeApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// un organisme tel que retourné par l'API
+struct OrganismeApi {
+    id: String,
+    #[serde(default)]
+    code: String,
+    libelle: String,
+    #[serde(default, rename = "idPere")]
+    id_pere: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_organismes() {
+        let xml = r#"<liste>
+    <organisme><libelle>OCCITANIE</libelle><id>1008</id><code>L08</code><idPere>1</idPere></organisme>
+    <organisme><libelle>NOUVELLE AQUITAINE</libelle><id>1010</id><code>L10</code><idPere>1</idPere></organisme>
+</liste>"#;
+        let ligues = Organisme::from_xml(xml, TypeOrganisme::Ligue).unwrap();
+        assert_eq!(ligues.len(), 2);
+        assert_eq!(ligues[0].code, "L08");
+        assert_eq!(ligues[1].type_organisme, TypeOrganisme::Ligue);
+        assert_eq!(ligues[0].parent.as_deref(), Some("1"));
+    }
+
+    fn organisme(id: &str, code: &str, type_organisme: TypeOrganisme, parent: &str) -> Organisme {
+        Organisme {
+            id: id.to_string(),
+            code: code.to_string(),
+            libelle: code.to_string(),
+            type_organisme,
+            parent: Some(parent.to_string()).filter(|p| !p.is_empty()),
+        }
+    }
+
+    fn club(numero: &str) -> Club {
+        serde_json::from_value(serde_json::json!({
+            "numero": numero,
+            "nom": numero,
+            "nomsalle": "",
+            "adressesalle1": "",
+            "adressesalle2": "",
+            "adressesalle3": "",
+            "codepsalle": "",
+            "villesalle": "",
+            "latitude": "",
+            "longitude": ""
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_hierarchie() {
+        let hierarchie = Hierarchie {
+            organismes: vec![
+                organisme("1", "FFTT", TypeOrganisme::Federation, ""),
+                organisme("5", "Z05", TypeOrganisme::Zone, "1"),
+                organisme("1008", "L08", TypeOrganisme::Ligue, "5"),
+                organisme("34", "D34", TypeOrganisme::Comite, "1008"),
+                organisme("30", "D30", TypeOrganisme::Comite, "1008"),
+            ],
+        };
+        let montpellier = club("11340010");
+        assert_eq!(montpellier.departement(), Some("34"));
+        assert_eq!(club("12974001").departement(), Some("974"));
+        assert_eq!(hierarchie.comite(&montpellier).unwrap().code, "D34");
+        assert_eq!(hierarchie.ligue(&montpellier).unwrap().code, "L08");
+        let ligue = hierarchie.organisme("1008").unwrap();
+        assert_eq!(hierarchie.enfants(ligue).len(), 2);
+        assert_eq!(hierarchie.parent(ligue).unwrap().code, "Z05");
+        assert_eq!(
+            hierarchie
+                .ancetre(ligue, TypeOrganisme::Federation)
+                .unwrap()
+                .code,
+            "FFTT"
+        );
+        assert!(hierarchie.ancetre(ligue, TypeOrganisme::Comite).is_none());
+
+        let bilan = |numero: &str, joueurs, equipes| BilanClub {
+            club: club(numero),
+            joueurs,
+            equipes,
+        };
+        let bilans = [
+            bilan("11340010", 120, 12),
+            bilan("11340020", 30, 3),
+            bilan("11300001", 50, 4),
+            bilan("11990001", 10, 1),
+        ];
+        let comites = hierarchie.statistiques(&bilans, TypeOrganisme::Comite);
+        assert_eq!(comites.len(), 2);
+        assert_eq!(comites[0].organisme.code, "D30");
+        assert_eq!(
+            (comites[1].clubs, comites[1].joueurs, comites[1].equipes),
+            (2, 150, 15)
+        );
+        let ligues = hierarchie.statistiques(&bilans, TypeOrganisme::Ligue);
+        assert_eq!(ligues.len(), 1);
+        assert_eq!(
+            (ligues[0].clubs, ligues[0].joueurs, ligues[0].equipes),
+            (3, 200, 19)
+        );
+    }
+}
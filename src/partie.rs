@@ -1,31 +1,38 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::ops::Add;
 
-use crate::journee::Journee;
+use crate::journee::{Journee, JourneeApi};
 
-#[derive(Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Default, Debug, Deserialize, Serialize)]
 /// une partie représente l'ensemble des rencontres d'une saison
 pub struct Partie {
-    /// le nombre de matchs validés
-    #[allow(dead_code)]
-    processed: u8,
-    /// le nombre total de matchs effectués
-    num_matchs: u8,
-    /// les points gagnés sur ces matchs
-    ex: f32,
     /// les rencontres
     pub journees: Vec<Journee>,
 }
 
+#[derive(Debug, Deserialize)]
+// une partie telle que retournée par l'API
+// les compteurs de matchs et de points de l'API ne sont pas conservés :
+// ils se déduisent des journées
+pub(crate) struct PartieApi {
+    #[serde(default)]
+    journees: Vec<JourneeApi>,
+}
+
+impl From<PartieApi> for Partie {
+    fn from(p: PartieApi) -> Self {
+        Partie {
+            journees: p.journees.into_iter().map(Journee::from).collect(),
+        }
+    }
+}
+
 /// la somme de 2 parties
 impl Add for Partie {
     type Output = Partie;
     fn add(self, other: Partie) -> Self {
         Partie {
-            num_matchs: self.num_matchs + other.num_matchs,
-            ex: self.ex + other.ex,
             journees: self.journees.into_iter().chain(other.journees).collect(),
-            ..self
         }
     }
 }
@@ -33,15 +40,7 @@ impl Add for Partie {
 impl Partie {
     /// la somme d'un ensemble de parties
     pub fn sum_parties(parties: &[Partie]) -> Partie {
-        parties.iter().fold(
-            Partie {
-                processed: 0,
-                num_matchs: 0,
-                ex: 0.0,
-                journees: Vec::new(),
-            },
-            |x, y| x + y.clone(),
-        )
+        parties.iter().fold(Partie::default(), |x, y| x + y.clone())
     }
 
     /// regroupe les journées d'une partie
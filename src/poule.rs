@@ -1,13 +1,18 @@
 use serde::Deserialize;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
+use crate::classement::{self, Ecart, LigneClassement};
 use crate::club::Club;
+use crate::graphique::{self, Positions};
+use crate::simulation::{self, Parametres, Probabilites};
+use crate::tour::Bareme;
 use crate::tour::Rencontres;
 use crate::tour::Tour;
-use crate::API;
+use crate::{api, telecharger, ErreurApi};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+/// une poule d'une division
 pub struct Poule {
     pub division: String,
     pub numero: String,
@@ -17,70 +22,121 @@ pub struct Poule {
 /// représente le classement d'une équipe dans la poule
 pub struct Classement {
     /// numéro de la poule
-    #[serde(rename = "poule")]
     pub numero_poule: u8,
     /// classement dans la poule
-    #[serde(rename = "clt")]
     pub classement: u8,
     /// nom de l'équipe
     pub equipe: String,
     /// nombre de matchs joués
-    #[serde(rename = "joue")]
     pub joues: u8,
     /// nombre de points
-    #[serde(rename = "pts")]
     pub points: u8,
     /// nombre de victoires
-    #[serde(rename = "vic")]
     pub victoires: u8,
     /// nombre de défaites
-    #[serde(rename = "def")]
     pub defaites: u8,
     /// nombre de nuls
-    #[serde(rename = "nul")]
     pub nuls: u8,
     /// nombre de forfaits
-    #[serde(rename = "pf")]
     pub forfaits: u8,
     /// nombre de parties gagnées
-    #[serde(rename = "pg")]
     pub parties_gagnees: u8,
     /// nombre de parties perdues
-    #[serde(rename = "pp")]
     pub parties_perdues: u8,
     /// le numero du club
-    #[serde(rename = "numero")]
     pub numero: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 /// un ensemble de classements
 pub struct Classements {
-    #[serde(rename = "classement")]
     /// les classements
     pub classements: Vec<Classement>,
 }
 
+impl Classements {
+    /// créé les classements à partir du document XML retourné par l'API
+    pub fn from_xml(xml: &str) -> Result<Classements, quick_xml::DeError> {
+        let doc: DocumentClassements = quick_xml::de::from_str(xml)?;
+        Ok(Classements {
+            classements: doc.classement.into_iter().map(Classement::from).collect(),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentClassements {
+    #[serde(default)]
+    classement: Vec<ClassementApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// un classement tel que retourné par l'API
+struct ClassementApi {
+    poule: u8,
+    clt: u8,
+    equipe: String,
+    joue: u8,
+    pts: u8,
+    vic: u8,
+    def: u8,
+    nul: u8,
+    pf: u8,
+    pg: u8,
+    pp: u8,
+    numero: String,
+}
+
+impl From<ClassementApi> for Classement {
+    fn from(c: ClassementApi) -> Self {
+        Classement {
+            numero_poule: c.poule,
+            classement: c.clt,
+            equipe: c.equipe,
+            joues: c.joue,
+            points: c.pts,
+            victoires: c.vic,
+            defaites: c.def,
+            nuls: c.nul,
+            forfaits: c.pf,
+            parties_gagnees: c.pg,
+            parties_perdues: c.pp,
+            numero: c.numero,
+        }
+    }
+}
+
 impl Poule {
     /// retourne les tours de la poule
     pub async fn get_tours(&self) -> Vec<(u8, Tour)> {
+        self.try_get_tours()
+            .await
+            .expect("Impossible de récupérer la poule")
+    }
+
+    /// retourne les tours de la poule, en retournant l'erreur éventuelle
+    pub async fn try_get_tours(&self) -> Result<Vec<(u8, Tour)>, ErreurApi> {
+        let api = api();
         let request_url = format!(
-            "{API}/proxy/xml_result_equ.php?force=1&D1={}&cx_poule={}",
+            "{api}/proxy/xml_result_equ.php?force=1&D1={}&cx_poule={}",
             self.division, self.numero
         );
-        log::debug!("Appel API: {request_url}");
-        let response = reqwest::get(&request_url)
-            .await
-            .expect("Impossible de récupérer la poule")
-            .text()
-            .await
-            .unwrap();
-        let rencontres: Rencontres = quick_xml::de::from_str(&response).unwrap();
-        let mut tours_hashed: HashMap<u8, Tour> = HashMap::new();
-        for r in &rencontres.rencontres {
+        let response = telecharger(&request_url).await?;
+        let rencontres = Rencontres::from_xml(&response)?;
+        Ok(Self::regrouper_tours(rencontres))
+    }
+
+    // regroupe les rencontres par tour, triés par numéro
+    fn regrouper_tours(rencontres: Rencontres) -> Vec<(u8, Tour)> {
+        let mut tours_hashed: BTreeMap<u8, Tour> = BTreeMap::new();
+        for r in rencontres.rencontres {
             match tours_hashed.get_mut(&r.tour) {
                 Some(tour) => {
-                    tour.rencontres.rencontres.push(r.clone());
+                    if r.date < tour.date {
+                        tour.date = r.date.clone();
+                    }
+                    tour.rencontres.rencontres.push(r);
                 }
                 None => {
                     tours_hashed.insert(
@@ -89,37 +145,69 @@ impl Poule {
                             numero: r.tour,
                             date: r.date.clone(),
                             rencontres: Rencontres {
-                                rencontres: vec![r.clone()],
+                                rencontres: vec![r],
                             },
                         },
                     );
                 }
             }
         }
-        //println!("{:?}", htours);
-        let mut tours = Vec::new();
-        for i in 1..=u8::try_from(tours_hashed.len()).expect("This number should be a u8") {
-            tours.push((i, tours_hashed[&i].clone()));
-        }
-        tours.sort_by_key(|x| x.0);
-        tours
+        tours_hashed.into_iter().collect()
     }
 
     /// retourne les classements des équipes de la poule
     pub async fn classement(&self) -> Vec<Classement> {
+        self.try_classement()
+            .await
+            .expect("Impossible de récupérer le classement de la poule")
+    }
+
+    /// retourne les classements des équipes de la poule, en retournant l'erreur éventuelle
+    pub async fn try_classement(&self) -> Result<Vec<Classement>, ErreurApi> {
+        let api = api();
         let request_url = format!(
-            "{API}/proxy/xml_result_equ.php?force=1&action=classement&D1={}&cx_poule={}",
+            "{api}/proxy/xml_result_equ.php?force=1&action=classement&D1={}&cx_poule={}",
             self.division, self.numero
         );
-        log::debug!("Appel API: {request_url}");
-        let response = reqwest::get(&request_url)
+        let response = telecharger(&request_url).await?;
+        Ok(Classements::from_xml(&response)?.classements)
+    }
+
+    /// calcule localement le classement de la poule à partir de ses rencontres
+    pub async fn classement_local(&self, bareme: &Bareme) -> Vec<LigneClassement> {
+        let rencontres: Vec<_> = self
+            .get_tours()
             .await
-            .expect("Impossible de récupérer le classement de la poule")
-            .text()
+            .into_iter()
+            .flat_map(|(_, t)| t.rencontres.rencontres)
+            .collect();
+        classement::calculer(&rencontres, bareme)
+    }
+
+    /// compare le classement calculé localement au classement officiel
+    /// permet de détecter les résultats en attente ou corrigés
+    pub async fn ecarts_classement(&self, bareme: &Bareme) -> Vec<Ecart> {
+        let calcule = self.classement_local(bareme).await;
+        let officiel = self.classement().await;
+        classement::ecarts(&calcule, &officiel)
+    }
+
+    /// calcule l'évolution du rang des équipes de la poule au fil des tours joués
+    pub async fn evolution_classement(&self, bareme: &Bareme) -> Vec<Positions> {
+        graphique::positions_par_tour(&self.get_tours().await, bareme)
+    }
+
+    /// simule la fin de phase à partir du classement officiel et des rencontres restant à jouer
+    pub async fn simuler(&self, parametres: &Parametres) -> Vec<Probabilites> {
+        let classements = self.classement().await;
+        let restantes: Vec<_> = self
+            .get_tours()
             .await
-            .unwrap();
-        let classement: Classements = quick_xml::de::from_str(&response).unwrap();
-        classement.classements
+            .into_iter()
+            .flat_map(|(_, t)| t.rencontres.rencontres)
+            .filter(|r| !r.est_jouee())
+            .collect();
+        simulation::simuler(&classements, &restantes, parametres)
     }
 
     /// retourne les clubs représentés dans la poule
@@ -133,3 +221,93 @@ impl Poule {
         clubs
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tour::{Bareme, Cote, Issue};
+
+    const RENCONTRES: &str = r#"<?xml version="1.0" encoding="ISO-8859-1"?>
+<liste>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>MONTPELLIER TT 1</equa><equb>NIMES ASPTT 2</equb><scorea>8</scorea><scoreb>6</scoreb><lien>renc_id=1</lien><dateprevue>28/09/2024</dateprevue><datereelle>28/09/2024</datereelle></tour>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>SETE TT 1</equa><equb>ALES TT 1</equb><scorea>7</scorea><scoreb>7</scoreb><lien>renc_id=2</lien><dateprevue>28/09/2024</dateprevue><datereelle>28/09/2024</datereelle></tour>
+    <tour><libelle>Poule 3 - tour n°2 du 12/10/2024</libelle><equa>NIMES ASPTT 2</equa><equb>SETE TT 1</equb><scorea>F</scorea><scoreb>0</scoreb><lien>renc_id=3</lien><dateprevue>12/10/2024</dateprevue><datereelle>12/10/2024</datereelle></tour>
+    <tour><libelle>Poule 3 - tour n°2 du 12/10/2024</libelle><equa>ALES TT 1</equa><equb>MONTPELLIER TT 1</equb><scorea></scorea><scoreb></scoreb><lien></lien><dateprevue>12/10/2024</dateprevue><datereelle>09/11/2024</datereelle></tour>
+    <tour><libelle>Poule 3 - tour n°3 du 16/11/2024</libelle><equa>MONTPELLIER TT 1</equa><equb>SETE TT 1</equb><scorea></scorea><scoreb></scoreb><lien></lien><dateprevue>16/11/2024</dateprevue><datereelle></datereelle></tour>
+</liste>"#;
+
+    #[test]
+    fn test_rencontres() {
+        let rencontres = Rencontres::from_xml(RENCONTRES).unwrap();
+        let r = &rencontres.rencontres;
+        assert_eq!(r[0].issue(), Issue::VictoireA);
+        assert_eq!(r[0].scorea, Some(8));
+        assert_eq!(r[1].issue(), Issue::Nul);
+        assert_eq!(r[2].issue(), Issue::ForfaitA);
+        assert_eq!(r[2].forfait, Some(Cote::Domicile));
+        assert_eq!(r[3].issue(), Issue::Reportee);
+        assert_eq!(r[4].issue(), Issue::NonJouee);
+        assert_eq!(r[4].scorea, None);
+    }
+
+    #[test]
+    fn test_libelle_invalide() {
+        for libelle in [
+            "Poule 3 - tour du 28/09/2024",
+            "Poule 3 - tour n°",
+            "tour n°x",
+        ] {
+            let xml = RENCONTRES.replacen("Poule 3 - tour n°1 du 28/09/2024", libelle, 1);
+            assert!(Rencontres::from_xml(&xml).is_err(), "{libelle}");
+        }
+    }
+
+    #[test]
+    fn test_points() {
+        let rencontres = Rencontres::from_xml(RENCONTRES).unwrap();
+        let r = &rencontres.rencontres;
+        let bareme = Bareme::default();
+        assert_eq!(r[0].cote("NIMES ASPTT 2"), Some(Cote::Exterieur));
+        assert_eq!(r[0].adversaire("NIMES ASPTT 2"), Some("MONTPELLIER TT 1"));
+        assert_eq!(r[0].score("NIMES ASPTT 2"), Some((6, 8)));
+        assert_eq!(r[0].points("MONTPELLIER TT 1", &bareme), Some(3));
+        assert_eq!(r[0].points("NIMES ASPTT 2", &bareme), Some(1));
+        assert_eq!(r[1].points("SETE TT 1", &bareme), Some(2));
+        assert_eq!(r[2].points("NIMES ASPTT 2", &bareme), Some(0));
+        assert_eq!(r[2].points("SETE TT 1", &bareme), Some(3));
+        assert_eq!(r[3].points("ALES TT 1", &bareme), None);
+        assert_eq!(r[0].points("ALES TT 1", &bareme), None);
+    }
+
+    #[test]
+    fn test_regrouper_tours() {
+        let rencontres = Rencontres::from_xml(RENCONTRES).unwrap();
+        let tours = Poule::regrouper_tours(rencontres);
+        assert_eq!(tours.len(), 3);
+        assert_eq!(tours[1].0, 2);
+        assert_eq!(tours[1].1.rencontres.rencontres.len(), 2);
+        assert!(tours[0].1.est_termine());
+        assert!(!tours[1].1.est_termine());
+    }
+
+    #[test]
+    fn test_serde() {
+        let rencontres = Rencontres::from_xml(RENCONTRES).unwrap();
+        let tours = Poule::regrouper_tours(rencontres);
+        let json = serde_json::to_string(&tours).unwrap();
+        let relus: Vec<(u8, Tour)> = serde_json::from_str(&json).unwrap();
+        assert_eq!(relus, tours);
+        let json = serde_json::to_value(&tours[1].1.rencontres.rencontres[1]).unwrap();
+        assert_eq!(json["a"], "ALES TT 1");
+        assert_eq!(json["date"], "2024-10-12");
+        assert_eq!(json["datereelle"], "2024-11-09");
+        assert_eq!(json["scorea"], serde_json::Value::Null);
+
+        let poule = Poule {
+            division: "5678".to_string(),
+            numero: "1234".to_string(),
+        };
+        let json = serde_json::to_string(&poule).unwrap();
+        assert_eq!(serde_json::from_str::<Poule>(&json).unwrap(), poule);
+    }
+}
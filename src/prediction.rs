@@ -0,0 +1,342 @@
+use serde::{Deserialize, Serialize};
+
+use crate::composition::{self, BilanJoueur};
+use crate::equipe::Equipe;
+use crate::tour::Bareme;
+use crate::ErreurApi;
+
+/// probabilité minimale de victoire d'un joueur, quel que soit l'écart de points
+pub const PROBABILITE_MIN: f64 = 0.02;
+/// probabilité maximale de victoire d'un joueur, quel que soit l'écart de points
+pub const PROBABILITE_MAX: f64 = 1.0 - PROBABILITE_MIN;
+
+// grille fédérale des points de classement, par écart de points décroissant :
+// (écart minimal, victoire normale, défaite normale, victoire anormale, défaite anormale)
+const GRILLE: [(f32, f32, f32, f32, f32); 9] = [
+    (500.0, 0.0, 0.0, 40.0, -29.0),
+    (400.0, 0.5, 0.0, 28.0, -20.0),
+    (300.0, 1.0, -0.5, 22.0, -16.0),
+    (200.0, 2.0, -1.0, 17.0, -12.5),
+    (150.0, 3.0, -2.0, 13.0, -10.0),
+    (100.0, 4.0, -3.0, 10.0, -8.0),
+    (50.0, 5.0, -4.0, 8.0, -7.0),
+    (25.0, 5.5, -4.5, 7.0, -6.0),
+    (0.0, 6.0, -5.0, 6.0, -5.0),
+];
+
+// ligne de la grille correspondant à un écart de points
+fn ligne(ecart: f32) -> (f32, f32, f32, f32) {
+    let (_, vn, dn, va, da) = GRILLE
+        .iter()
+        .find(|(minimum, ..)| ecart >= *minimum)
+        .expect("La grille couvre tous les écarts");
+    (*vn, *dn, *va, *da)
+}
+
+/// points gagnés (positifs) ou perdus (négatifs) par un joueur selon la grille fédérale,
+/// à partir de ses points, de ceux de son adversaire et de l'issue de la partie
+pub fn points_partie(points: f32, points_adversaire: f32, victoire: bool) -> f32 {
+    let (vn, dn, va, da) = ligne((points - points_adversaire).abs());
+    // le résultat est normal lorsque le mieux classé l'emporte
+    let mieux_classe = points >= points_adversaire;
+    match (victoire, mieux_classe) {
+        (true, true) => vn,
+        (true, false) => va,
+        (false, false) => dn,
+        (false, true) => da,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// le format d'une rencontre par équipes
+pub struct Format {
+    /// les simples, sous la forme (indice du joueur A, indice du joueur B)
+    pub simples: Vec<(usize, usize)>,
+    /// les doubles, sous la forme ((joueurs A), (joueurs B))
+    pub doubles: Vec<((usize, usize), (usize, usize))>,
+}
+
+impl Default for Format {
+    /// le format à 4 joueurs du championnat de France par équipes : 12 simples et 2 doubles
+    fn default() -> Self {
+        Format {
+            simples: vec![
+                (0, 0),
+                (1, 1),
+                (2, 2),
+                (3, 3),
+                (0, 1),
+                (1, 0),
+                (3, 2),
+                (2, 3),
+                (0, 2),
+                (2, 0),
+                (3, 1),
+                (1, 3),
+            ],
+            doubles: vec![((0, 1), (0, 1)), ((2, 3), (2, 3))],
+        }
+    }
+}
+
+impl Format {
+    /// nombre de parties de la rencontre
+    pub fn parties(&self) -> usize {
+        self.simples.len() + self.doubles.len()
+    }
+
+    /// nombre de joueurs de chaque équipe disputant les simples
+    pub fn joueurs(&self) -> usize {
+        self.simples
+            .iter()
+            .map(|(ia, ib)| ia.max(ib) + 1)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// la prédiction du résultat d'une rencontre
+pub struct Prediction {
+    /// probabilité que l'équipe A gagne k parties, pour k de 0 au nombre de parties
+    pub distribution: Vec<f64>,
+    /// probabilité de victoire de l'équipe A
+    pub victoire: f64,
+    /// probabilité de match nul
+    pub nul: f64,
+    /// probabilité de défaite de l'équipe A
+    pub defaite: f64,
+    /// nombre moyen de parties gagnées par l'équipe A
+    pub score_moyen: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// la force estimée d'une équipe de la poule
+pub struct Force {
+    /// nom de l'équipe
+    pub equipe: String,
+    /// points de classement moyens attendus par rencontre contre les autres équipes
+    pub points_attendus: f64,
+}
+
+/// probabilité de victoire d'un joueur face à un autre, déduite de la grille fédérale
+///
+/// Les chances du mieux classé sont proportionnelles aux points échangés par les deux joueurs
+/// lors d'un résultat anormal, celles du moins bien classé aux points échangés lors d'un
+/// résultat normal : à écart nul les chances sont égales. Une contre-performance restant
+/// toujours possible, la probabilité est bornée à [`PROBABILITE_MIN`, `PROBABILITE_MAX`],
+/// y compris au-delà de 500 points d'écart où la grille ne récompense plus la victoire normale.
+pub fn probabilite(points: f64, points_adversaire: f64) -> f64 {
+    let (vn, dn, va, da) = ligne((points - points_adversaire).abs() as f32);
+    let anormal = f64::from(va - da);
+    let normal = f64::from(vn - dn);
+    let mieux_classe = (anormal / (anormal + normal)).clamp(PROBABILITE_MIN, PROBABILITE_MAX);
+    if points >= points_adversaire {
+        mieux_classe
+    } else {
+        1.0 - mieux_classe
+    }
+}
+
+/// prédit le résultat d'une rencontre entre deux compositions, données par les points des joueurs
+/// les joueurs manquants sont considérés comme perdant leurs parties,
+/// une partie où les deux joueurs manquent n'est gagnée par aucune équipe
+pub fn predire(a: &[f32], b: &[f32], format: &Format) -> Prediction {
+    let points = |joueurs: &[f32], i: usize| joueurs.get(i).map(|p| f64::from(*p));
+    // probabilité que l'équipe A gagne chaque partie, None si aucune équipe ne la gagne
+    let issue = |pa: Option<f64>, pb: Option<f64>| match (pa, pb) {
+        (Some(pa), Some(pb)) => Some(probabilite(pa, pb)),
+        (Some(_), None) => Some(1.0),
+        (None, Some(_)) => Some(0.0),
+        (None, None) => None,
+    };
+    let mut probas = vec![];
+    for (ia, ib) in &format.simples {
+        probas.push(issue(points(a, *ia), points(b, *ib)));
+    }
+    for ((a1, a2), (b1, b2)) in &format.doubles {
+        let paire = |joueurs: &[f32], i: usize, j: usize| {
+            Some((points(joueurs, i)? + points(joueurs, j)?) / 2.0)
+        };
+        probas.push(issue(paire(a, *a1, *a2), paire(b, *b1, *b2)));
+    }
+
+    // distribution du nombre de parties gagnées (loi binomiale de Poisson)
+    let mut distribution = vec![0.0; format.parties() + 1];
+    distribution[0] = 1.0;
+    let disputees: Vec<f64> = probas.into_iter().flatten().collect();
+    for (i, p) in disputees.iter().enumerate() {
+        for k in (0..=i + 1).rev() {
+            let precedente = if k > 0 { distribution[k - 1] } else { 0.0 };
+            distribution[k] = distribution[k] * (1.0 - p) + precedente * p;
+        }
+    }
+    // l'équipe B gagne les parties disputées que l'équipe A ne gagne pas
+    let n = disputees.len();
+    let (mut victoire, mut nul, mut defaite) = (0.0, 0.0, 0.0);
+    for (k, d) in distribution.iter().enumerate().take(n + 1) {
+        match (2 * k).cmp(&n) {
+            std::cmp::Ordering::Greater => victoire += d,
+            std::cmp::Ordering::Equal => nul += d,
+            std::cmp::Ordering::Less => defaite += d,
+        }
+    }
+    Prediction {
+        score_moyen: distribution
+            .iter()
+            .enumerate()
+            .map(|(k, d)| k as f64 * d)
+            .sum(),
+        distribution,
+        victoire,
+        nul,
+        defaite,
+    }
+}
+
+/// classe les équipes d'une poule par force, selon les points attendus
+/// lors de rencontres contre chacune des autres équipes
+pub fn classer_par_force(
+    equipes: &[(String, Vec<f32>)],
+    format: &Format,
+    bareme: &Bareme,
+) -> Vec<Force> {
+    let mut forces: Vec<Force> = equipes
+        .iter()
+        .map(|(nom, joueurs)| {
+            let adversaires: Vec<&Vec<f32>> = equipes
+                .iter()
+                .filter(|(autre, _)| autre != nom)
+                .map(|(_, j)| j)
+                .collect();
+            let total: f64 = adversaires
+                .iter()
+                .map(|b| {
+                    let p = predire(joueurs, b, format);
+                    p.victoire * f64::from(bareme.victoire)
+                        + p.nul * f64::from(bareme.nul)
+                        + p.defaite * f64::from(bareme.defaite)
+                })
+                .sum();
+            Force {
+                equipe: nom.clone(),
+                points_attendus: total / adversaires.len().max(1) as f64,
+            }
+        })
+        .collect();
+    forces.sort_by(|a, b| b.points_attendus.total_cmp(&a.points_attendus));
+    forces
+}
+
+/// retourne la composition probable d'une équipe, à partir du bilan de ses joueurs :
+/// les joueurs ayant le plus joué, triés par points décroissants
+pub fn composition_probable(bilans: &[BilanJoueur], taille: usize) -> Vec<f32> {
+    let mut bilans: Vec<&BilanJoueur> = bilans.iter().collect();
+    bilans.sort_by(|a, b| {
+        b.tours
+            .len()
+            .cmp(&a.tours.len())
+            .then(b.points.cmp(&a.points))
+    });
+    let mut points: Vec<f32> = bilans
+        .iter()
+        .take(taille)
+        .filter_map(|b| b.points.map(f32::from))
+        .collect();
+    points.sort_by(|a, b| b.total_cmp(a));
+    points
+}
+
+/// retourne les points de la composition probable d'une équipe,
+/// établie sur les rencontres déjà jouées
+pub async fn composition_equipe(equipe: &Equipe, format: &Format) -> Vec<f32> {
+    let bilans = composition::bilan(&equipe.compositions().await);
+    composition_probable(&bilans, format.joueurs())
+}
+
+/// retourne la composition probable d'une équipe comme [`composition_equipe`],
+/// en retournant l'erreur éventuelle
+pub async fn try_composition_equipe(
+    equipe: &Equipe,
+    format: &Format,
+) -> Result<Vec<f32>, ErreurApi> {
+    let bilans = composition::bilan(&equipe.try_compositions().await?);
+    Ok(composition_probable(&bilans, format.joueurs()))
+}
+
+/// prédit le résultat d'une rencontre entre deux équipes,
+/// à partir de leurs compositions probables établies sur les rencontres déjà jouées
+pub async fn predire_equipes(a: &Equipe, b: &Equipe, format: &Format) -> Prediction {
+    let a = composition_equipe(a, format).await;
+    let b = composition_equipe(b, format).await;
+    predire(&a, &b, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_points_partie() {
+        assert_eq!(points_partie(1000.0, 1000.0, true), 6.0);
+        assert_eq!(points_partie(1200.0, 1000.0, true), 2.0);
+        assert_eq!(points_partie(1200.0, 1000.0, false), -12.5);
+        assert_eq!(points_partie(1000.0, 1200.0, true), 17.0);
+        assert_eq!(points_partie(1000.0, 1200.0, false), -1.0);
+        assert_eq!(points_partie(500.0, 1600.0, false), 0.0);
+    }
+
+    #[test]
+    fn test_probabilite() {
+        assert!((probabilite(1500.0, 1500.0) - 0.5).abs() < 1e-9);
+        // à 200 points d'écart : 17 + 12,5 points en jeu sur un résultat anormal, 2 + 1 sinon
+        assert!((probabilite(1700.0, 1500.0) - 29.5 / 32.5).abs() < 1e-9);
+        assert!((probabilite(1500.0, 1700.0) - 3.0 / 32.5).abs() < 1e-9);
+        // au-delà de la grille, la contre-performance reste possible
+        assert_eq!(probabilite(2100.0, 1500.0), PROBABILITE_MAX);
+        assert!((probabilite(1500.0, 2100.0) - PROBABILITE_MIN).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_predire() {
+        let egal = predire(
+            &[1500.0, 1500.0, 1500.0, 1500.0],
+            &[1500.0, 1500.0, 1500.0, 1500.0],
+            &Format::default(),
+        );
+        assert_eq!(egal.distribution.len(), 15);
+        assert!((egal.distribution.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!((egal.victoire - egal.defaite).abs() < 1e-9);
+        assert!((egal.score_moyen - 7.0).abs() < 1e-9);
+        assert_eq!(Format::default().joueurs(), 4);
+
+        let incomplet = predire(
+            &[1500.0, 1500.0, 1500.0],
+            &[1500.0, 1500.0, 1500.0, 1500.0],
+            &Format::default(),
+        );
+        assert!(incomplet.defaite > incomplet.victoire);
+
+        // les parties où les deux joueurs manquent ne comptent pour aucune équipe
+        let double_forfait = predire(
+            &[1500.0, 1500.0, 1500.0],
+            &[1500.0, 1500.0, 1500.0],
+            &Format::default(),
+        );
+        assert_eq!(double_forfait.distribution.len(), 15);
+        assert!((double_forfait.victoire - double_forfait.defaite).abs() < 1e-9);
+        assert!((double_forfait.score_moyen - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_classer_par_force() {
+        let equipes = vec![
+            ("FAIBLE".to_string(), vec![900.0, 850.0, 800.0, 800.0]),
+            ("FORTE".to_string(), vec![2000.0, 1900.0, 1800.0, 1800.0]),
+            ("MOYENNE".to_string(), vec![1400.0, 1300.0, 1300.0, 1200.0]),
+        ];
+        let forces = classer_par_force(&equipes, &Format::default(), &Bareme::default());
+        let noms: Vec<&str> = forces.iter().map(|f| f.equipe.as_str()).collect();
+        assert_eq!(noms, vec!["FORTE", "MOYENNE", "FAIBLE"]);
+    }
+}
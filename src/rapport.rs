@@ -0,0 +1,610 @@
+use chrono::NaiveDate;
+
+use crate::calendrier::{self, Evenement};
+use crate::club::Club;
+use crate::date::Date;
+use crate::echapper_xml;
+use crate::equipe::Equipe;
+use crate::joueur::Joueur;
+use crate::poule::Classement;
+use crate::tour::{Cote, Tour};
+
+/// nombre de jours séparant au plus deux rencontres d'un même week-end
+const WEEKEND: i64 = 2;
+
+#[derive(Clone, Debug)]
+/// les informations d'une équipe du club présentées dans le rapport
+pub struct RapportEquipe {
+    /// l'équipe
+    pub equipe: Equipe,
+    /// le classement officiel de sa poule
+    pub classements: Vec<Classement>,
+    /// sa dernière rencontre jouée
+    pub dernier: Option<Evenement>,
+    /// sa prochaine rencontre
+    pub prochain: Option<Evenement>,
+}
+
+impl RapportEquipe {
+    /// construit les informations de l'équipe à partir du classement et des tours de sa poule
+    pub fn new(equipe: Equipe, classements: Vec<Classement>, tours: &[(u8, Tour)]) -> Self {
+        let mut evenements = calendrier::evenements(&equipe, tours);
+        calendrier::trier(&mut evenements);
+        let dernier = evenements
+            .iter()
+            .rfind(|e| e.rencontre.est_jouee())
+            .cloned();
+        let prochain = evenements.into_iter().find(|e| !e.rencontre.est_jouee());
+        RapportEquipe {
+            equipe,
+            classements,
+            dernier,
+            prochain,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// le rapport d'activité d'un club
+pub struct Rapport {
+    /// le club
+    pub club: Club,
+    /// la date du rapport
+    pub date: Date,
+    /// les équipes du club
+    pub equipes: Vec<RapportEquipe>,
+    /// les joueurs ayant le plus progressé depuis le début de la phase, du meilleur au moins bon
+    pub progressions: Vec<Joueur>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// les gabarits d'un rapport
+///
+/// Le gabarit du document accepte les variables `{{club}}`, `{{date}}`, `{{resultats}}`,
+/// `{{rencontres}}`, `{{progressions}}` et `{{equipes}}`, cette dernière étant remplacée
+/// par le gabarit d'équipe rempli pour chaque équipe. Le gabarit d'équipe accepte
+/// `{{equipe}}`, `{{epreuve}}`, `{{division}}`, `{{poule}}` et `{{classement}}`.
+pub struct Gabarits {
+    /// le gabarit du document complet
+    pub document: String,
+    /// le gabarit de la section de chaque équipe
+    pub equipe: String,
+}
+
+impl Gabarits {
+    /// les gabarits HTML par défaut, produisant une page autonome
+    pub fn html() -> Self {
+        Gabarits {
+            document: r#"<!DOCTYPE html>
+<html lang="fr">
+<head>
+<meta charset="utf-8">
+<title>{{club}}</title>
+<style>
+body { font-family: sans-serif; max-width: 60em; margin: auto; padding: 1em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; }
+th { background: #eee; }
+tr.club { font-weight: bold; }
+</style>
+</head>
+<body>
+<h1>{{club}}</h1>
+<p>Rapport du {{date}}</p>
+<h2>Derniers résultats</h2>
+{{resultats}}
+<h2>Prochaines rencontres</h2>
+{{rencontres}}
+<h2>Progressions</h2>
+{{progressions}}
+<h2>Classements</h2>
+{{equipes}}
+</body>
+</html>
+"#
+            .to_string(),
+            equipe: r#"<section>
+<h3>{{equipe}}</h3>
+<p>{{division}} - poule {{poule}}</p>
+{{classement}}
+</section>
+"#
+            .to_string(),
+        }
+    }
+
+    /// les gabarits Markdown par défaut
+    pub fn markdown() -> Self {
+        Gabarits {
+            document: "# {{club}}
+
+Rapport du {{date}}
+
+## Derniers résultats
+
+{{resultats}}
+## Prochaines rencontres
+
+{{rencontres}}
+## Progressions
+
+{{progressions}}
+## Classements
+
+{{equipes}}"
+                .to_string(),
+            equipe: "### {{equipe}}
+
+{{division}} - poule {{poule}}
+
+{{classement}}
+"
+            .to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+// le format de sortie du rapport
+enum Sortie {
+    Html,
+    Markdown,
+}
+
+impl Sortie {
+    fn echapper(&self, texte: &str) -> String {
+        match self {
+            Sortie::Html => echapper_xml(texte),
+            Sortie::Markdown => {
+                let mut echappe = String::with_capacity(texte.len());
+                for c in texte.chars() {
+                    if matches!(c, '\\' | '|' | '*' | '_' | '[' | ']' | '`') {
+                        echappe.push('\\');
+                    }
+                    echappe.push(c);
+                }
+                echappe
+            }
+        }
+    }
+}
+
+// un tableau du rapport, dont certaines lignes sont mises en évidence
+struct Tableau {
+    entetes: Vec<&'static str>,
+    lignes: Vec<(Vec<String>, bool)>,
+}
+
+impl Tableau {
+    fn rendre(&self, sortie: Sortie, vide: &str) -> String {
+        if self.lignes.is_empty() {
+            return match sortie {
+                Sortie::Html => format!("<p>{}</p>\n", sortie.echapper(vide)),
+                Sortie::Markdown => format!("{}\n", sortie.echapper(vide)),
+            };
+        }
+        let mut texte = String::new();
+        match sortie {
+            Sortie::Html => {
+                texte.push_str("<table>\n<tr>");
+                for e in &self.entetes {
+                    texte.push_str(&format!("<th>{}</th>", sortie.echapper(e)));
+                }
+                texte.push_str("</tr>\n");
+                for (cellules, evidence) in &self.lignes {
+                    texte.push_str(if *evidence {
+                        "<tr class=\"club\">"
+                    } else {
+                        "<tr>"
+                    });
+                    for c in cellules {
+                        texte.push_str(&format!("<td>{}</td>", sortie.echapper(c)));
+                    }
+                    texte.push_str("</tr>\n");
+                }
+                texte.push_str("</table>\n");
+            }
+            Sortie::Markdown => {
+                texte.push_str(&format!("| {} |\n", self.entetes.join(" | ")));
+                texte.push_str(&format!("|{}\n", " --- |".repeat(self.entetes.len())));
+                for (cellules, evidence) in &self.lignes {
+                    let cellules: Vec<String> = cellules
+                        .iter()
+                        .map(|c| {
+                            let c = sortie.echapper(c);
+                            if *evidence && !c.is_empty() {
+                                format!("**{c}**")
+                            } else {
+                                c
+                            }
+                        })
+                        .collect();
+                    texte.push_str(&format!("| {} |\n", cellules.join(" | ")));
+                }
+            }
+        }
+        texte
+    }
+}
+
+impl Rapport {
+    /// construit le rapport à partir des équipes du club et de ses joueurs,
+    /// en retenant les `progressions` joueurs ayant le plus progressé
+    pub fn new(
+        club: Club,
+        equipes: Vec<RapportEquipe>,
+        joueurs: &[Joueur],
+        progressions: usize,
+    ) -> Self {
+        let mut joueurs = joueurs.to_vec();
+        joueurs.sort_by(|a, b| b.progression_phase().total_cmp(&a.progression_phase()));
+        joueurs.truncate(progressions);
+        Rapport {
+            club,
+            date: Date::now(),
+            equipes,
+            progressions: joueurs,
+        }
+    }
+
+    /// construit le rapport du club en interrogeant l'API
+    pub async fn collecter(club: &Club, progressions: usize) -> Self {
+        log::info!("Construction du rapport du club {}...", club.nom);
+        let mut equipes = vec![];
+        for equipe in club.api_equipes().await {
+            let classements = equipe.poule.classement().await;
+            let tours = equipe.poule.get_tours().await;
+            equipes.push(RapportEquipe::new(equipe, classements, &tours));
+        }
+        let joueurs = club.api_joueurs().await;
+        Rapport::new(club.clone(), equipes, &joueurs, progressions)
+    }
+
+    /// les dernières rencontres jouées par les équipes du club, lors du week-end le plus récent
+    pub fn derniers_resultats(&self) -> Vec<&Evenement> {
+        let derniers: Vec<&Evenement> = self
+            .equipes
+            .iter()
+            .filter_map(|e| e.dernier.as_ref())
+            .collect();
+        let Some(recent) = derniers.iter().filter_map(|e| jour(&e.date)).max() else {
+            return vec![];
+        };
+        let mut resultats: Vec<&Evenement> = derniers
+            .into_iter()
+            .filter(|e| jour(&e.date).is_some_and(|d| (recent - d).num_days() <= WEEKEND))
+            .collect();
+        resultats.sort_by_key(|e| e.numero_equipe);
+        resultats
+    }
+
+    /// les prochaines rencontres des équipes du club, par date
+    pub fn prochaines_rencontres(&self) -> Vec<&Evenement> {
+        let mut rencontres: Vec<&Evenement> = self
+            .equipes
+            .iter()
+            .filter_map(|e| e.prochain.as_ref())
+            .collect();
+        rencontres.sort_by(|a, b| {
+            a.date
+                .cmp(&b.date)
+                .then(a.numero_equipe.cmp(&b.numero_equipe))
+        });
+        rencontres
+    }
+
+    /// rend le rapport au format HTML
+    pub fn html(&self, gabarits: &Gabarits) -> String {
+        self.rendre(gabarits, Sortie::Html)
+    }
+
+    /// rend le rapport au format Markdown
+    pub fn markdown(&self, gabarits: &Gabarits) -> String {
+        self.rendre(gabarits, Sortie::Markdown)
+    }
+
+    fn rendre(&self, gabarits: &Gabarits, sortie: Sortie) -> String {
+        let resultats = Tableau {
+            entetes: vec!["Équipe", "Adversaire", "Lieu", "Score"],
+            lignes: self
+                .derniers_resultats()
+                .into_iter()
+                .map(|e| {
+                    let score = match e.rencontre.score(&nom_dans_rencontre(e)) {
+                        Some((pour, contre)) => format!("{pour} - {contre}"),
+                        None => "forfait".to_string(),
+                    };
+                    (
+                        vec![
+                            e.equipe.clone(),
+                            e.adversaire.clone(),
+                            lieu(e.cote).to_string(),
+                            score,
+                        ],
+                        false,
+                    )
+                })
+                .collect(),
+        };
+        let rencontres = Tableau {
+            entetes: vec!["Date", "Équipe", "Adversaire", "Lieu"],
+            lignes: self
+                .prochaines_rencontres()
+                .into_iter()
+                .map(|e| {
+                    (
+                        vec![
+                            e.date.to_string(),
+                            e.equipe.clone(),
+                            e.adversaire.clone(),
+                            lieu(e.cote).to_string(),
+                        ],
+                        false,
+                    )
+                })
+                .collect(),
+        };
+        let progressions = Tableau {
+            entetes: vec!["Joueur", "Points", "Points virtuels", "Progression"],
+            lignes: self
+                .progressions
+                .iter()
+                .map(|j| {
+                    (
+                        vec![
+                            j.display(),
+                            format!("{:.0}", j.point),
+                            format!("{:.0}", j.r#_virtual),
+                            format!("{:+.0}", j.progression_phase()),
+                        ],
+                        false,
+                    )
+                })
+                .collect(),
+        };
+        let equipes: String = self
+            .equipes
+            .iter()
+            .map(|e| {
+                let classement = Tableau {
+                    entetes: vec!["Rang", "Équipe", "Joués", "Points", "V", "N", "D", "F"],
+                    lignes: e
+                        .classements
+                        .iter()
+                        .map(|c| {
+                            (
+                                vec![
+                                    c.classement.to_string(),
+                                    c.equipe.clone(),
+                                    c.joues.to_string(),
+                                    c.points.to_string(),
+                                    c.victoires.to_string(),
+                                    c.nuls.to_string(),
+                                    c.defaites.to_string(),
+                                    c.forfaits.to_string(),
+                                ],
+                                e.equipe.est_nommee(&c.equipe),
+                            )
+                        })
+                        .collect(),
+                };
+                remplir(
+                    &gabarits.equipe,
+                    &[
+                        ("equipe", sortie.echapper(e.equipe.nom_rencontre())),
+                        ("epreuve", sortie.echapper(&e.equipe._epreuve)),
+                        ("division", sortie.echapper(&e.equipe._division)),
+                        ("poule", sortie.echapper(&e.equipe.poule.numero)),
+                        (
+                            "classement",
+                            classement.rendre(sortie, "Classement indisponible"),
+                        ),
+                    ],
+                )
+            })
+            .collect();
+        remplir(
+            &gabarits.document,
+            &[
+                ("club", sortie.echapper(&self.club.nom)),
+                ("date", self.date.to_string()),
+                ("resultats", resultats.rendre(sortie, "Aucun résultat")),
+                (
+                    "rencontres",
+                    rencontres.rendre(sortie, "Aucune rencontre à venir"),
+                ),
+                ("progressions", progressions.rendre(sortie, "Aucun joueur")),
+                ("equipes", equipes),
+            ],
+        )
+    }
+}
+
+// remplace les variables `{{nom}}` du gabarit par leur valeur, en un seul parcours :
+// une valeur contenant elle-même `{{...}}` est insérée telle quelle
+// une variable inconnue est conservée
+fn remplir(gabarit: &str, variables: &[(&str, String)]) -> String {
+    let mut texte = String::with_capacity(gabarit.len());
+    let mut reste = gabarit;
+    while let Some(debut) = reste.find("{{") {
+        texte.push_str(&reste[..debut]);
+        let suite = &reste[debut + 2..];
+        let variable = suite.find("}}").and_then(|fin| {
+            let (_, valeur) = variables.iter().find(|(nom, _)| *nom == &suite[..fin])?;
+            Some((valeur, fin))
+        });
+        match variable {
+            Some((valeur, fin)) => {
+                texte.push_str(valeur);
+                reste = &suite[fin + 2..];
+            }
+            None => {
+                texte.push_str("{{");
+                reste = suite;
+            }
+        }
+    }
+    texte.push_str(reste);
+    texte
+}
+
+// le nom de l'équipe du club tel qu'écrit dans la rencontre
+fn nom_dans_rencontre(e: &Evenement) -> String {
+    match e.cote {
+        Cote::Domicile => e.rencontre.a.clone(),
+        Cote::Exterieur => e.rencontre.b.clone(),
+    }
+}
+
+fn lieu(cote: Cote) -> &'static str {
+    match cote {
+        Cote::Domicile => "Domicile",
+        Cote::Exterieur => "Extérieur",
+    }
+}
+
+fn jour(date: &Date) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(date.annee.into(), date.mois.into(), date.jour.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equipe::EquipeApi;
+    use crate::tour::Rencontres;
+
+    const RENCONTRES: &str = r#"<liste>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>MONTPELLIER TT 3</equa><equb>NIMES ASPTT 2</equb><scorea>9</scorea><scoreb>5</scoreb><dateprevue>28/09/2024</dateprevue></tour>
+    <tour><libelle>Poule 3 - tour n°2 du 12/10/2024</libelle><equa>ALES TT 1</equa><equb>MONTPELLIER TT 3</equb><dateprevue>12/10/2024</dateprevue></tour>
+</liste>"#;
+
+    fn rapport() -> Rapport {
+        let equipe: Equipe = serde_json::from_str::<EquipeApi>(
+            r#"{
+                "idequipe": "42",
+                "libequipe": "MONTPELLIER TT 3 - Phase 1",
+                "libepr": "FED_Championnat de France par Equipes Masculin",
+                "libdivision": "Régionale 1",
+                "liendivision": "cx_poule=1234&D1=5678&organisme_pere=11"
+            }"#,
+        )
+        .unwrap()
+        .into();
+        let rencontres = Rencontres::from_xml(RENCONTRES).unwrap().rencontres;
+        let tours: Vec<(u8, Tour)> = rencontres
+            .into_iter()
+            .map(|r| {
+                (
+                    r.tour,
+                    Tour {
+                        numero: r.tour,
+                        date: r.date.clone(),
+                        rencontres: Rencontres {
+                            rencontres: vec![r],
+                        },
+                    },
+                )
+            })
+            .collect();
+        let classement: Classement = serde_json::from_value(serde_json::json!({
+            "numero_poule": 3,
+            "classement": 1,
+            "equipe": "MONTPELLIER TT 3",
+            "joues": 1,
+            "points": 3,
+            "victoires": 1,
+            "defaites": 0,
+            "nuls": 0,
+            "forfaits": 0,
+            "parties_gagnees": 9,
+            "parties_perdues": 5,
+            "numero": "11340010"
+        }))
+        .unwrap();
+        let club: Club = serde_json::from_value(serde_json::json!({
+            "numero": "11340010",
+            "nom": "MONTPELLIER TT <&>",
+            "nomsalle": "",
+            "adressesalle1": "",
+            "adressesalle2": "",
+            "adressesalle3": "",
+            "codepsalle": "",
+            "villesalle": "",
+            "latitude": "",
+            "longitude": ""
+        }))
+        .unwrap();
+        let joueurs: Vec<Joueur> = [(1, 10.0), (2, 50.0), (3, -20.0)]
+            .into_iter()
+            .map(|(i, progression): (u8, f32)| Joueur {
+                licence: i.to_string(),
+                nom: format!("JOUEUR{i}"),
+                prenom: "Jean".to_string(),
+                points_init: 1000.0,
+                point: 1000.0,
+                r#_virtual: 1000.0 + progression,
+                categorie: None,
+            })
+            .collect();
+        Rapport::new(
+            club,
+            vec![RapportEquipe::new(equipe, vec![classement], &tours)],
+            &joueurs,
+            2,
+        )
+    }
+
+    #[test]
+    fn test_rapport() {
+        let rapport = rapport();
+        let equipe = &rapport.equipes[0];
+        assert_eq!(equipe.dernier.as_ref().unwrap().tour, 1);
+        assert_eq!(equipe.prochain.as_ref().unwrap().adversaire, "ALES TT 1");
+        assert_eq!(rapport.derniers_resultats().len(), 1);
+        let licences: Vec<&str> = rapport
+            .progressions
+            .iter()
+            .map(|j| j.licence.as_str())
+            .collect();
+        assert_eq!(licences, vec!["2", "1"]);
+    }
+
+    #[test]
+    fn test_html() {
+        let html = rapport().html(&Gabarits::html());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>MONTPELLIER TT &lt;&amp;&gt;</title>"));
+        assert!(html.contains("<td>NIMES ASPTT 2</td><td>Domicile</td><td>9 - 5</td>"));
+        assert!(html.contains("<tr class=\"club\"><td>1</td><td>MONTPELLIER TT 3</td>"));
+        assert!(!html.contains("{{"));
+    }
+
+    #[test]
+    fn test_markdown() {
+        let gabarits = Gabarits {
+            equipe: "* {{equipe}} ({{division}})\n".to_string(),
+            ..Gabarits::markdown()
+        };
+        let markdown = rapport().markdown(&gabarits);
+        assert!(markdown.starts_with("# MONTPELLIER TT <&>\n"));
+        assert!(markdown.contains("| MONTPELLIER TT 3 | NIMES ASPTT 2 | Domicile | 9 - 5 |"));
+        assert!(markdown.contains("| Jean JOUEUR2 | 1000 | 1050 | +50 |"));
+        assert!(markdown.contains("* MONTPELLIER TT 3 (Régionale 1)\n"));
+    }
+
+    #[test]
+    fn test_remplir() {
+        let variables = [
+            ("club", "{{date}} [TT]".to_string()),
+            ("date", "2024-10-01".to_string()),
+        ];
+        assert_eq!(
+            remplir("{{club}} le {{date}} {{inconnue}} {{", &variables),
+            "{{date}} [TT] le 2024-10-01 {{inconnue}} {{"
+        );
+        assert_eq!(
+            Sortie::Markdown.echapper("A_B *C* [D](e) `f` g\\h|i"),
+            "A\\_B \\*C\\* \\[D\\](e) \\`f\\` g\\\\h\\|i"
+        );
+    }
+}
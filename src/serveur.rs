@@ -0,0 +1,394 @@
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::club::Club;
+use crate::joueur::Joueur;
+use crate::poule::Poule;
+use crate::ErreurApi;
+
+#[derive(Clone, Debug)]
+// une réponse mise en cache
+struct Entree {
+    creation: Instant,
+    corps: Arc<String>,
+    etag: String,
+}
+
+#[derive(Debug)]
+/// le cache des réponses de la passerelle, pour limiter les appels à l'API
+pub struct Cache {
+    duree: Duration,
+    capacite: usize,
+    entrees: Mutex<HashMap<String, Entree>>,
+}
+
+impl Cache {
+    /// nombre maximal de réponses conservées par défaut
+    pub const CAPACITE: usize = 1000;
+
+    /// créé un cache conservant les réponses pendant la durée donnée
+    pub fn new(duree: Duration) -> Self {
+        Self::avec_capacite(duree, Self::CAPACITE)
+    }
+
+    /// créé un cache conservant au plus `capacite` réponses pendant la durée donnée
+    pub fn avec_capacite(duree: Duration, capacite: usize) -> Self {
+        Cache {
+            duree,
+            capacite,
+            entrees: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // retourne la réponse en cache, ou la produit et la met en cache
+    async fn obtenir<F, Fut, T>(&self, cle: &str, producteur: F) -> Result<Entree, StatusCode>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, ErreurApi>>,
+        T: Serialize,
+    {
+        if let Some(entree) = self.entrees.lock().expect("Cache corrompu").get(cle) {
+            if entree.creation.elapsed() < self.duree {
+                return Ok(entree.clone());
+            }
+        }
+        let valeur = match producteur().await {
+            Ok(valeur) => valeur,
+            Err(ErreurApi::Introuvable) => return Err(StatusCode::NOT_FOUND),
+            Err(e) => {
+                log::error!("Erreur lors de la récupération de {cle} : {e}");
+                return Err(StatusCode::BAD_GATEWAY);
+            }
+        };
+        let corps = serde_json::to_string(&valeur).expect("Les données sont sérialisables");
+        let mut hacheur = DefaultHasher::new();
+        corps.hash(&mut hacheur);
+        let entree = Entree {
+            creation: Instant::now(),
+            etag: format!("\"{:016x}\"", hacheur.finish()),
+            corps: Arc::new(corps),
+        };
+        let mut entrees = self.entrees.lock().expect("Cache corrompu");
+        // on purge les réponses expirées, puis les plus anciennes si le cache reste plein
+        entrees.retain(|_, e| e.creation.elapsed() < self.duree);
+        while entrees.len() >= self.capacite.max(1) {
+            let Some(ancienne) = entrees
+                .iter()
+                .min_by_key(|(_, e)| e.creation)
+                .map(|(c, _)| c.clone())
+            else {
+                break;
+            };
+            entrees.remove(&ancienne);
+        }
+        entrees.insert(cle.to_string(), entree.clone());
+        Ok(entree)
+    }
+}
+
+/// construit le routeur de la passerelle
+///
+/// Les réponses sont au format JSON de la bibliothèque, accompagnées d'un `ETag` :
+/// une requête portant un `If-None-Match` correspondant reçoit une réponse 304.
+pub fn routeur(cache: Arc<Cache>) -> Router {
+    Router::new()
+        .route("/clubs/{id}", get(club))
+        .route("/clubs/{id}/joueurs", get(joueurs_club))
+        .route("/clubs/{id}/equipes", get(equipes_club))
+        .route("/clubs/{id}/calendrier", get(calendrier_club))
+        .route("/poules/{division}/{numero}/classement", get(classement))
+        .route("/poules/{division}/{numero}/tours", get(tours))
+        .route("/joueurs/{licence}", get(joueur))
+        .route("/joueurs/{licence}/parties", get(parties))
+        .with_state(cache)
+}
+
+/// lance la passerelle à l'adresse donnée, jusqu'à son arrêt
+pub async fn servir(adresse: &str, duree_cache: Duration) -> std::io::Result<()> {
+    let ecoute = tokio::net::TcpListener::bind(adresse).await?;
+    log::info!("Passerelle à l'écoute sur {}", ecoute.local_addr()?);
+    axum::serve(ecoute, routeur(Arc::new(Cache::new(duree_cache)))).await
+}
+
+// répond avec la valeur produite ou mise en cache, en gérant l'ETag
+async fn repondre<F, Fut, T>(
+    cache: &Cache,
+    cle: &str,
+    entetes: &HeaderMap,
+    producteur: F,
+) -> Response
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, ErreurApi>>,
+    T: Serialize,
+{
+    let entree = match cache.obtenir(cle, producteur).await {
+        Ok(entree) => entree,
+        Err(statut) => {
+            let message = serde_json::json!({ "erreur": statut.canonical_reason() });
+            return (statut, axum::Json(message)).into_response();
+        }
+    };
+    let etag =
+        HeaderValue::from_str(&entree.etag).expect("Un ETag est une valeur d'en-tête valide");
+    let controle = HeaderValue::from_str(&format!("max-age={}", cache.duree.as_secs()))
+        .expect("Une durée est une valeur d'en-tête valide");
+    let correspond = entetes
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| {
+            v.split(',')
+                .any(|e| e.trim() == entree.etag || e.trim() == "*")
+        });
+    if correspond {
+        return (
+            StatusCode::NOT_MODIFIED,
+            [(header::ETAG, etag), (header::CACHE_CONTROL, controle)],
+        )
+            .into_response();
+    }
+    (
+        StatusCode::OK,
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            ),
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, controle),
+        ],
+        entree.corps.as_str().to_string(),
+    )
+        .into_response()
+}
+
+// refuse une requête dont un paramètre n'est pas alphanumérique :
+// les paramètres sont insérés dans les URL de l'API, où ils ne doivent pas ajouter d'argument
+fn refuser(parametres: &[&str]) -> Option<Response> {
+    let valide = |p: &&str| !p.is_empty() && p.chars().all(|c| c.is_ascii_alphanumeric());
+    if parametres.iter().all(valide) {
+        return None;
+    }
+    let statut = StatusCode::BAD_REQUEST;
+    let message = serde_json::json!({ "erreur": statut.canonical_reason() });
+    Some((statut, axum::Json(message)).into_response())
+}
+
+async fn club(
+    State(cache): State<Arc<Cache>>,
+    Path(id): Path<String>,
+    entetes: HeaderMap,
+) -> Response {
+    if let Some(refus) = refuser(&[&id]) {
+        return refus;
+    }
+    repondre(&cache, &format!("clubs/{id}"), &entetes, || async move {
+        Club::try_new(&id).await
+    })
+    .await
+}
+
+async fn joueurs_club(
+    State(cache): State<Arc<Cache>>,
+    Path(id): Path<String>,
+    entetes: HeaderMap,
+) -> Response {
+    if let Some(refus) = refuser(&[&id]) {
+        return refus;
+    }
+    repondre(
+        &cache,
+        &format!("clubs/{id}/joueurs"),
+        &entetes,
+        || async move { Club::try_new(&id).await?.try_api_joueurs().await },
+    )
+    .await
+}
+
+async fn equipes_club(
+    State(cache): State<Arc<Cache>>,
+    Path(id): Path<String>,
+    entetes: HeaderMap,
+) -> Response {
+    if let Some(refus) = refuser(&[&id]) {
+        return refus;
+    }
+    repondre(
+        &cache,
+        &format!("clubs/{id}/equipes"),
+        &entetes,
+        || async move { Club::try_new(&id).await?.try_api_equipes().await },
+    )
+    .await
+}
+
+async fn calendrier_club(
+    State(cache): State<Arc<Cache>>,
+    Path(id): Path<String>,
+    entetes: HeaderMap,
+) -> Response {
+    if let Some(refus) = refuser(&[&id]) {
+        return refus;
+    }
+    repondre(
+        &cache,
+        &format!("clubs/{id}/calendrier"),
+        &entetes,
+        || async move { Club::try_new(&id).await?.try_calendrier().await },
+    )
+    .await
+}
+
+async fn classement(
+    State(cache): State<Arc<Cache>>,
+    Path((division, numero)): Path<(String, String)>,
+    entetes: HeaderMap,
+) -> Response {
+    if let Some(refus) = refuser(&[&division, &numero]) {
+        return refus;
+    }
+    let cle = format!("poules/{division}/{numero}/classement");
+    repondre(&cache, &cle, &entetes, || async move {
+        Poule { division, numero }.try_classement().await
+    })
+    .await
+}
+
+async fn tours(
+    State(cache): State<Arc<Cache>>,
+    Path((division, numero)): Path<(String, String)>,
+    entetes: HeaderMap,
+) -> Response {
+    if let Some(refus) = refuser(&[&division, &numero]) {
+        return refus;
+    }
+    let cle = format!("poules/{division}/{numero}/tours");
+    repondre(&cache, &cle, &entetes, || async move {
+        let tours: Vec<_> = Poule { division, numero }
+            .try_get_tours()
+            .await?
+            .into_iter()
+            .map(|(_, t)| t)
+            .collect();
+        Ok(tours)
+    })
+    .await
+}
+
+async fn joueur(
+    State(cache): State<Arc<Cache>>,
+    Path(licence): Path<String>,
+    entetes: HeaderMap,
+) -> Response {
+    if let Some(refus) = refuser(&[&licence]) {
+        return refus;
+    }
+    repondre(
+        &cache,
+        &format!("joueurs/{licence}"),
+        &entetes,
+        || async move { Joueur::try_new(&licence).await },
+    )
+    .await
+}
+
+async fn parties(
+    State(cache): State<Arc<Cache>>,
+    Path(licence): Path<String>,
+    entetes: HeaderMap,
+) -> Response {
+    if let Some(refus) = refuser(&[&licence]) {
+        return refus;
+    }
+    repondre(
+        &cache,
+        &format!("joueurs/{licence}/parties"),
+        &entetes,
+        || async move {
+            Joueur::try_new(&licence)
+                .await?
+                .try_api_parties()
+                .await?
+                .ok_or(ErreurApi::Introuvable)
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_cache_et_etag() {
+        let cache = Cache::new(Duration::from_secs(60));
+        let appels = Arc::new(AtomicUsize::new(0));
+        let producteur = || {
+            let appels = appels.clone();
+            async move {
+                appels.fetch_add(1, Ordering::SeqCst);
+                Ok(vec![1, 2, 3])
+            }
+        };
+
+        let reponse = repondre(&cache, "test", &HeaderMap::new(), producteur).await;
+        assert_eq!(reponse.status(), StatusCode::OK);
+        let etag = reponse.headers()[header::ETAG].clone();
+        assert_eq!(
+            reponse.headers()[header::CACHE_CONTROL].to_str().unwrap(),
+            "max-age=60"
+        );
+
+        let mut entetes = HeaderMap::new();
+        entetes.insert(header::IF_NONE_MATCH, etag.clone());
+        let reponse = repondre(&cache, "test", &entetes, producteur).await;
+        assert_eq!(reponse.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(reponse.headers()[header::ETAG], etag);
+        // la seconde réponse provient du cache
+        assert_eq!(appels.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_erreurs() {
+        let cache = Cache::new(Duration::ZERO);
+        let reponse = repondre(&cache, "absent", &HeaderMap::new(), || async {
+            Err::<u8, _>(ErreurApi::Introuvable)
+        })
+        .await;
+        assert_eq!(reponse.status(), StatusCode::NOT_FOUND);
+        let reponse = repondre(&cache, "invalide", &HeaderMap::new(), || async {
+            Err::<u8, _>(ErreurApi::Document("API injoignable".to_string()))
+        })
+        .await;
+        assert_eq!(reponse.status(), StatusCode::BAD_GATEWAY);
+
+        // un paramètre décodé ne peut ajouter d'argument à la requête vers l'API
+        assert!(refuser(&["11340010"]).is_none());
+        let refus = refuser(&["5678", "1234&action=classement"]).unwrap();
+        assert_eq!(refus.status(), StatusCode::BAD_REQUEST);
+        assert!(refuser(&[""]).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_capacite() {
+        let cache = Cache::avec_capacite(Duration::from_secs(60), 2);
+        for cle in ["a", "b", "c"] {
+            repondre(&cache, cle, &HeaderMap::new(), || async { Ok(0) }).await;
+        }
+        let entrees = cache.entrees.lock().unwrap();
+        assert_eq!(entrees.len(), 2);
+        // la réponse la plus ancienne a été évincée
+        assert!(!entrees.contains_key("a"));
+    }
+}
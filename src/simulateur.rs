@@ -0,0 +1,486 @@
+use axum::body::Body;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::club::Club;
+use crate::echapper_xml;
+use crate::equipe::Equipe;
+use crate::joueur::Joueur;
+use crate::journee::Journee;
+use crate::poule::{Classement, Poule};
+use crate::tour::{Cote, Rencontre};
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// un joueur du jeu de données, avec son club et ses journées
+pub struct JoueurSimule {
+    /// le joueur
+    pub joueur: Joueur,
+    /// numéro de son club
+    pub club: String,
+    /// ses journées de la saison
+    #[serde(default)]
+    pub journees: Vec<Journee>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une équipe du jeu de données, avec son club
+pub struct EquipeSimulee {
+    /// l'équipe
+    pub equipe: Equipe,
+    /// numéro de son club
+    pub club: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// une poule du jeu de données, avec ses rencontres et son classement
+pub struct PouleSimulee {
+    /// la poule
+    pub poule: Poule,
+    /// ses rencontres
+    pub rencontres: Vec<Rencontre>,
+    /// son classement officiel
+    pub classements: Vec<Classement>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+/// le jeu de données servi par le simulateur, au format JSON de la bibliothèque
+pub struct Donnees {
+    /// les clubs
+    #[serde(default)]
+    pub clubs: Vec<Club>,
+    /// les joueurs
+    #[serde(default)]
+    pub joueurs: Vec<JoueurSimule>,
+    /// les équipes
+    #[serde(default)]
+    pub equipes: Vec<EquipeSimulee>,
+    /// les poules
+    #[serde(default)]
+    pub poules: Vec<PouleSimulee>,
+}
+
+impl Donnees {
+    /// enregistre le jeu de données au format JSON
+    pub fn enregistrer(&self, chemin: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(chemin, serde_json::to_string_pretty(self)?)
+    }
+
+    /// charge un jeu de données enregistré au format JSON
+    pub fn charger(chemin: impl AsRef<std::path::Path>) -> std::io::Result<Donnees> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(chemin)?)?)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// une défaillance injectée dans les réponses d'un endpoint
+pub struct Defaillance {
+    /// délai avant la réponse
+    pub latence: Duration,
+    /// code d'erreur HTTP retourné à la place de la réponse
+    pub statut: Option<u16>,
+    /// tronque la réponse pour la rendre invalide
+    pub malformee: bool,
+}
+
+#[derive(Debug, Default)]
+// l'état partagé du simulateur
+struct Etat {
+    donnees: RwLock<Donnees>,
+    // les défaillances, par préfixe du chemin de l'endpoint
+    defaillances: RwLock<Vec<(String, Defaillance)>>,
+}
+
+#[derive(Clone, Debug, Default)]
+/// un serveur simulant l'API FFTT à partir d'un jeu de données en mémoire
+///
+/// Il sert les endpoints utilisés par la bibliothèque, aux mêmes formats que l'API ;
+/// il suffit de lui faire pointer la base des endpoints avec [`crate::avec_api`].
+pub struct Simulateur {
+    etat: Arc<Etat>,
+}
+
+impl Simulateur {
+    /// créé le simulateur servant le jeu de données
+    pub fn new(donnees: Donnees) -> Self {
+        Simulateur {
+            etat: Arc::new(Etat {
+                donnees: RwLock::new(donnees),
+                defaillances: RwLock::new(vec![]),
+            }),
+        }
+    }
+
+    /// remplace le jeu de données servi
+    pub fn remplacer(&self, donnees: Donnees) {
+        *self.etat.donnees.write().expect("Simulateur corrompu") = donnees;
+    }
+
+    /// modifie le jeu de données servi
+    pub fn modifier(&self, modification: impl FnOnce(&mut Donnees)) {
+        modification(&mut self.etat.donnees.write().expect("Simulateur corrompu"));
+    }
+
+    /// injecte une défaillance dans les endpoints dont le chemin commence par `prefixe`
+    /// (`/` pour tous les endpoints)
+    pub fn injecter(&self, prefixe: &str, defaillance: Defaillance) {
+        let mut defaillances = self.etat.defaillances.write().expect("Simulateur corrompu");
+        defaillances.retain(|(p, _)| p != prefixe);
+        defaillances.push((prefixe.to_string(), defaillance));
+    }
+
+    /// retire toutes les défaillances injectées
+    pub fn retablir(&self) {
+        self.etat
+            .defaillances
+            .write()
+            .expect("Simulateur corrompu")
+            .clear();
+    }
+
+    /// construit le routeur du simulateur
+    pub fn routeur(&self) -> Router {
+        Router::new()
+            .route("/joueur/{licence}", get(joueur))
+            .route("/parties/{licence}", get(parties))
+            .route("/club/{id}/equipes", get(equipes))
+            .route("/proxy/xml_club_detail.php", get(club))
+            .route("/proxy/xml_licence_b.php", get(licences))
+            .route("/proxy/xml_result_equ.php", get(resultats))
+            .layer(middleware::from_fn_with_state(self.etat.clone(), defaillir))
+            .with_state(self.etat.clone())
+    }
+
+    /// lance le simulateur à l'adresse donnée dans une tâche de fond,
+    /// et retourne l'adresse effective (utile avec le port 0)
+    pub async fn lancer(&self, adresse: &str) -> std::io::Result<SocketAddr> {
+        let ecoute = tokio::net::TcpListener::bind(adresse).await?;
+        let adresse = ecoute.local_addr()?;
+        let routeur = self.routeur();
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(ecoute, routeur).await {
+                log::error!("Arrêt du simulateur : {e}");
+            }
+        });
+        log::info!("Simulateur à l'écoute sur {adresse}");
+        Ok(adresse)
+    }
+}
+
+// applique la défaillance du plus long préfixe correspondant au chemin demandé
+async fn defaillir(State(etat): State<Arc<Etat>>, requete: Request, suivant: Next) -> Response {
+    let chemin = requete.uri().path().to_string();
+    let defaillance = etat
+        .defaillances
+        .read()
+        .expect("Simulateur corrompu")
+        .iter()
+        .filter(|(p, _)| chemin.starts_with(p.as_str()))
+        .max_by_key(|(p, _)| p.len())
+        .map(|(_, d)| d.clone());
+    let Some(defaillance) = defaillance else {
+        return suivant.run(requete).await;
+    };
+    tokio::time::sleep(defaillance.latence).await;
+    if let Some(statut) = defaillance.statut {
+        return StatusCode::from_u16(statut)
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+            .into_response();
+    }
+    let reponse = suivant.run(requete).await;
+    if !defaillance.malformee {
+        return reponse;
+    }
+    let (parties, corps) = reponse.into_parts();
+    let corps = axum::body::to_bytes(corps, usize::MAX)
+        .await
+        .unwrap_or_default();
+    let tronque = corps.slice(..corps.len() / 2);
+    let mut parties = parties;
+    parties.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parties, Body::from(tronque))
+}
+
+fn json(valeur: serde_json::Value) -> Response {
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        valeur.to_string(),
+    )
+        .into_response()
+}
+
+fn xml(elements: &str) -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/xml; charset=utf-8")],
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<liste>{elements}</liste>"),
+    )
+        .into_response()
+}
+
+// écrit un élément XML et ses champs
+fn element(xml: &mut String, nom: &str, champs: &[(&str, String)]) {
+    let _ = write!(xml, "<{nom}>");
+    for (champ, valeur) in champs {
+        let _ = write!(xml, "<{champ}>{}</{champ}>", echapper_xml(valeur));
+    }
+    let _ = write!(xml, "</{nom}>");
+}
+
+async fn joueur(State(etat): State<Arc<Etat>>, Path(licence): Path<String>) -> Response {
+    let donnees = etat.donnees.read().expect("Simulateur corrompu");
+    let Some(j) = donnees.joueurs.iter().find(|j| j.joueur.licence == licence) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let j = &j.joueur;
+    json(serde_json::json!({
+        "licence": j.licence,
+        "nom": j.nom,
+        "prenom": j.prenom,
+        "initm": j.points_init,
+        "point": j.point,
+        "virtual": j.r#_virtual,
+        "cat": j.categorie,
+    }))
+}
+
+async fn parties(State(etat): State<Arc<Etat>>, Path(licence): Path<String>) -> Response {
+    let donnees = etat.donnees.read().expect("Simulateur corrompu");
+    let Some(j) = donnees.joueurs.iter().find(|j| j.joueur.licence == licence) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let journees: Vec<serde_json::Value> = j
+        .journees
+        .iter()
+        .map(|j| {
+            serde_json::json!({
+                "epreuve": j.epreuve,
+                "date": j.date.to_string(),
+                "ex": j.ex(),
+                "matchs": j.matchs().iter().map(|m| serde_json::json!({
+                    "nom": m.nom,
+                    "coeff": m.coeff,
+                    "ex": m.ex,
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    let matchs: usize = j.journees.iter().map(|j| j.matchs().len()).sum();
+    let ex: f32 = j.journees.iter().map(Journee::ex).sum();
+    json(serde_json::json!({
+        "list": [{
+            "processed": matchs,
+            "num_matchs": matchs,
+            "ex": ex,
+            "journees": journees,
+        }]
+    }))
+}
+
+async fn equipes(State(etat): State<Arc<Etat>>, Path(id): Path<String>) -> Response {
+    let donnees = etat.donnees.read().expect("Simulateur corrompu");
+    let equipes: Vec<serde_json::Value> = donnees
+        .equipes
+        .iter()
+        .filter(|e| e.club == id)
+        .map(|e| {
+            serde_json::json!({
+                "idequipe": e.equipe.id,
+                "libequipe": e.equipe.nom,
+                "libepr": e.equipe._epreuve,
+                "libdivision": e.equipe._division,
+                "liendivision": format!(
+                    "cx_poule={}&D1={}&organisme_pere=0",
+                    e.equipe.poule.numero, e.equipe.poule.division
+                ),
+            })
+        })
+        .collect();
+    json(serde_json::Value::Array(equipes))
+}
+
+async fn club(
+    State(etat): State<Arc<Etat>>,
+    Query(parametres): Query<HashMap<String, String>>,
+) -> Response {
+    let donnees = etat.donnees.read().expect("Simulateur corrompu");
+    let numero = parametres.get("club").cloned().unwrap_or_default();
+    let Some(c) = donnees.clubs.iter().find(|c| c.numero == numero) else {
+        return xml("");
+    };
+    let mut elements = String::new();
+    element(
+        &mut elements,
+        "club",
+        &[
+            ("numero", c.numero.clone()),
+            ("nom", c.nom.clone()),
+            ("nomsalle", c.nomsalle.clone()),
+            ("adressesalle1", c.adressesalle1.clone()),
+            ("adressesalle2", c.adressesalle2.clone()),
+            ("adressesalle3", c.adressesalle3.clone()),
+            ("codepsalle", c.codepsalle.clone()),
+            ("villesalle", c.villesalle.clone()),
+            ("latitude", c.latitude.clone()),
+            ("longitude", c.longitude.clone()),
+        ],
+    );
+    xml(&elements)
+}
+
+async fn licences(
+    State(etat): State<Arc<Etat>>,
+    Query(parametres): Query<HashMap<String, String>>,
+) -> Response {
+    let donnees = etat.donnees.read().expect("Simulateur corrompu");
+    let numero = parametres.get("club").cloned().unwrap_or_default();
+    let mut elements = String::new();
+    for j in donnees.joueurs.iter().filter(|j| j.club == numero) {
+        element(
+            &mut elements,
+            "licence",
+            &[
+                ("licence", j.joueur.licence.clone()),
+                ("nom", j.joueur.nom.clone()),
+                ("prenom", j.joueur.prenom.clone()),
+                ("numclub", j.club.clone()),
+                ("cat", j.joueur.categorie.clone().unwrap_or_default()),
+            ],
+        );
+    }
+    xml(&elements)
+}
+
+async fn resultats(
+    State(etat): State<Arc<Etat>>,
+    Query(parametres): Query<HashMap<String, String>>,
+) -> Response {
+    let donnees = etat.donnees.read().expect("Simulateur corrompu");
+    let parametre = |nom: &str| parametres.get(nom).cloned().unwrap_or_default();
+    let poule = Poule {
+        division: parametre("D1"),
+        numero: parametre("cx_poule"),
+    };
+    let Some(p) = donnees.poules.iter().find(|p| p.poule == poule) else {
+        return xml("");
+    };
+    let mut elements = String::new();
+    if parametre("action") == "classement" {
+        for c in &p.classements {
+            element(
+                &mut elements,
+                "classement",
+                &[
+                    ("poule", c.numero_poule.to_string()),
+                    ("clt", c.classement.to_string()),
+                    ("equipe", c.equipe.clone()),
+                    ("joue", c.joues.to_string()),
+                    ("pts", c.points.to_string()),
+                    ("vic", c.victoires.to_string()),
+                    ("def", c.defaites.to_string()),
+                    ("nul", c.nuls.to_string()),
+                    ("pf", c.forfaits.to_string()),
+                    ("pg", c.parties_gagnees.to_string()),
+                    ("pp", c.parties_perdues.to_string()),
+                    ("numero", c.numero.clone()),
+                ],
+            );
+        }
+    } else {
+        for r in &p.rencontres {
+            let score = |score: Option<u8>, cote: Cote| {
+                if r.forfait == Some(cote) {
+                    "F".to_string()
+                } else {
+                    score.map(|s| s.to_string()).unwrap_or_default()
+                }
+            };
+            let mut champs = vec![
+                (
+                    "libelle",
+                    format!("Poule {} - tour n°{} du {}", poule.numero, r.tour, r.date),
+                ),
+                ("equa", r.a.clone()),
+                ("equb", r.b.clone()),
+                ("scorea", score(r.scorea, Cote::Domicile)),
+                ("scoreb", score(r.scoreb, Cote::Exterieur)),
+                ("dateprevue", r.date.to_string()),
+            ];
+            if let Some(d) = &r.datereelle {
+                champs.push(("datereelle", d.to_string()));
+            }
+            champs.push(("lien", r.lien.clone()));
+            element(&mut elements, "tour", &champs);
+        }
+    }
+    xml(&elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn donnees() -> Donnees {
+        let rencontres: Vec<Rencontre> = serde_json::from_value(serde_json::json!([
+            {"tour": 1, "a": "A 1", "b": "B 1", "scorea": null, "scoreb": 0, "forfait": "Domicile",
+             "date": "2024-09-28", "datereelle": null, "lien": ""},
+            {"tour": 2, "a": "B 1", "b": "A 1", "scorea": 9, "scoreb": 5, "forfait": null,
+             "date": "2024-10-12", "datereelle": "2024-10-19", "lien": "renc_id=1"}
+        ]))
+        .unwrap();
+        Donnees {
+            poules: vec![PouleSimulee {
+                poule: Poule {
+                    division: "5678".to_string(),
+                    numero: "1234".to_string(),
+                },
+                rencontres,
+                classements: vec![],
+            }],
+            ..Donnees::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resultats() {
+        let simulateur = Simulateur::new(donnees());
+        let adresse = simulateur.lancer("127.0.0.1:0").await.unwrap();
+        let url =
+            format!("http://{adresse}/proxy/xml_result_equ.php?force=1&D1=5678&cx_poule=1234");
+        let xml = reqwest::get(&url).await.unwrap().text().await.unwrap();
+        let rencontres = crate::tour::Rencontres::from_xml(&xml).unwrap().rencontres;
+        assert_eq!(rencontres, donnees().poules[0].rencontres);
+
+        simulateur.injecter(
+            "/proxy",
+            Defaillance {
+                statut: Some(503),
+                ..Defaillance::default()
+            },
+        );
+        let reponse = reqwest::get(&url).await.unwrap();
+        assert_eq!(reponse.status().as_u16(), 503);
+
+        simulateur.injecter(
+            "/proxy",
+            Defaillance {
+                malformee: true,
+                ..Defaillance::default()
+            },
+        );
+        let xml = reqwest::get(&url).await.unwrap().text().await.unwrap();
+        assert!(crate::tour::Rencontres::from_xml(&xml).is_err());
+
+        simulateur.retablir();
+        let reponse = reqwest::get(&url).await.unwrap();
+        assert!(reponse.status().is_success());
+    }
+}
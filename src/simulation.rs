@@ -0,0 +1,333 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::poule::Classement;
+use crate::prediction;
+use crate::tour::{Bareme, Rencontre};
+
+#[derive(Clone, Debug)]
+/// les paramètres de la simulation de fin de phase
+pub struct Parametres {
+    /// nombre d'équipes montant en division supérieure
+    pub montees: usize,
+    /// nombre d'équipes descendant en division inférieure
+    pub descentes: usize,
+    /// nombre maximal de rencontres restantes pour lequel tous les résultats sont énumérés,
+    /// au-delà un tirage de Monte-Carlo est effectué, de même lorsque le nombre de scénarios
+    /// (3 puissance le nombre de rencontres) dépasse les entiers de la plateforme
+    pub seuil_enumeration: usize,
+    /// nombre de tirages de Monte-Carlo
+    pub tirages: u32,
+    /// graine du générateur aléatoire, pour des résultats reproductibles
+    pub graine: u64,
+    /// probabilité d'un match nul
+    pub proba_nul: f64,
+    /// force de chaque équipe (par exemple la moyenne des points de ses joueurs)
+    /// une équipe absente est considérée de force égale à chacun de ses adversaires
+    pub forces: HashMap<String, f64>,
+    /// barème des points attribués
+    pub bareme: Bareme,
+}
+
+impl Default for Parametres {
+    fn default() -> Self {
+        Parametres {
+            montees: 1,
+            descentes: 2,
+            seuil_enumeration: 8,
+            tirages: 10_000,
+            graine: 0x5EED,
+            proba_nul: 0.1,
+            forces: HashMap::new(),
+            bareme: Bareme::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// les probabilités de fin de phase d'une équipe
+pub struct Probabilites {
+    /// nom de l'équipe
+    pub equipe: String,
+    /// probabilité de finir à chaque rang, le rang 1 étant à l'indice 0
+    pub rangs: Vec<f64>,
+    /// probabilité de monter
+    pub montee: f64,
+    /// probabilité de descendre
+    pub descente: f64,
+}
+
+/// simule la fin de phase à partir du classement actuel et des rencontres restant à jouer
+///
+/// Les équipes à égalité de points sont départagées au quotient des parties actuel,
+/// les égalités persistantes étant réparties équitablement entre les rangs concernés.
+pub fn simuler(
+    classements: &[Classement],
+    restantes: &[Rencontre],
+    parametres: &Parametres,
+) -> Vec<Probabilites> {
+    let equipes: Vec<&Classement> = classements.iter().collect();
+    let n = equipes.len();
+    let index: HashMap<&str, usize> = equipes
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.equipe.as_str(), i))
+        .collect();
+    // seules les rencontres entre équipes connues et sans résultat sont simulées
+    let restantes: Vec<(usize, usize, [f64; 3])> = restantes
+        .iter()
+        .filter(|r| !r.est_jouee())
+        .filter_map(|r| {
+            let a = *index.get(r.a.as_str())?;
+            let b = *index.get(r.b.as_str())?;
+            Some((a, b, probas(&r.a, &r.b, parametres)))
+        })
+        .collect();
+
+    let mut rangs = vec![vec![0.0; n]; n];
+    let base: Vec<u16> = equipes.iter().map(|c| u16::from(c.points)).collect();
+    let bareme = &parametres.bareme;
+    let gains = [
+        (bareme.victoire, bareme.defaite),
+        (bareme.nul, bareme.nul),
+        (bareme.defaite, bareme.victoire),
+    ];
+
+    // le nombre de scénarios à énumérer, s'il est sous le seuil et représentable
+    let scenarios = u32::try_from(restantes.len())
+        .ok()
+        .and_then(|n| 3usize.checked_pow(n))
+        .filter(|_| restantes.len() <= parametres.seuil_enumeration);
+    if let Some(total) = scenarios {
+        for scenario in 0..total {
+            let mut points = base.clone();
+            let mut poids = 1.0;
+            let mut s = scenario;
+            for (a, b, p) in &restantes {
+                let issue = s % 3;
+                s /= 3;
+                poids *= p[issue];
+                points[*a] += u16::from(gains[issue].0);
+                points[*b] += u16::from(gains[issue].1);
+            }
+            repartir(&equipes, &points, poids, &mut rangs);
+        }
+    } else {
+        let mut alea = SplitMix64(parametres.graine);
+        let poids = 1.0 / f64::from(parametres.tirages);
+        for _ in 0..parametres.tirages {
+            let mut points = base.clone();
+            for (a, b, p) in &restantes {
+                let x = alea.suivant();
+                let issue = if x < p[0] {
+                    0
+                } else if x < p[0] + p[1] {
+                    1
+                } else {
+                    2
+                };
+                points[*a] += u16::from(gains[issue].0);
+                points[*b] += u16::from(gains[issue].1);
+            }
+            repartir(&equipes, &points, poids, &mut rangs);
+        }
+    }
+
+    equipes
+        .iter()
+        .zip(rangs)
+        .map(|(c, rangs)| Probabilites {
+            equipe: c.equipe.clone(),
+            montee: rangs.iter().take(parametres.montees).sum(),
+            descente: rangs
+                .iter()
+                .skip(n.saturating_sub(parametres.descentes))
+                .sum(),
+            rangs,
+        })
+        .collect()
+}
+
+// probabilités (victoire A, nul, victoire B) d'une rencontre
+fn probas(a: &str, b: &str, parametres: &Parametres) -> [f64; 3] {
+    // une équipe de force inconnue est considérée de force égale à son adversaire
+    let pa = match (parametres.forces.get(a), parametres.forces.get(b)) {
+        (Some(fa), Some(fb)) => prediction::probabilite(*fa, *fb),
+        _ => 0.5,
+    };
+    let joue = 1.0 - parametres.proba_nul;
+    [joue * pa, parametres.proba_nul, joue * (1.0 - pa)]
+}
+
+// ajoute le poids d'un scénario aux rangs obtenus par chaque équipe
+fn repartir(equipes: &[&Classement], points: &[u16], poids: f64, rangs: &mut [Vec<f64>]) {
+    let mut ordre: Vec<usize> = (0..equipes.len()).collect();
+    let comparer = |a: &usize, b: &usize| -> Ordering {
+        points[*b].cmp(&points[*a]).then_with(|| {
+            let (ca, cb) = (equipes[*a], equipes[*b]);
+            (u32::from(cb.parties_gagnees) * u32::from(ca.parties_perdues))
+                .cmp(&(u32::from(ca.parties_gagnees) * u32::from(cb.parties_perdues)))
+        })
+    };
+    ordre.sort_by(comparer);
+    let mut debut = 0;
+    while debut < ordre.len() {
+        let fin = debut
+            + ordre[debut..]
+                .iter()
+                .take_while(|x| comparer(x, &ordre[debut]) == Ordering::Equal)
+                .count();
+        let part = poids / (fin - debut) as f64;
+        for equipe in &ordre[debut..fin] {
+            for rang in rangs[*equipe].iter_mut().take(fin).skip(debut) {
+                *rang += part;
+            }
+        }
+        debut = fin;
+    }
+}
+
+// générateur pseudo-aléatoire SplitMix64, suffisant pour la simulation
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    // retourne un flottant uniforme dans [0, 1)
+    pub(crate) fn suivant(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Date;
+
+    fn classement(
+        equipe: &str,
+        points: u8,
+        parties_gagnees: u8,
+        parties_perdues: u8,
+    ) -> Classement {
+        Classement {
+            numero_poule: 1,
+            classement: 0,
+            equipe: equipe.to_string(),
+            joues: 0,
+            points,
+            victoires: 0,
+            defaites: 0,
+            nuls: 0,
+            forfaits: 0,
+            parties_gagnees,
+            parties_perdues,
+            numero: String::new(),
+        }
+    }
+
+    fn restante(a: &str, b: &str) -> Rencontre {
+        Rencontre {
+            tour: 7,
+            a: a.to_string(),
+            b: b.to_string(),
+            scorea: None,
+            scoreb: None,
+            forfait: None,
+            date: Date::new(2025, 5, 17),
+            datereelle: None,
+            lien: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_enumeration() {
+        let classements = vec![
+            classement("A", 15, 50, 20),
+            classement("B", 14, 40, 30),
+            classement("C", 8, 20, 50),
+        ];
+        let restantes = vec![restante("A", "B")];
+        let parametres = Parametres {
+            montees: 1,
+            descentes: 1,
+            ..Default::default()
+        };
+        let probas = simuler(&classements, &restantes, &parametres);
+        // A monte sauf en cas de défaite contre B
+        assert!((probas[0].montee - 0.55).abs() < 1e-9);
+        assert!((probas[1].montee - 0.45).abs() < 1e-9);
+        assert!((probas[2].descente - 1.0).abs() < 1e-9);
+        for p in &probas {
+            assert!((p.rangs.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_monte_carlo() {
+        let classements = vec![
+            classement("A", 10, 30, 30),
+            classement("B", 10, 30, 30),
+            classement("C", 10, 30, 30),
+            classement("D", 10, 30, 30),
+        ];
+        let restantes = vec![restante("A", "B"), restante("C", "D")];
+        let mut forces = HashMap::new();
+        forces.insert("A".to_string(), 1800.0);
+        forces.insert("B".to_string(), 1200.0);
+        let parametres = Parametres {
+            seuil_enumeration: 0,
+            forces,
+            ..Default::default()
+        };
+        let probas = simuler(&classements, &restantes, &parametres);
+        assert!(probas[0].montee > probas[1].montee);
+        assert!(probas[1].descente > probas[0].descente);
+        for p in &probas {
+            assert!((p.rangs.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_force_inconnue() {
+        let mut forces = HashMap::new();
+        forces.insert("A".to_string(), 1800.0);
+        let parametres = Parametres {
+            forces,
+            ..Default::default()
+        };
+        let [a, nul, b] = probas("A", "B", &parametres);
+        assert!((a - b).abs() < 1e-9);
+        assert!((a + nul + b - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_seuil_trop_grand() {
+        let equipes = ["A", "B", "C", "D", "E", "F", "G", "H"];
+        let classements: Vec<Classement> =
+            equipes.iter().map(|e| classement(e, 10, 30, 30)).collect();
+        // 56 rencontres : 3^56 scénarios ne sont pas représentables, on tire au sort
+        let restantes: Vec<Rencontre> = equipes
+            .iter()
+            .flat_map(|a| {
+                equipes
+                    .iter()
+                    .filter(move |b| *b != a)
+                    .map(move |b| restante(a, b))
+            })
+            .collect();
+        let parametres = Parametres {
+            seuil_enumeration: usize::MAX,
+            tirages: 100,
+            ..Default::default()
+        };
+        let probas = simuler(&classements, &restantes, &parametres);
+        for p in &probas {
+            assert!((p.rangs.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,781 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::club::Club;
+use crate::date::Date;
+use crate::equipe::Equipe;
+use crate::joueur::Joueur;
+use crate::journee::Journee;
+use crate::poule::{Classement, Poule};
+use crate::tour::{Cote, Rencontre};
+use crate::ErreurApi;
+
+/// version courante du schéma de la base
+pub const VERSION_SCHEMA: u32 = 1;
+
+// les migrations successives du schéma, la migration d'indice i menant à la version i + 1
+const MIGRATIONS: [&str; 1] = [r#"
+CREATE TABLE clubs (
+    numero TEXT PRIMARY KEY,
+    nom TEXT NOT NULL,
+    nomsalle TEXT NOT NULL,
+    adressesalle1 TEXT NOT NULL,
+    adressesalle2 TEXT NOT NULL,
+    adressesalle3 TEXT NOT NULL,
+    codepsalle TEXT NOT NULL,
+    villesalle TEXT NOT NULL,
+    latitude TEXT NOT NULL,
+    longitude TEXT NOT NULL
+);
+CREATE TABLE joueurs (
+    licence TEXT PRIMARY KEY,
+    club TEXT,
+    nom TEXT NOT NULL,
+    prenom TEXT NOT NULL,
+    categorie TEXT,
+    points_init REAL NOT NULL,
+    point REAL NOT NULL,
+    virtuel REAL NOT NULL
+);
+CREATE INDEX joueurs_club ON joueurs (club);
+CREATE TABLE points (
+    licence TEXT NOT NULL,
+    date TEXT NOT NULL,
+    points_init REAL NOT NULL,
+    point REAL NOT NULL,
+    virtuel REAL NOT NULL,
+    PRIMARY KEY (licence, date)
+);
+CREATE TABLE equipes (
+    id TEXT PRIMARY KEY,
+    club TEXT,
+    nom TEXT NOT NULL,
+    epreuve TEXT NOT NULL,
+    division TEXT NOT NULL,
+    poule_division TEXT NOT NULL,
+    poule_numero TEXT NOT NULL
+);
+CREATE INDEX equipes_club ON equipes (club);
+CREATE TABLE poules (
+    division TEXT NOT NULL,
+    numero TEXT NOT NULL,
+    PRIMARY KEY (division, numero)
+);
+CREATE TABLE rencontres (
+    poule_division TEXT NOT NULL,
+    poule_numero TEXT NOT NULL,
+    tour INTEGER NOT NULL,
+    a TEXT NOT NULL,
+    b TEXT NOT NULL,
+    scorea INTEGER,
+    scoreb INTEGER,
+    forfait TEXT,
+    date TEXT NOT NULL,
+    datereelle TEXT,
+    lien TEXT NOT NULL,
+    PRIMARY KEY (poule_division, poule_numero, tour, a, b)
+);
+CREATE TABLE classements (
+    poule_division TEXT NOT NULL,
+    poule_numero TEXT NOT NULL,
+    equipe TEXT NOT NULL,
+    numero_poule INTEGER NOT NULL,
+    classement INTEGER NOT NULL,
+    joues INTEGER NOT NULL,
+    points INTEGER NOT NULL,
+    victoires INTEGER NOT NULL,
+    defaites INTEGER NOT NULL,
+    nuls INTEGER NOT NULL,
+    forfaits INTEGER NOT NULL,
+    parties_gagnees INTEGER NOT NULL,
+    parties_perdues INTEGER NOT NULL,
+    numero TEXT NOT NULL,
+    PRIMARY KEY (poule_division, poule_numero, equipe)
+);
+CREATE TABLE journees (
+    licence TEXT NOT NULL,
+    date TEXT NOT NULL,
+    donnees TEXT NOT NULL,
+    PRIMARY KEY (licence, date)
+);
+"#];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// le nombre d'enregistrements créés ou modifiés lors d'une synchronisation
+pub struct Synchronisation {
+    /// clubs
+    pub clubs: usize,
+    /// joueurs
+    pub joueurs: usize,
+    /// équipes
+    pub equipes: usize,
+    /// rencontres
+    pub rencontres: usize,
+    /// lignes de classement
+    pub classements: usize,
+    /// journées des joueurs
+    pub journees: usize,
+}
+
+#[derive(Debug)]
+/// une erreur lors d'une synchronisation avec l'API
+pub enum ErreurSynchronisation {
+    /// l'enregistrement dans la base a échoué
+    Base(rusqlite::Error),
+    /// l'interrogation de l'API a échoué
+    Api(ErreurApi),
+}
+
+impl std::fmt::Display for ErreurSynchronisation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErreurSynchronisation::Base(e) => write!(f, "base de données : {e}"),
+            ErreurSynchronisation::Api(e) => write!(f, "API : {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ErreurSynchronisation {}
+
+impl From<rusqlite::Error> for ErreurSynchronisation {
+    fn from(e: rusqlite::Error) -> Self {
+        ErreurSynchronisation::Base(e)
+    }
+}
+
+impl From<ErreurApi> for ErreurSynchronisation {
+    fn from(e: ErreurApi) -> Self {
+        ErreurSynchronisation::Api(e)
+    }
+}
+
+/// une base SQLite conservant localement les données de l'API
+pub struct Store {
+    connexion: Connection,
+}
+
+impl Store {
+    /// ouvre la base au chemin donné, en la créant ou en migrant son schéma si nécessaire
+    pub fn ouvrir(chemin: impl AsRef<Path>) -> rusqlite::Result<Store> {
+        Store::initialiser(Connection::open(chemin)?)
+    }
+
+    /// ouvre une base temporaire en mémoire
+    pub fn en_memoire() -> rusqlite::Result<Store> {
+        Store::initialiser(Connection::open_in_memory()?)
+    }
+
+    fn initialiser(mut connexion: Connection) -> rusqlite::Result<Store> {
+        let version: u32 = connexion.query_row("PRAGMA user_version", [], |r| r.get(0))?;
+        if version > VERSION_SCHEMA {
+            log::warn!("Schéma de la base en version {version}, plus récent que la bibliothèque");
+        }
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            log::info!("Migration du schéma de la base en version {}...", i + 1);
+            let transaction = connexion.transaction()?;
+            transaction.execute_batch(migration)?;
+            transaction.pragma_update(None, "user_version", i + 1)?;
+            transaction.commit()?;
+        }
+        Ok(Store { connexion })
+    }
+
+    /// la version du schéma de la base
+    pub fn version(&self) -> rusqlite::Result<u32> {
+        self.connexion
+            .query_row("PRAGMA user_version", [], |r| r.get(0))
+    }
+
+    /// la connexion à la base, pour les requêtes non couvertes par les méthodes du store
+    pub fn connexion(&self) -> &Connection {
+        &self.connexion
+    }
+
+    /// enregistre le club, retourne vrai s'il est nouveau ou a changé
+    pub fn enregistrer_club(&self, club: &Club) -> rusqlite::Result<bool> {
+        if self.club(&club.numero)?.as_ref() == Some(club) {
+            return Ok(false);
+        }
+        self.connexion.execute(
+            "INSERT OR REPLACE INTO clubs VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                club.numero,
+                club.nom,
+                club.nomsalle,
+                club.adressesalle1,
+                club.adressesalle2,
+                club.adressesalle3,
+                club.codepsalle,
+                club.villesalle,
+                club.latitude,
+                club.longitude
+            ],
+        )?;
+        Ok(true)
+    }
+
+    /// retourne le club enregistré sous ce numéro
+    pub fn club(&self, numero: &str) -> rusqlite::Result<Option<Club>> {
+        self.connexion
+            .query_row("SELECT * FROM clubs WHERE numero = ?1", [numero], |r| {
+                Ok(Club {
+                    numero: r.get("numero")?,
+                    nom: r.get("nom")?,
+                    nomsalle: r.get("nomsalle")?,
+                    adressesalle1: r.get("adressesalle1")?,
+                    adressesalle2: r.get("adressesalle2")?,
+                    adressesalle3: r.get("adressesalle3")?,
+                    codepsalle: r.get("codepsalle")?,
+                    villesalle: r.get("villesalle")?,
+                    latitude: r.get("latitude")?,
+                    longitude: r.get("longitude")?,
+                })
+            })
+            .optional()
+    }
+
+    /// enregistre le joueur et ses points du jour dans son historique,
+    /// retourne vrai s'il est nouveau ou a changé
+    pub fn enregistrer_joueur(
+        &self,
+        joueur: &Joueur,
+        club: Option<&str>,
+    ) -> rusqlite::Result<bool> {
+        self.connexion.execute(
+            "INSERT OR REPLACE INTO points VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                joueur.licence,
+                iso(&Date::now()),
+                joueur.points_init,
+                joueur.point,
+                joueur.r#_virtual
+            ],
+        )?;
+        let club_enregistre: Option<Option<String>> = self
+            .connexion
+            .query_row(
+                "SELECT club FROM joueurs WHERE licence = ?1",
+                [&joueur.licence],
+                |r| r.get(0),
+            )
+            .optional()?;
+        if self.joueur(&joueur.licence)?.as_ref() == Some(joueur)
+            && club_enregistre.as_ref().map(Option::as_deref) == Some(club)
+        {
+            return Ok(false);
+        }
+        self.connexion.execute(
+            "INSERT OR REPLACE INTO joueurs VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                joueur.licence,
+                club,
+                joueur.nom,
+                joueur.prenom,
+                joueur.categorie,
+                joueur.points_init,
+                joueur.point,
+                joueur.r#_virtual
+            ],
+        )?;
+        Ok(true)
+    }
+
+    /// retourne le joueur enregistré sous ce numéro de licence
+    pub fn joueur(&self, licence: &str) -> rusqlite::Result<Option<Joueur>> {
+        self.connexion
+            .query_row(
+                "SELECT * FROM joueurs WHERE licence = ?1",
+                [licence],
+                joueur,
+            )
+            .optional()
+    }
+
+    /// retourne les joueurs du club, par points décroissants
+    pub fn joueurs(&self, club: &str) -> rusqlite::Result<Vec<Joueur>> {
+        let mut requete = self
+            .connexion
+            .prepare("SELECT * FROM joueurs WHERE club = ?1 ORDER BY point DESC, nom, prenom")?;
+        let joueurs = requete.query_map([club], joueur)?.collect();
+        joueurs
+    }
+
+    /// retourne l'historique des points virtuels du joueur, relevés à chaque enregistrement
+    pub fn historique_points(&self, licence: &str) -> rusqlite::Result<Vec<(Date, f32)>> {
+        let mut requete = self
+            .connexion
+            .prepare("SELECT date, virtuel FROM points WHERE licence = ?1 ORDER BY date")?;
+        let historique = requete
+            .query_map([licence], |r| Ok((date(r, "date")?, r.get("virtuel")?)))?
+            .collect();
+        historique
+    }
+
+    /// retourne les joueurs du club ayant le plus progressé depuis le début de la phase
+    pub fn meilleures_progressions(
+        &self,
+        club: &str,
+        nombre: usize,
+    ) -> rusqlite::Result<Vec<Joueur>> {
+        let mut requete = self.connexion.prepare(
+            "SELECT * FROM joueurs WHERE club = ?1 ORDER BY virtuel - point DESC LIMIT ?2",
+        )?;
+        let joueurs = requete.query_map(params![club, nombre], joueur)?.collect();
+        joueurs
+    }
+
+    /// enregistre l'équipe et sa poule, retourne vrai si elle est nouvelle ou a changé
+    pub fn enregistrer_equipe(
+        &self,
+        equipe: &Equipe,
+        club: Option<&str>,
+    ) -> rusqlite::Result<bool> {
+        self.connexion.execute(
+            "INSERT OR IGNORE INTO poules VALUES (?1, ?2)",
+            params![equipe.poule.division, equipe.poule.numero],
+        )?;
+        let existe: bool = self.connexion.query_row(
+            "SELECT COUNT(*) FROM equipes WHERE id = ?1 AND club IS ?2 AND nom = ?3
+             AND epreuve = ?4 AND division = ?5 AND poule_division = ?6 AND poule_numero = ?7",
+            params![
+                equipe.id,
+                club,
+                equipe.nom,
+                equipe._epreuve,
+                equipe._division,
+                equipe.poule.division,
+                equipe.poule.numero
+            ],
+            |r| r.get(0),
+        )?;
+        if existe {
+            return Ok(false);
+        }
+        self.connexion.execute(
+            "INSERT OR REPLACE INTO equipes VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                equipe.id,
+                club,
+                equipe.nom,
+                equipe._epreuve,
+                equipe._division,
+                equipe.poule.division,
+                equipe.poule.numero
+            ],
+        )?;
+        Ok(true)
+    }
+
+    /// retourne les équipes du club
+    pub fn equipes(&self, club: &str) -> rusqlite::Result<Vec<Equipe>> {
+        let mut requete = self
+            .connexion
+            .prepare("SELECT * FROM equipes WHERE club = ?1 ORDER BY nom")?;
+        let equipes = requete
+            .query_map([club], |r| {
+                Ok(Equipe {
+                    id: r.get("id")?,
+                    nom: r.get("nom")?,
+                    _epreuve: r.get("epreuve")?,
+                    _division: r.get("division")?,
+                    poule: Poule {
+                        division: r.get("poule_division")?,
+                        numero: r.get("poule_numero")?,
+                    },
+                })
+            })?
+            .collect();
+        equipes
+    }
+
+    /// retourne les poules enregistrées
+    pub fn poules(&self) -> rusqlite::Result<Vec<Poule>> {
+        let mut requete = self
+            .connexion
+            .prepare("SELECT division, numero FROM poules ORDER BY division, numero")?;
+        let poules = requete
+            .query_map([], |r| {
+                Ok(Poule {
+                    division: r.get(0)?,
+                    numero: r.get(1)?,
+                })
+            })?
+            .collect();
+        poules
+    }
+
+    /// enregistre les rencontres de la poule, retourne le nombre de rencontres nouvelles ou modifiées
+    pub fn enregistrer_rencontres(
+        &mut self,
+        poule: &Poule,
+        rencontres: &[Rencontre],
+    ) -> rusqlite::Result<usize> {
+        let existantes = self.rencontres(poule)?;
+        let transaction = self.connexion.transaction()?;
+        transaction.execute(
+            "INSERT OR IGNORE INTO poules VALUES (?1, ?2)",
+            params![poule.division, poule.numero],
+        )?;
+        let mut modifiees = 0;
+        for r in rencontres.iter().filter(|r| !existantes.contains(r)) {
+            transaction.execute(
+                "INSERT OR REPLACE INTO rencontres VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    poule.division,
+                    poule.numero,
+                    r.tour,
+                    r.a,
+                    r.b,
+                    r.scorea,
+                    r.scoreb,
+                    r.forfait.map(|c| match c {
+                        Cote::Domicile => "domicile",
+                        Cote::Exterieur => "exterieur",
+                    }),
+                    iso(&r.date),
+                    r.datereelle.as_ref().map(iso),
+                    r.lien
+                ],
+            )?;
+            modifiees += 1;
+        }
+        transaction.commit()?;
+        Ok(modifiees)
+    }
+
+    /// retourne les rencontres de la poule, par tour
+    pub fn rencontres(&self, poule: &Poule) -> rusqlite::Result<Vec<Rencontre>> {
+        let mut requete = self.connexion.prepare(
+            "SELECT * FROM rencontres WHERE poule_division = ?1 AND poule_numero = ?2
+             ORDER BY tour, date, a",
+        )?;
+        let rencontres = requete
+            .query_map(params![poule.division, poule.numero], rencontre)?
+            .collect();
+        rencontres
+    }
+
+    /// retourne les rencontres jouées ou à jouer par l'équipe, toutes poules confondues
+    pub fn rencontres_equipe(&self, equipe: &str) -> rusqlite::Result<Vec<Rencontre>> {
+        let mut requete = self.connexion.prepare(
+            "SELECT * FROM rencontres WHERE a = ?1 OR b = ?1 ORDER BY COALESCE(datereelle, date)",
+        )?;
+        let rencontres = requete.query_map([equipe], rencontre)?.collect();
+        rencontres
+    }
+
+    /// enregistre le classement de la poule, retourne le nombre de lignes nouvelles ou modifiées
+    pub fn enregistrer_classement(
+        &mut self,
+        poule: &Poule,
+        classements: &[Classement],
+    ) -> rusqlite::Result<usize> {
+        let existants = self.classement(poule)?;
+        let transaction = self.connexion.transaction()?;
+        let mut modifies = 0;
+        for c in classements.iter().filter(|c| !existants.contains(c)) {
+            transaction.execute(
+                "INSERT OR REPLACE INTO classements
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                params![
+                    poule.division,
+                    poule.numero,
+                    c.equipe,
+                    c.numero_poule,
+                    c.classement,
+                    c.joues,
+                    c.points,
+                    c.victoires,
+                    c.defaites,
+                    c.nuls,
+                    c.forfaits,
+                    c.parties_gagnees,
+                    c.parties_perdues,
+                    c.numero
+                ],
+            )?;
+            modifies += 1;
+        }
+        transaction.commit()?;
+        Ok(modifies)
+    }
+
+    /// retourne le classement enregistré de la poule, par rang
+    pub fn classement(&self, poule: &Poule) -> rusqlite::Result<Vec<Classement>> {
+        let mut requete = self.connexion.prepare(
+            "SELECT * FROM classements WHERE poule_division = ?1 AND poule_numero = ?2
+             ORDER BY classement",
+        )?;
+        let classements = requete
+            .query_map(params![poule.division, poule.numero], |r| {
+                Ok(Classement {
+                    numero_poule: r.get("numero_poule")?,
+                    classement: r.get("classement")?,
+                    equipe: r.get("equipe")?,
+                    joues: r.get("joues")?,
+                    points: r.get("points")?,
+                    victoires: r.get("victoires")?,
+                    defaites: r.get("defaites")?,
+                    nuls: r.get("nuls")?,
+                    forfaits: r.get("forfaits")?,
+                    parties_gagnees: r.get("parties_gagnees")?,
+                    parties_perdues: r.get("parties_perdues")?,
+                    numero: r.get("numero")?,
+                })
+            })?
+            .collect();
+        classements
+    }
+
+    /// enregistre les journées du joueur, retourne le nombre de journées nouvelles ou modifiées
+    pub fn enregistrer_journees(
+        &mut self,
+        licence: &str,
+        journees: &[Journee],
+    ) -> rusqlite::Result<usize> {
+        let transaction = self.connexion.transaction()?;
+        let mut modifiees = 0;
+        for j in journees {
+            let donnees = serde_json::to_string(j).expect("Une journée est sérialisable");
+            modifiees += transaction.execute(
+                "INSERT INTO journees VALUES (?1, ?2, ?3)
+                 ON CONFLICT (licence, date) DO UPDATE SET donnees = excluded.donnees
+                 WHERE donnees != excluded.donnees",
+                params![licence, iso(&j.date), donnees],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(modifiees)
+    }
+
+    /// retourne les journées du joueur, par date
+    pub fn journees(&self, licence: &str) -> rusqlite::Result<Vec<Journee>> {
+        let mut requete = self
+            .connexion
+            .prepare("SELECT donnees FROM journees WHERE licence = ?1 ORDER BY date")?;
+        let journees = requete
+            .query_map([licence], |r| {
+                let donnees: String = r.get(0)?;
+                serde_json::from_str(&donnees).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        0,
+                        rusqlite::types::Type::Text,
+                        Box::new(e),
+                    )
+                })
+            })?
+            .collect();
+        journees
+    }
+
+    /// synchronise le club, ses joueurs, ses équipes et leurs poules avec l'API
+    ///
+    /// Seuls les enregistrements créés ou modifiés sont comptés. Les poules sont toujours
+    /// interrogées, y compris terminées, afin de prendre en compte les corrections ultérieures
+    /// (pénalités, décisions sur forfait...). Les journées des joueurs, coûteuses à récupérer,
+    /// ne le sont que si `journees` est vrai. La synchronisation s'arrête à la première erreur,
+    /// les enregistrements déjà effectués étant conservés.
+    pub async fn synchroniser_club(
+        &mut self,
+        numero: &str,
+        journees: bool,
+    ) -> Result<Synchronisation, ErreurSynchronisation> {
+        log::info!("Synchronisation du club {numero}...");
+        let mut synchronisation = Synchronisation::default();
+        let club = Club::try_new(numero).await?;
+        synchronisation.clubs += usize::from(self.enregistrer_club(&club)?);
+
+        for joueur in club.try_api_joueurs().await? {
+            synchronisation.joueurs +=
+                usize::from(self.enregistrer_joueur(&joueur, Some(&club.numero))?);
+            if journees {
+                if let Some(partie) = joueur.try_api_parties().await? {
+                    synchronisation.journees +=
+                        self.enregistrer_journees(&joueur.licence, &partie.journees)?;
+                }
+            }
+        }
+
+        for equipe in club.try_api_equipes().await? {
+            synchronisation.equipes +=
+                usize::from(self.enregistrer_equipe(&equipe, Some(&club.numero))?);
+            let poule = &equipe.poule;
+            let rencontres: Vec<Rencontre> = poule
+                .try_get_tours()
+                .await?
+                .into_iter()
+                .flat_map(|(_, t)| t.rencontres.rencontres)
+                .collect();
+            synchronisation.rencontres += self.enregistrer_rencontres(poule, &rencontres)?;
+            let classements = poule.try_classement().await?;
+            synchronisation.classements += self.enregistrer_classement(poule, &classements)?;
+        }
+        log::info!("Synchronisation terminée : {synchronisation:?}");
+        Ok(synchronisation)
+    }
+}
+
+fn joueur(r: &Row) -> rusqlite::Result<Joueur> {
+    Ok(Joueur {
+        licence: r.get("licence")?,
+        nom: r.get("nom")?,
+        prenom: r.get("prenom")?,
+        points_init: r.get("points_init")?,
+        point: r.get("point")?,
+        r#_virtual: r.get("virtuel")?,
+        categorie: r.get("categorie")?,
+    })
+}
+
+fn rencontre(r: &Row) -> rusqlite::Result<Rencontre> {
+    let forfait: Option<String> = r.get("forfait")?;
+    let datereelle: Option<String> = r.get("datereelle")?;
+    Ok(Rencontre {
+        tour: r.get("tour")?,
+        a: r.get("a")?,
+        b: r.get("b")?,
+        scorea: r.get("scorea")?,
+        scoreb: r.get("scoreb")?,
+        forfait: forfait.map(|c| {
+            if c == "domicile" {
+                Cote::Domicile
+            } else {
+                Cote::Exterieur
+            }
+        }),
+        date: date(r, "date")?,
+        datereelle: datereelle.and_then(|d| Date::from_str(&d).ok()),
+        lien: r.get("lien")?,
+    })
+}
+
+// lit une date enregistrée au format AAAA-MM-JJ
+fn date(r: &Row, colonne: &str) -> rusqlite::Result<Date> {
+    let texte: String = r.get(colonne)?;
+    Date::from_str(&texte).map_err(|_| {
+        rusqlite::Error::FromSqlConversionFailure(
+            r.as_ref().column_index(colonne).unwrap_or_default(),
+            rusqlite::types::Type::Text,
+            format!("Date invalide : {texte}").into(),
+        )
+    })
+}
+
+fn iso(date: &Date) -> String {
+    format!("{:04}-{:02}-{:02}", date.annee, date.mois, date.jour)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tour::Rencontres;
+
+    const RENCONTRES: &str = r#"<liste>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>MONTPELLIER TT 3</equa><equb>NIMES ASPTT 2</equb><scorea>F</scorea><scoreb>0</scoreb><dateprevue>28/09/2024</dateprevue></tour>
+    <tour><libelle>Poule 3 - tour n°2 du 12/10/2024</libelle><equa>ALES TT 1</equa><equb>MONTPELLIER TT 3</equb><dateprevue>12/10/2024</dateprevue><datereelle>09/11/2024</datereelle></tour>
+</liste>"#;
+
+    fn poule() -> Poule {
+        Poule {
+            division: "5678".to_string(),
+            numero: "1234".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_schema() {
+        let store = Store::en_memoire().unwrap();
+        assert_eq!(store.version().unwrap(), VERSION_SCHEMA);
+        // une seconde initialisation ne rejoue pas les migrations
+        let store = Store::initialiser(store.connexion).unwrap();
+        assert_eq!(store.version().unwrap(), VERSION_SCHEMA);
+    }
+
+    #[test]
+    fn test_joueurs() {
+        let store = Store::en_memoire().unwrap();
+        let mut joueur = Joueur {
+            licence: "12345".to_string(),
+            nom: "DOE".to_string(),
+            prenom: "John".to_string(),
+            points_init: 1000.0,
+            point: 1100.0,
+            r#_virtual: 1150.5,
+            categorie: Some("V1".to_string()),
+        };
+        assert!(store.enregistrer_joueur(&joueur, Some("11340010")).unwrap());
+        assert!(!store.enregistrer_joueur(&joueur, Some("11340010")).unwrap());
+        joueur.r#_virtual = 1160.0;
+        assert!(store.enregistrer_joueur(&joueur, Some("11340010")).unwrap());
+        assert_eq!(store.joueurs("11340010").unwrap(), vec![joueur.clone()]);
+        assert_eq!(
+            store.meilleures_progressions("11340010", 1).unwrap(),
+            vec![joueur]
+        );
+        let historique = store.historique_points("12345").unwrap();
+        assert_eq!(historique.len(), 1);
+        assert_eq!(historique[0].1, 1160.0);
+    }
+
+    #[test]
+    fn test_rencontres() {
+        let mut store = Store::en_memoire().unwrap();
+        let rencontres = Rencontres::from_xml(RENCONTRES).unwrap().rencontres;
+        assert_eq!(
+            store.enregistrer_rencontres(&poule(), &rencontres).unwrap(),
+            2
+        );
+        assert_eq!(
+            store.enregistrer_rencontres(&poule(), &rencontres).unwrap(),
+            0
+        );
+        assert_eq!(store.rencontres(&poule()).unwrap(), rencontres);
+        assert_eq!(store.poules().unwrap(), vec![poule()]);
+        assert_eq!(store.rencontres_equipe("ALES TT 1").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_classement_et_journees() {
+        let mut store = Store::en_memoire().unwrap();
+        let classement: Classement = serde_json::from_value(serde_json::json!({
+            "numero_poule": 3,
+            "classement": 1,
+            "equipe": "MONTPELLIER TT 3",
+            "joues": 1,
+            "points": 3,
+            "victoires": 1,
+            "defaites": 0,
+            "nuls": 0,
+            "forfaits": 0,
+            "parties_gagnees": 9,
+            "parties_perdues": 5,
+            "numero": "11340010"
+        }))
+        .unwrap();
+        let classements = vec![classement];
+        assert_eq!(
+            store
+                .enregistrer_classement(&poule(), &classements)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            store
+                .enregistrer_classement(&poule(), &classements)
+                .unwrap(),
+            0
+        );
+        assert_eq!(store.classement(&poule()).unwrap(), classements);
+
+        let journees: Vec<Journee> = serde_json::from_value(serde_json::json!([
+            {"date": "28/09/2024", "matchs": [{"nom": "X", "coeff": 1.0, "ex": 12.0}]}
+        ]))
+        .unwrap();
+        assert_eq!(store.enregistrer_journees("12345", &journees).unwrap(), 1);
+        assert_eq!(store.enregistrer_journees("12345", &journees).unwrap(), 0);
+        let relues = store.journees("12345").unwrap();
+        assert_eq!(relues.len(), 1);
+        assert_eq!(relues[0].ex(), 12.0);
+    }
+}
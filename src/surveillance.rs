@@ -0,0 +1,415 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::joueur::Joueur;
+use crate::poule::{Classement, Poule};
+use crate::tour::Rencontre;
+use crate::ErreurApi;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+/// un changement détecté lors de la surveillance
+pub enum Notification {
+    /// le score d'une rencontre a été publié
+    NouveauScore {
+        /// la poule de la rencontre
+        poule: Poule,
+        /// la rencontre
+        rencontre: Rencontre,
+    },
+    /// le classement d'une poule a changé
+    ClassementModifie {
+        /// la poule
+        poule: Poule,
+        /// le classement précédent
+        avant: Vec<Classement>,
+        /// le nouveau classement
+        apres: Vec<Classement>,
+    },
+    /// les points virtuels d'un joueur ont changé
+    PointsModifies {
+        /// numéro de licence
+        licence: String,
+        /// nom et prénom du joueur
+        nom: String,
+        /// points virtuels précédents
+        avant: f32,
+        /// nouveaux points virtuels
+        apres: f32,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// l'état observé des poules et des joueurs surveillés
+pub struct Observation {
+    /// les rencontres et le classement de chaque poule
+    pub poules: Vec<(Poule, Vec<Rencontre>, Vec<Classement>)>,
+    /// les joueurs
+    pub joueurs: Vec<Joueur>,
+}
+
+impl Observation {
+    /// retourne les notifications correspondant aux changements depuis l'observation précédente
+    ///
+    /// Une poule ou un joueur absent de l'une des deux observations,
+    /// faute d'avoir pu être récupéré, ne produit aucune notification.
+    pub fn notifications(&self, precedente: &Observation) -> Vec<Notification> {
+        let mut notifications = vec![];
+        for (poule, rencontres, classements) in &self.poules {
+            let Some((_, avant, classements_avant)) =
+                precedente.poules.iter().find(|(p, _, _)| p == poule)
+            else {
+                continue;
+            };
+            for r in rencontres.iter().filter(|r| r.est_jouee()) {
+                let deja_jouee = avant
+                    .iter()
+                    .any(|x| x.tour == r.tour && x.a == r.a && x.b == r.b && x.est_jouee());
+                if !deja_jouee {
+                    notifications.push(Notification::NouveauScore {
+                        poule: poule.clone(),
+                        rencontre: r.clone(),
+                    });
+                }
+            }
+            if classements != classements_avant {
+                notifications.push(Notification::ClassementModifie {
+                    poule: poule.clone(),
+                    avant: classements_avant.clone(),
+                    apres: classements.clone(),
+                });
+            }
+        }
+        for j in &self.joueurs {
+            if let Some(avant) = precedente.joueurs.iter().find(|a| a.licence == j.licence) {
+                if avant.r#_virtual != j.r#_virtual {
+                    notifications.push(Notification::PointsModifies {
+                        licence: j.licence.clone(),
+                        nom: j.display(),
+                        avant: avant.r#_virtual,
+                        apres: j.r#_virtual,
+                    });
+                }
+            }
+        }
+        notifications
+    }
+}
+
+#[derive(Clone, Debug)]
+/// un webhook recevant chaque notification en JSON par une requête POST
+pub struct Webhook {
+    /// l'URL appelée
+    pub url: String,
+    client: reqwest::Client,
+}
+
+impl Webhook {
+    /// créé le webhook appelant l'URL donnée
+    pub fn new(url: &str) -> Self {
+        Webhook {
+            url: url.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// envoie la notification au webhook
+    pub async fn envoyer(&self, notification: &Notification) -> Result<(), reqwest::Error> {
+        let corps = serde_json::to_string(notification).expect("Une notification est sérialisable");
+        self.client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(corps)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+/// la surveillance périodique de poules et de joueurs
+pub struct Surveillance {
+    /// les poules surveillées
+    pub poules: Vec<Poule>,
+    /// les licences des joueurs surveillés
+    pub licences: Vec<String>,
+    /// l'intervalle entre deux interrogations de l'API
+    pub intervalle: Duration,
+    /// le webhook notifié en plus des destinataires, s'il y en a un
+    pub webhook: Option<Webhook>,
+}
+
+impl Surveillance {
+    /// créé une surveillance sans poule ni joueur, interrogeant l'API à l'intervalle donné
+    pub fn new(intervalle: Duration) -> Self {
+        Surveillance {
+            poules: vec![],
+            licences: vec![],
+            intervalle,
+            webhook: None,
+        }
+    }
+
+    /// ajoute une poule à surveiller
+    pub fn poule(mut self, poule: Poule) -> Self {
+        self.poules.push(poule);
+        self
+    }
+
+    /// ajoute un joueur à surveiller
+    pub fn joueur(mut self, licence: &str) -> Self {
+        self.licences.push(licence.to_string());
+        self
+    }
+
+    /// notifie également le webhook à l'URL donnée
+    pub fn webhook(mut self, url: &str) -> Self {
+        self.webhook = Some(Webhook::new(url));
+        self
+    }
+
+    /// interroge l'API sur l'état des poules et des joueurs surveillés
+    /// une poule ou un joueur dont la récupération échoue est absent de l'observation,
+    /// l'erreur étant journalisée
+    pub async fn observer(&self) -> Observation {
+        let mut observation = Observation::default();
+        for poule in &self.poules {
+            let resultat = async {
+                let rencontres: Vec<Rencontre> = poule
+                    .try_get_tours()
+                    .await?
+                    .into_iter()
+                    .flat_map(|(_, t)| t.rencontres.rencontres)
+                    .collect();
+                Ok::<_, ErreurApi>((rencontres, poule.try_classement().await?))
+            }
+            .await;
+            match resultat {
+                Ok((rencontres, classements)) => {
+                    observation
+                        .poules
+                        .push((poule.clone(), rencontres, classements));
+                }
+                Err(e) => log::error!("Impossible d'observer la poule {} : {e}", poule.numero),
+            }
+        }
+        for licence in &self.licences {
+            match Joueur::try_new(licence).await {
+                Ok(joueur) => observation.joueurs.push(joueur),
+                Err(e) => log::error!("Impossible d'observer le joueur {licence} : {e}"),
+            }
+        }
+        observation
+    }
+
+    /// surveille indéfiniment les poules et les joueurs, en appelant `rappel` pour chaque changement
+    /// la première observation sert de référence et ne produit aucune notification
+    /// la surveillance s'arrête lorsque `rappel` retourne faux
+    pub async fn surveiller<F>(&self, rappel: F)
+    where
+        F: FnMut(&Notification) -> bool,
+    {
+        self.surveiller_tant_que(rappel, || true).await;
+    }
+
+    // surveille comme `surveiller`, en s'arrêtant dès que `actif` retourne faux à un tic
+    async fn surveiller_tant_que<F, A>(&self, mut rappel: F, actif: A)
+    where
+        F: FnMut(&Notification) -> bool,
+        A: Fn() -> bool,
+    {
+        let mut precedente = self.observer().await;
+        let mut intervalle = tokio::time::interval(self.intervalle);
+        // le premier tic est immédiat
+        intervalle.tick().await;
+        loop {
+            intervalle.tick().await;
+            if !actif() {
+                return;
+            }
+            let mut observation = self.observer().await;
+            for notification in observation.notifications(&precedente) {
+                log::info!("Changement détecté : {notification:?}");
+                if let Some(webhook) = &self.webhook {
+                    if let Err(e) = webhook.envoyer(&notification).await {
+                        log::error!("Impossible de notifier le webhook {} : {e}", webhook.url);
+                    }
+                }
+                if !rappel(&notification) {
+                    return;
+                }
+            }
+            // on conserve l'état précédent des poules et joueurs qui n'ont pu être récupérés
+            for p in precedente.poules {
+                if !observation.poules.iter().any(|(x, _, _)| *x == p.0) {
+                    observation.poules.push(p);
+                }
+            }
+            for j in precedente.joueurs {
+                if !observation.joueurs.iter().any(|x| x.licence == j.licence) {
+                    observation.joueurs.push(j);
+                }
+            }
+            precedente = observation;
+        }
+    }
+
+    /// lance la surveillance dans une tâche de fond et retourne le canal recevant les notifications
+    /// la surveillance s'arrête au premier tic suivant l'abandon du récepteur
+    pub fn lancer(self) -> mpsc::UnboundedReceiver<Notification> {
+        let (emetteur, recepteur) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            self.surveiller_tant_que(
+                |n| emetteur.send(n.clone()).is_ok(),
+                || !emetteur.is_closed(),
+            )
+            .await;
+        });
+        recepteur
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tour::Rencontres;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const AVANT: &str = r#"<liste>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>A</equa><equb>B</equb><dateprevue>28/09/2024</dateprevue></tour>
+</liste>"#;
+
+    const APRES: &str = r#"<liste>
+    <tour><libelle>Poule 3 - tour n°1 du 28/09/2024</libelle><equa>A</equa><equb>B</equb><scorea>10</scorea><scoreb>4</scoreb><dateprevue>28/09/2024</dateprevue></tour>
+</liste>"#;
+
+    fn poule() -> Poule {
+        Poule {
+            division: "5678".to_string(),
+            numero: "1234".to_string(),
+        }
+    }
+
+    fn joueur(virtuel: f32) -> Joueur {
+        Joueur {
+            licence: "12345".to_string(),
+            nom: "DOE".to_string(),
+            prenom: "John".to_string(),
+            points_init: 1000.0,
+            point: 1000.0,
+            r#_virtual: virtuel,
+            categorie: None,
+        }
+    }
+
+    fn observation(rencontres: &str, virtuel: f32) -> Observation {
+        Observation {
+            poules: vec![(
+                poule(),
+                Rencontres::from_xml(rencontres).unwrap().rencontres,
+                vec![],
+            )],
+            joueurs: vec![joueur(virtuel)],
+        }
+    }
+
+    #[test]
+    fn test_notifications() {
+        let avant = observation(AVANT, 1000.0);
+        let apres = observation(APRES, 1012.0);
+        assert!(avant.notifications(&avant).is_empty());
+        // une poule absente de l'observation précédente ne produit pas de notification
+        assert_eq!(apres.notifications(&Observation::default()), vec![]);
+
+        let notifications = apres.notifications(&avant);
+        assert_eq!(notifications.len(), 2);
+        assert!(matches!(
+            &notifications[0],
+            Notification::NouveauScore { rencontre, .. } if rencontre.scorea == Some(10)
+        ));
+        assert_eq!(
+            notifications[1],
+            Notification::PointsModifies {
+                licence: "12345".to_string(),
+                nom: "John DOE".to_string(),
+                avant: 1000.0,
+                apres: 1012.0,
+            }
+        );
+        let json = serde_json::to_value(&notifications[1]).unwrap();
+        assert_eq!(json["type"], "points_modifies");
+    }
+
+    #[tokio::test]
+    async fn test_arret() {
+        // sans poule ni joueur, aucune notification n'est émise : seul `actif` arrête la boucle
+        let surveillance = Surveillance::new(Duration::from_millis(1));
+        let tics = std::cell::Cell::new(0);
+        let arret = surveillance.surveiller_tant_que(
+            |_| true,
+            || {
+                tics.set(tics.get() + 1);
+                tics.get() < 3
+            },
+        );
+        tokio::time::timeout(Duration::from_secs(5), arret)
+            .await
+            .unwrap();
+        assert_eq!(tics.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_webhook() {
+        let serveur = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/notifications", serveur.local_addr().unwrap());
+        let requete = tokio::spawn(async move {
+            let (mut flux, _) = serveur.accept().await.unwrap();
+            let mut requete = vec![];
+            let mut tampon = [0; 1024];
+            // lit les en-têtes puis le corps annoncé par Content-Length
+            loop {
+                let n = flux.read(&mut tampon).await.unwrap();
+                requete.extend_from_slice(&tampon[..n]);
+                let texte = String::from_utf8_lossy(&requete).to_string();
+                if let Some((entetes, corps)) = texte.split_once("\r\n\r\n") {
+                    let longueur: usize = entetes
+                        .lines()
+                        .find_map(|l| {
+                            l.to_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse().unwrap())
+                        })
+                        .unwrap_or_default();
+                    if corps.len() >= longueur {
+                        break;
+                    }
+                }
+            }
+            flux.write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8(requete).unwrap()
+        });
+
+        let notification = Notification::PointsModifies {
+            licence: "12345".to_string(),
+            nom: "John DOE".to_string(),
+            avant: 1000.0,
+            apres: 1012.0,
+        };
+        Webhook::new(&url).envoyer(&notification).await.unwrap();
+        let requete = requete.await.unwrap();
+        assert!(requete.starts_with("POST /notifications HTTP/1.1"));
+        assert!(requete
+            .to_lowercase()
+            .contains("content-type: application/json"));
+        let corps = requete.split_once("\r\n\r\n").unwrap().1;
+        assert_eq!(
+            serde_json::from_str::<Notification>(corps).unwrap(),
+            notification
+        );
+    }
+}
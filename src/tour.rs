@@ -3,57 +3,266 @@ use serde::Deserializer;
 use serde::Serialize;
 
 use crate::date::Date;
+use crate::feuille::FeuilleDeMatch;
+use crate::{api, recuperer};
 
 /// La rencontre d'une équipe contre une autre
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Rencontre {
-    #[serde(rename = "libelle", deserialize_with = "deserialize_numero_tour")]
     /// le tour de la rencontre
     pub tour: u8,
-    #[serde(rename = "equa")]
     /// l'équipe A (receveur)
     pub a: String,
-    #[serde(rename = "equb")]
     /// l'équipe B (extérieur)
     pub b: String,
-    #[serde(default)]
-    /// le score de l'équipe A
-    pub scorea: String,
-    #[serde(default)]
-    /// le score de l'équipe B
-    pub scoreb: String,
-    #[serde(rename = "dateprevue")]
+    /// le score de l'équipe A, None si la rencontre n'est pas jouée
+    pub scorea: Option<u8>,
+    /// le score de l'équipe B, None si la rencontre n'est pas jouée
+    pub scoreb: Option<u8>,
+    /// l'équipe ayant déclaré forfait
+    pub forfait: Option<Cote>,
     /// la date de la rencontre
     pub date: Date,
+    /// la date à laquelle la rencontre a réellement lieu, si elle diffère de la date prévue
+    pub datereelle: Option<Date>,
+    /// les paramètres permettant de récupérer la feuille de match
+    pub lien: String,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+/// le côté d'une équipe lors d'une rencontre
+pub enum Cote {
+    /// l'équipe A, qui reçoit
+    Domicile,
+    /// l'équipe B, qui se déplace
+    Exterieur,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+/// l'issue d'une rencontre
+pub enum Issue {
+    /// victoire de l'équipe A
+    VictoireA,
+    /// victoire de l'équipe B
+    VictoireB,
+    /// match nul
+    Nul,
+    /// forfait de l'équipe A
+    ForfaitA,
+    /// forfait de l'équipe B
+    ForfaitB,
+    /// rencontre pas encore jouée
+    NonJouee,
+    /// rencontre reportée à une date ultérieure
+    Reportee,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+/// le barème des points attribués au classement de la poule
+pub struct Bareme {
+    /// points attribués pour une victoire
+    pub victoire: u8,
+    /// points attribués pour un match nul
+    pub nul: u8,
+    /// points attribués pour une défaite
+    pub defaite: u8,
+    /// points attribués pour un forfait
+    pub forfait: u8,
+}
+
+impl Default for Bareme {
+    /// le barème du championnat de France par équipes
+    fn default() -> Self {
+        Bareme {
+            victoire: 3,
+            nul: 2,
+            defaite: 1,
+            forfait: 0,
+        }
+    }
+}
+
+impl Rencontre {
+    /// retourne l'issue de la rencontre
+    pub fn issue(&self) -> Issue {
+        match (self.forfait, self.scorea, self.scoreb) {
+            (Some(Cote::Domicile), _, _) => Issue::ForfaitA,
+            (Some(Cote::Exterieur), _, _) => Issue::ForfaitB,
+            (None, Some(a), Some(b)) if a > b => Issue::VictoireA,
+            (None, Some(a), Some(b)) if a < b => Issue::VictoireB,
+            (None, Some(_), Some(_)) => Issue::Nul,
+            _ => match &self.datereelle {
+                Some(d) if *d != self.date => Issue::Reportee,
+                _ => Issue::NonJouee,
+            },
+        }
+    }
+
+    /// indique si la rencontre a un résultat (score ou forfait)
+    pub fn est_jouee(&self) -> bool {
+        !matches!(self.issue(), Issue::NonJouee | Issue::Reportee)
+    }
+
+    /// retourne le côté de l'équipe donnée, None si elle ne joue pas cette rencontre
+    pub fn cote(&self, equipe: &str) -> Option<Cote> {
+        if self.a == equipe {
+            Some(Cote::Domicile)
+        } else if self.b == equipe {
+            Some(Cote::Exterieur)
+        } else {
+            None
+        }
+    }
+
+    /// retourne l'adversaire de l'équipe donnée
+    pub fn adversaire(&self, equipe: &str) -> Option<&str> {
+        match self.cote(equipe)? {
+            Cote::Domicile => Some(&self.b),
+            Cote::Exterieur => Some(&self.a),
+        }
+    }
+
+    /// retourne le score (pour, contre) du point de vue de l'équipe donnée
+    pub fn score(&self, equipe: &str) -> Option<(u8, u8)> {
+        let (a, b) = (self.scorea?, self.scoreb?);
+        match self.cote(equipe)? {
+            Cote::Domicile => Some((a, b)),
+            Cote::Exterieur => Some((b, a)),
+        }
+    }
+
+    /// retourne les points de classement attribués à l'équipe donnée selon le barème
+    /// retourne None si l'équipe ne joue pas la rencontre ou si celle-ci n'a pas de résultat
+    pub fn points(&self, equipe: &str, bareme: &Bareme) -> Option<u8> {
+        let cote = self.cote(equipe)?;
+        let points = match (self.issue(), cote) {
+            (Issue::VictoireA, Cote::Domicile) | (Issue::VictoireB, Cote::Exterieur) => {
+                bareme.victoire
+            }
+            (Issue::VictoireA, Cote::Exterieur) | (Issue::VictoireB, Cote::Domicile) => {
+                bareme.defaite
+            }
+            (Issue::Nul, _) => bareme.nul,
+            (Issue::ForfaitA, Cote::Domicile) | (Issue::ForfaitB, Cote::Exterieur) => {
+                bareme.forfait
+            }
+            (Issue::ForfaitA, Cote::Exterieur) | (Issue::ForfaitB, Cote::Domicile) => {
+                bareme.victoire
+            }
+            (Issue::NonJouee | Issue::Reportee, _) => return None,
+        };
+        Some(points)
+    }
+
+    /// récupère la feuille de match détaillée de la rencontre
+    /// retourne None si la rencontre n'a pas de feuille de match ou en cas d'erreur
+    pub async fn details(&self) -> Option<FeuilleDeMatch> {
+        if self.lien.is_empty() {
+            return None;
+        }
+        let api = api();
+        let request_url = format!("{api}/proxy/xml_chp_renc.php?{}", self.lien);
+        let xml = recuperer(&request_url).await?;
+        FeuilleDeMatch::from_xml(&xml)
+            .map_err(|e| {
+                log::error!("Erreur lors de la désérialisation de la feuille de match : {e}")
+            })
+            .ok()
+    }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 /// un ensemble de rencontres
 pub struct Rencontres {
-    #[serde(rename = "tour")]
     pub rencontres: Vec<Rencontre>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+impl Rencontres {
+    /// créé les rencontres à partir du document XML retourné par l'API
+    pub fn from_xml(xml: &str) -> Result<Rencontres, quick_xml::DeError> {
+        let doc: DocumentRencontres = quick_xml::de::from_str(xml)?;
+        Ok(Rencontres {
+            rencontres: doc.tour.into_iter().map(Rencontre::from).collect(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// un tour d'une phase, regroupant les rencontres d'une même journée
 pub struct Tour {
     pub numero: u8,
     pub date: Date,
     pub rencontres: Rencontres,
 }
 
+impl Tour {
+    /// indique si toutes les rencontres du tour ont un résultat
+    pub fn est_termine(&self) -> bool {
+        self.rencontres.rencontres.iter().all(Rencontre::est_jouee)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+// représente le document XML retourné par l'API
+struct DocumentRencontres {
+    #[serde(default)]
+    tour: Vec<RencontreApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// représente une rencontre telle que retournée par l'API
+struct RencontreApi {
+    #[serde(deserialize_with = "deserialize_numero_tour")]
+    libelle: u8,
+    equa: String,
+    equb: String,
+    #[serde(default)]
+    scorea: String,
+    #[serde(default)]
+    scoreb: String,
+    dateprevue: Date,
+    #[serde(default)]
+    datereelle: String,
+    #[serde(default)]
+    lien: String,
+}
+
+impl From<RencontreApi> for Rencontre {
+    fn from(r: RencontreApi) -> Self {
+        // un forfait est indiqué par un "F" à la place du score de l'équipe forfait
+        let forfait = if est_forfait(&r.scorea) {
+            Some(Cote::Domicile)
+        } else if est_forfait(&r.scoreb) {
+            Some(Cote::Exterieur)
+        } else {
+            None
+        };
+        Rencontre {
+            tour: r.libelle,
+            a: r.equa,
+            b: r.equb,
+            scorea: r.scorea.trim().parse().ok(),
+            scoreb: r.scoreb.trim().parse().ok(),
+            forfait,
+            date: r.dateprevue,
+            datereelle: r.datereelle.parse().ok(),
+            lien: r.lien,
+        }
+    }
+}
+
+fn est_forfait(score: &str) -> bool {
+    score.trim().to_uppercase().starts_with('F')
+}
+
 fn deserialize_numero_tour<'de, D>(deserializer: D) -> Result<u8, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s: &str = Deserialize::deserialize(deserializer)?;
-    let numero = s
-        .split('°')
-        .last()
-        .expect("Numéro de tour manquant")
-        .split(' ')
-        .next()
-        .unwrap()
-        .parse()
-        .unwrap();
-    Ok(numero)
+    let s: String = Deserialize::deserialize(deserializer)?;
+    // le numéro suit le "n°" du libellé (ex : "Poule 3 - tour n°1 du 28/09/2024")
+    s.rsplit_once('°')
+        .and_then(|(_, suite)| suite.split(' ').next())
+        .and_then(|numero| numero.parse().ok())
+        .ok_or_else(|| serde::de::Error::custom(format!("Numéro de tour invalide : {s}")))
 }
@@ -0,0 +1,477 @@
+use serde::{Deserialize, Serialize};
+
+use crate::club::Club;
+use crate::date::Date;
+use crate::joueur::Joueur;
+use crate::journee::Journee;
+use crate::partie::Partie;
+use crate::{api, recuperer};
+
+/// rayon moyen de la Terre en kilomètres
+const RAYON_TERRE: f64 = 6371.0;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+/// le niveau d'homologation d'un tournoi
+pub enum Homologation {
+    /// tournoi international
+    Internationale,
+    /// tournoi national
+    Nationale,
+    /// tournoi régional
+    Regionale,
+    /// tournoi départemental
+    Departementale,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// un tableau d'un tournoi
+pub struct Tableau {
+    /// identifiant du tableau
+    pub id: String,
+    /// libellé du tableau (ex : "Tableau B - 500 à 999 points")
+    pub libelle: String,
+    /// date du tableau, si elle est connue
+    pub date: Option<Date>,
+    /// points minimum pour s'inscrire
+    pub points_min: Option<u16>,
+    /// points maximum pour s'inscrire
+    pub points_max: Option<u16>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// un tournoi homologué
+pub struct Tournoi {
+    /// identifiant du tournoi
+    pub id: String,
+    /// nom du tournoi
+    pub nom: String,
+    /// niveau d'homologation, s'il est connu
+    pub homologation: Option<Homologation>,
+    /// identifiant de l'organisme ayant homologué le tournoi
+    pub organisme: String,
+    /// premier jour du tournoi
+    pub debut: Date,
+    /// dernier jour du tournoi
+    pub fin: Date,
+    /// adresse de la salle
+    pub adresse: String,
+    /// code postal de la salle
+    pub code_postal: String,
+    /// ville de la salle
+    pub ville: String,
+    /// latitude de la salle
+    pub latitude: Option<f64>,
+    /// longitude de la salle
+    pub longitude: Option<f64>,
+    /// lien vers le règlement du tournoi
+    pub reglement: Option<String>,
+    /// les tableaux du tournoi
+    pub tableaux: Vec<Tableau>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// le résultat d'un joueur dans un tableau d'un tournoi
+pub struct ResultatTournoi {
+    /// identifiant du tableau
+    pub tableau: String,
+    /// place obtenue dans le tableau
+    pub rang: u16,
+    /// nom et prénom du joueur
+    pub nom: String,
+    /// numéro de licence du joueur, s'il est connu
+    pub licence: Option<String>,
+    /// nom du club du joueur
+    pub club: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// une recherche de tournois
+pub struct Recherche {
+    /// identifiant de l'organisme ayant homologué les tournois
+    pub organisme: Option<String>,
+    /// premier jour de la période recherchée
+    pub debut: Option<Date>,
+    /// dernier jour de la période recherchée
+    pub fin: Option<Date>,
+    /// position (latitude, longitude) et distance maximale en kilomètres
+    pub autour: Option<(f64, f64, f64)>,
+}
+
+impl Tableau {
+    /// indique si un joueur ayant les points donnés peut s'inscrire au tableau
+    pub fn accessible(&self, points: f32) -> bool {
+        self.points_min.is_none_or(|min| points >= f32::from(min))
+            && self.points_max.is_none_or(|max| points <= f32::from(max))
+    }
+}
+
+impl Tournoi {
+    /// créé les tournois à partir du document JSON retourné par l'API
+    pub fn from_json(json: &str) -> Result<Vec<Tournoi>, serde_json::Error> {
+        let tournois: Vec<TournoiApi> = serde_json::from_str(json)?;
+        Ok(tournois.into_iter().map(Tournoi::from).collect())
+    }
+
+    /// retourne la distance en kilomètres entre la salle du tournoi et la position donnée
+    pub fn distance(&self, latitude: f64, longitude: f64) -> Option<f64> {
+        Some(distance(
+            self.latitude?,
+            self.longitude?,
+            latitude,
+            longitude,
+        ))
+    }
+
+    /// retourne la distance en kilomètres entre la salle du tournoi et celle du club
+    pub fn distance_club(&self, club: &Club) -> Option<f64> {
+        let (latitude, longitude) = position(club)?;
+        self.distance(latitude, longitude)
+    }
+
+    /// retourne les tableaux auxquels le joueur peut s'inscrire selon ses points actuels
+    pub fn tableaux_accessibles(&self, joueur: &Joueur) -> Vec<&Tableau> {
+        self.tableaux
+            .iter()
+            .filter(|t| t.accessible(joueur.point))
+            .collect()
+    }
+
+    /// retourne les journées de l'historique d'un joueur disputées lors du tournoi
+    ///
+    /// Une journée correspond au tournoi si elle a lieu pendant celui-ci et que son épreuve,
+    /// lorsqu'elle est connue, n'est pas une épreuve fédérale (championnat, critérium...).
+    pub fn journees<'a>(&self, partie: &'a Partie) -> Vec<&'a Journee> {
+        partie
+            .journees
+            .iter()
+            .filter(|j| j.date >= self.debut && j.date <= self.fin)
+            .filter(|j| {
+                j.epreuve
+                    .as_deref()
+                    .is_none_or(|e| !e.to_uppercase().starts_with("FED_"))
+            })
+            .collect()
+    }
+
+    /// retourne les résultats du tournoi, tableau par tableau, une fois celui-ci joué
+    pub async fn api_resultats(&self) -> Vec<ResultatTournoi> {
+        let api = api();
+        let request_url = format!("{api}/tournois/{}/resultats", self.id);
+        let Some(json) = recuperer(&request_url).await else {
+            return vec![];
+        };
+        serde_json::from_str::<Vec<ResultatApi>>(&json)
+            .map(|r| r.into_iter().map(ResultatTournoi::from).collect())
+            .unwrap_or_else(|e| {
+                log::error!("Erreur lors de la désérialisation des résultats du tournoi : {e}");
+                vec![]
+            })
+    }
+}
+
+impl Recherche {
+    /// créé une recherche de tous les tournois
+    pub fn new() -> Self {
+        Recherche::default()
+    }
+
+    /// restreint la recherche aux tournois homologués par l'organisme donné
+    pub fn organisme(mut self, organisme: &str) -> Self {
+        self.organisme = Some(organisme.to_string());
+        self
+    }
+
+    /// restreint la recherche aux tournois se déroulant, au moins en partie, entre les deux dates
+    pub fn entre(mut self, debut: Date, fin: Date) -> Self {
+        self.debut = Some(debut);
+        self.fin = Some(fin);
+        self
+    }
+
+    /// restreint la recherche aux tournois situés à moins de `rayon` kilomètres du club
+    /// sans effet si la position du club est inconnue
+    pub fn autour(mut self, club: &Club, rayon: f64) -> Self {
+        self.autour = position(club).map(|(latitude, longitude)| (latitude, longitude, rayon));
+        self
+    }
+
+    /// indique si le tournoi correspond à la recherche
+    pub fn correspond(&self, tournoi: &Tournoi) -> bool {
+        self.organisme
+            .as_ref()
+            .is_none_or(|o| *o == tournoi.organisme)
+            && self.debut.as_ref().is_none_or(|d| tournoi.fin >= *d)
+            && self.fin.as_ref().is_none_or(|f| tournoi.debut <= *f)
+            && self.autour.is_none_or(|(latitude, longitude, rayon)| {
+                tournoi
+                    .distance(latitude, longitude)
+                    .is_some_and(|d| d <= rayon)
+            })
+    }
+
+    // l'URL de la recherche, dont les paramètres sont encodés
+    fn url(&self, api: &str) -> Option<String> {
+        let mut parametres = vec![];
+        if let Some(organisme) = &self.organisme {
+            parametres.push(("organisme", organisme.clone()));
+        }
+        if let Some(debut) = &self.debut {
+            parametres.push(("debut", debut.to_string()));
+        }
+        if let Some(fin) = &self.fin {
+            parametres.push(("fin", fin.to_string()));
+        }
+        reqwest::Url::parse_with_params(&format!("{api}/tournois"), parametres)
+            .map_err(|e| log::error!("Base d'API invalide ({api}) : {e}"))
+            .ok()
+            .map(String::from)
+    }
+
+    /// retourne les tournois correspondant à la recherche, par date de début
+    pub async fn tournois(&self) -> Vec<Tournoi> {
+        let Some(request_url) = self.url(&api()) else {
+            return vec![];
+        };
+        let Some(json) = recuperer(&request_url).await else {
+            return vec![];
+        };
+        let mut tournois: Vec<Tournoi> = Tournoi::from_json(&json)
+            .unwrap_or_else(|e| {
+                log::error!("Erreur lors de la désérialisation des tournois : {e}");
+                vec![]
+            })
+            .into_iter()
+            .filter(|t| self.correspond(t))
+            .collect();
+        tournois.sort_by(|a, b| a.debut.cmp(&b.debut));
+        tournois
+    }
+}
+
+impl Joueur {
+    /// retourne les journées du joueur disputées lors du tournoi, à partir de ses parties
+    pub async fn api_journees_tournoi(&self, tournoi: &Tournoi) -> Vec<Journee> {
+        match self.api_parties().await {
+            Some(partie) => tournoi.journees(&partie).into_iter().cloned().collect(),
+            None => vec![],
+        }
+    }
+}
+
+/// distance en kilomètres entre deux positions GPS, par la formule de haversine
+pub fn distance(latitude1: f64, longitude1: f64, latitude2: f64, longitude2: f64) -> f64 {
+    let (phi1, phi2) = (latitude1.to_radians(), latitude2.to_radians());
+    let dphi = (latitude2 - latitude1).to_radians();
+    let dlambda = (longitude2 - longitude1).to_radians();
+    let a = (dphi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (dlambda / 2.0).sin().powi(2);
+    2.0 * RAYON_TERRE * a.sqrt().asin()
+}
+
+// la position GPS de la salle du club, si elle est renseignée
+fn position(club: &Club) -> Option<(f64, f64)> {
+    Some((
+        club.latitude.trim().parse().ok()?,
+        club.longitude.trim().parse().ok()?,
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+// un tournoi tel que retourné par l'API
+struct TournoiApi {
+    id: String,
+    nom: String,
+    #[serde(default, rename = "type")]
+    type_tournoi: String,
+    #[serde(default)]
+    organisme: String,
+    debut: Date,
+    fin: Date,
+    #[serde(default)]
+    adresse: String,
+    #[serde(default)]
+    code_postal: String,
+    #[serde(default)]
+    ville: String,
+    #[serde(default)]
+    latitude: Option<String>,
+    #[serde(default)]
+    longitude: Option<String>,
+    #[serde(default)]
+    reglement: Option<String>,
+    #[serde(default)]
+    tableaux: Vec<TableauApi>,
+}
+
+#[derive(Debug, Deserialize)]
+// un tableau tel que retourné par l'API
+struct TableauApi {
+    id: String,
+    libelle: String,
+    #[serde(default)]
+    date: Option<Date>,
+    #[serde(default)]
+    points_min: Option<u16>,
+    #[serde(default)]
+    points_max: Option<u16>,
+}
+
+#[derive(Debug, Deserialize)]
+// un résultat tel que retourné par l'API
+struct ResultatApi {
+    tableau: String,
+    rang: u16,
+    nom: String,
+    #[serde(default)]
+    licence: Option<String>,
+    #[serde(default)]
+    club: String,
+}
+
+impl From<TournoiApi> for Tournoi {
+    fn from(t: TournoiApi) -> Self {
+        let coordonnee = |c: Option<String>| c.and_then(|c| c.trim().parse().ok());
+        Tournoi {
+            id: t.id,
+            nom: t.nom,
+            homologation: match t.type_tournoi.trim().to_uppercase().as_str() {
+                "I" => Some(Homologation::Internationale),
+                "N" => Some(Homologation::Nationale),
+                "R" => Some(Homologation::Regionale),
+                "D" => Some(Homologation::Departementale),
+                _ => None,
+            },
+            organisme: t.organisme,
+            debut: t.debut,
+            fin: t.fin,
+            adresse: t.adresse,
+            code_postal: t.code_postal,
+            ville: t.ville,
+            latitude: coordonnee(t.latitude),
+            longitude: coordonnee(t.longitude),
+            reglement: t.reglement.filter(|r| !r.is_empty()),
+            tableaux: t
+                .tableaux
+                .into_iter()
+                .map(|t| Tableau {
+                    id: t.id,
+                    libelle: t.libelle,
+                    date: t.date,
+                    points_min: t.points_min,
+                    points_max: t.points_max,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<ResultatApi> for ResultatTournoi {
+    fn from(r: ResultatApi) -> Self {
+        ResultatTournoi {
+            tableau: r.tableau,
+            rang: r.rang,
+            nom: r.nom,
+            licence: r.licence.filter(|l| !l.is_empty()),
+            club: r.club,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOURNOIS: &str = r#"[
+        {"id": "1", "nom": "Tournoi de Sète", "type": "R", "organisme": "1008",
+         "debut": "12/04/2025", "fin": "13/04/2025", "adresse": "Gymnase", "code_postal": "34200",
+         "ville": "SETE", "latitude": "43.4028", "longitude": "3.6928",
+         "tableaux": [
+            {"id": "A", "libelle": "Tableau A", "date": "12/04/2025", "points_max": 899},
+            {"id": "B", "libelle": "Tableau B", "date": "12/04/2025", "points_min": 900, "points_max": 1499},
+            {"id": "C", "libelle": "Toutes séries", "date": "13/04/2025"}
+         ]},
+        {"id": "2", "nom": "Tournoi de Lille", "type": "N", "organisme": "1001",
+         "debut": "19/04/2025", "fin": "19/04/2025", "latitude": "50.6292", "longitude": "3.0573"}
+    ]"#;
+
+    fn club() -> Club {
+        serde_json::from_value(serde_json::json!({
+            "numero": "11340010",
+            "nom": "MONTPELLIER TT",
+            "nomsalle": "",
+            "adressesalle1": "",
+            "adressesalle2": "",
+            "adressesalle3": "",
+            "codepsalle": "34000",
+            "villesalle": "MONTPELLIER",
+            "latitude": "43.6109",
+            "longitude": "3.8772"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_recherche() {
+        let tournois = Tournoi::from_json(TOURNOIS).unwrap();
+        assert_eq!(tournois[0].homologation, Some(Homologation::Regionale));
+        assert_eq!(tournois[1].tableaux, vec![]);
+
+        let sete = tournois[0].distance_club(&club()).unwrap();
+        assert!((25.0..30.0).contains(&sete), "{sete}");
+        let lille = tournois[1].distance_club(&club()).unwrap();
+        assert!((750.0..800.0).contains(&lille), "{lille}");
+
+        let recherche = Recherche::new().autour(&club(), 100.0);
+        assert!(recherche.correspond(&tournois[0]));
+        assert!(!recherche.correspond(&tournois[1]));
+        let recherche =
+            Recherche::new().entre("2025-04-13".parse().unwrap(), "2025-04-30".parse().unwrap());
+        assert!(recherche.correspond(&tournois[0]));
+        assert!(recherche.correspond(&tournois[1]));
+        assert!(!Recherche::new().organisme("1001").correspond(&tournois[0]));
+
+        let url = Recherche::new()
+            .organisme("1001&fin=2099-01-01")
+            .url("http://localhost/v1")
+            .unwrap();
+        assert_eq!(
+            url,
+            "http://localhost/v1/tournois?organisme=1001%26fin%3D2099-01-01"
+        );
+    }
+
+    #[test]
+    fn test_tableaux_journees() {
+        let tournoi = &Tournoi::from_json(TOURNOIS).unwrap()[0];
+        let joueur = Joueur {
+            licence: "3412345".to_string(),
+            nom: "DOE".to_string(),
+            prenom: "John".to_string(),
+            points_init: 890.0,
+            point: 890.0,
+            r#_virtual: 912.5,
+            categorie: None,
+        };
+        let tableaux: Vec<&str> = tournoi
+            .tableaux_accessibles(&joueur)
+            .iter()
+            .map(|t| t.id.as_str())
+            .collect();
+        // les points virtuels ne comptent pas pour l'inscription
+        assert_eq!(tableaux, vec!["A", "C"]);
+
+        let partie: Partie = serde_json::from_value(serde_json::json!({
+            "journees": [
+                {"date": "12/04/2025", "epreuve": "Tournoi de Sète", "matchs": [
+                    {"nom": "ROE Jane", "coeff": 0.5, "ex": 3.0}]},
+                {"date": "13/04/2025", "epreuve": "FED_Championnat de France par Equipes Masculin",
+                 "matchs": [{"nom": "POE Jim", "coeff": 1.0, "ex": -5.0}]},
+                {"date": "05/04/2025", "matchs": [{"nom": "LOE Joe", "coeff": 0.5, "ex": 2.0}]}
+            ]
+        }))
+        .unwrap();
+        let journees = tournoi.journees(&partie);
+        assert_eq!(journees.len(), 1);
+        assert_eq!(journees[0].epreuve.as_deref(), Some("Tournoi de Sète"));
+        assert_eq!(journees[0].ex(), 3.0);
+    }
+}
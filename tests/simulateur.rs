@@ -0,0 +1,229 @@
+//! Interroge le simulateur à travers la bibliothèque
+#![cfg(feature = "mock")]
+
+use libfftt::club::Club;
+use libfftt::equipe::Equipe;
+use libfftt::generateur::{self, Parametres};
+use libfftt::joueur::Joueur;
+use libfftt::optimisation;
+use libfftt::organisme::BilanClub;
+use libfftt::poule::{Classement, Poule};
+use libfftt::prediction::Format;
+use libfftt::simulateur::Simulateur;
+use libfftt::simulateur::{Defaillance, Donnees, EquipeSimulee, JoueurSimule, PouleSimulee};
+use libfftt::tour::{Cote, Rencontre};
+use libfftt::ErreurApi;
+
+fn donnees() -> Donnees {
+    let club: Club = serde_json::from_value(serde_json::json!({
+        "numero": "11340010",
+        "nom": "CLUB TEST",
+        "nomsalle": "Gymnase",
+        "adressesalle1": "1 rue du Sport",
+        "adressesalle2": "",
+        "adressesalle3": "",
+        "codepsalle": "34000",
+        "villesalle": "MONTPELLIER",
+        "latitude": "43.61",
+        "longitude": "3.87"
+    }))
+    .unwrap();
+    let poule = Poule {
+        division: "5678".to_string(),
+        numero: "1234".to_string(),
+    };
+    let joueur: Joueur = serde_json::from_value(serde_json::json!({
+        "licence": "3412345",
+        "nom": "DOE",
+        "prenom": "John",
+        "points_init": 1000.0,
+        "point": 1010.0,
+        "virtuel": 1024.5,
+        "categorie": "S"
+    }))
+    .unwrap();
+    let journees = serde_json::from_value(serde_json::json!([
+        {"date": "2024-09-28", "matchs": [
+            {"nom": "DHUILE Jean", "coeff": 1.0, "ex": 6.0},
+            {"nom": "MARTIN Paul", "coeff": 1.0, "ex": -2.5}
+        ]}
+    ]))
+    .unwrap();
+    let rencontres: Vec<Rencontre> = serde_json::from_value(serde_json::json!([
+        {"tour": 1, "a": "CLUB TEST 1", "b": "AUTRE 1", "scorea": 9, "scoreb": 5,
+         "forfait": null, "date": "2024-09-28", "datereelle": null, "lien": ""},
+        {"tour": 2, "a": "AUTRE 1", "b": "CLUB TEST 1", "scorea": null, "scoreb": null,
+         "forfait": null, "date": "2024-10-12", "datereelle": null, "lien": ""}
+    ]))
+    .unwrap();
+    let classements: Vec<Classement> = serde_json::from_value(serde_json::json!([
+        {"numero_poule": 1, "classement": 1, "equipe": "CLUB TEST 1", "joues": 1, "points": 3,
+         "victoires": 1, "defaites": 0, "nuls": 0, "forfaits": 0,
+         "parties_gagnees": 9, "parties_perdues": 5, "numero": "11340010"},
+        {"numero_poule": 1, "classement": 2, "equipe": "AUTRE 1", "joues": 1, "points": 1,
+         "victoires": 0, "defaites": 1, "nuls": 0, "forfaits": 0,
+         "parties_gagnees": 5, "parties_perdues": 9, "numero": "11340020"}
+    ]))
+    .unwrap();
+    Donnees {
+        clubs: vec![club],
+        joueurs: vec![JoueurSimule {
+            joueur,
+            club: "11340010".to_string(),
+            journees,
+        }],
+        equipes: vec![EquipeSimulee {
+            equipe: Equipe {
+                id: "42".to_string(),
+                nom: "CLUB TEST 1".to_string(),
+                _epreuve: "FED_Championnat par Equipes".to_string(),
+                _division: "Régionale 1".to_string(),
+                poule: poule.clone(),
+            },
+            club: "11340010".to_string(),
+        }],
+        poules: vec![PouleSimulee {
+            poule,
+            rencontres,
+            classements,
+        }],
+    }
+}
+
+#[tokio::test]
+async fn test_simulateur() {
+    let simulateur = Simulateur::new(donnees());
+    let adresse = simulateur.lancer("127.0.0.1:0").await.unwrap();
+    libfftt::avec_api(&format!("http://{adresse}/"), parcourir(simulateur)).await;
+}
+
+// interroge chaque endpoint du simulateur
+async fn parcourir(simulateur: Simulateur) {
+    let donnees = donnees();
+    let club = Club::new("11340010").await;
+    assert_eq!(club, donnees.clubs[0]);
+    let joueurs = club.api_joueurs().await;
+    assert_eq!(joueurs, vec![donnees.joueurs[0].joueur.clone()]);
+
+    let equipes = club.api_equipes().await;
+    assert_eq!(equipes.len(), 1);
+    let bilan = BilanClub::collecter(club.clone()).await.unwrap();
+    assert_eq!((bilan.joueurs, bilan.equipes), (1, 1));
+    assert_eq!(equipes[0].poule, donnees.poules[0].poule);
+    let poule = &equipes[0].poule;
+    assert_eq!(poule.classement().await, donnees.poules[0].classements);
+    let tours = poule.get_tours().await;
+    assert_eq!(tours.len(), 2);
+    assert_eq!(tours[0].1.rencontres.rencontres[0].scorea, Some(9));
+
+    // sans feuille de match, la composition de l'adversaire et donc sa force sont inconnues
+    let date = "2024-10-12".parse().unwrap();
+    let du_jour = optimisation::equipes_du_jour(&equipes, &date, &Format::default())
+        .await
+        .unwrap();
+    assert_eq!(du_jour.len(), 1);
+    assert_eq!(du_jour[0].adversaire.as_deref(), Some("AUTRE 1"));
+    assert_eq!(du_jour[0].composition_adverse, None);
+
+    #[cfg(feature = "store")]
+    {
+        use libfftt::store::{ErreurSynchronisation, Store};
+
+        let mut store = Store::en_memoire().unwrap();
+        let synchronisation = store.synchroniser_club("11340010", true).await.unwrap();
+        assert_eq!(synchronisation.joueurs, 1);
+        assert_eq!(synchronisation.rencontres, 2);
+        assert_eq!(synchronisation.classements, 2);
+        // une nouvelle synchronisation interroge les poules mais n'enregistre aucun changement
+        let synchronisation = store.synchroniser_club("11340010", false).await.unwrap();
+        assert_eq!(synchronisation.rencontres + synchronisation.classements, 0);
+        // une erreur de l'API est retournée au lieu de paniquer
+        assert!(matches!(
+            store.synchroniser_club("0000000", false).await,
+            Err(ErreurSynchronisation::Api(ErreurApi::Introuvable))
+        ));
+    }
+
+    let joueur = Joueur::new("3412345").await.unwrap();
+    let parties = joueur.api_parties().await.unwrap();
+    assert_eq!(parties.journees.len(), 1);
+    assert_eq!(parties.journees[0].ex(), 3.5);
+    assert!(Joueur::new("0000000").await.is_err());
+    assert!(matches!(
+        Joueur::try_new("0000000").await,
+        Err(ErreurApi::Introuvable)
+    ));
+    assert!(matches!(
+        Club::try_new("0000000").await,
+        Err(ErreurApi::Introuvable)
+    ));
+
+    // un club adverse introuvable laisse seulement le lieu de la rencontre inconnu
+    let calendrier = club.try_calendrier().await.unwrap();
+    assert_eq!(calendrier.len(), 2);
+    assert!(calendrier
+        .iter()
+        .all(|e| e.lieu.is_some() == (e.cote == Cote::Domicile)));
+    // le nom de l'adversaire est rapproché du classement malgré la casse et les espaces
+    let mut avec_adversaire = donnees.clone();
+    let mut adversaire = avec_adversaire.clubs[0].clone();
+    adversaire.numero = "11340020".to_string();
+    adversaire.nom = "AUTRE".to_string();
+    avec_adversaire.clubs.push(adversaire.clone());
+    avec_adversaire.poules[0].classements[1].equipe = "Autre  1".to_string();
+    simulateur.remplacer(avec_adversaire);
+    let calendrier = club.try_calendrier().await.unwrap();
+    let exterieur = calendrier
+        .iter()
+        .find(|e| e.cote == Cote::Exterieur)
+        .unwrap();
+    assert_eq!(exterieur.lieu, Some(adversaire));
+    simulateur.remplacer(donnees.clone());
+
+    // une défaillance sur un endpoint n'affecte pas les autres
+    simulateur.injecter(
+        "/joueur/",
+        Defaillance {
+            statut: Some(500),
+            ..Defaillance::default()
+        },
+    );
+    assert!(Joueur::new("3412345").await.is_err());
+    assert_eq!(Club::new("11340010").await, donnees.clubs[0]);
+    simulateur.retablir();
+
+    // les variantes faillibles retournent l'erreur au lieu de paniquer
+    simulateur.injecter(
+        "/proxy/xml_result_equ.php",
+        Defaillance {
+            malformee: true,
+            ..Defaillance::default()
+        },
+    );
+    assert!(matches!(
+        poule.try_classement().await,
+        Err(ErreurApi::Document(_))
+    ));
+    assert!(club.try_calendrier().await.is_err());
+    simulateur.retablir();
+
+    // un jeu de données généré est servi tel quel
+    let donnees = generateur::generer(&Parametres::default());
+    simulateur.remplacer(donnees.clone());
+    let club = Club::new(&donnees.clubs[0].numero).await;
+    assert_eq!(club, donnees.clubs[0]);
+    let equipes = club.api_equipes().await;
+    assert_eq!(equipes.len(), 2);
+    let poule = donnees
+        .poules
+        .iter()
+        .find(|p| p.poule == equipes[0].poule)
+        .unwrap();
+    assert_eq!(equipes[0].poule.classement().await, poule.classements);
+    let joueur = Joueur::new(&donnees.joueurs[0].joueur.licence)
+        .await
+        .unwrap();
+    assert_eq!(joueur, donnees.joueurs[0].joueur);
+    let parties = joueur.api_parties().await.unwrap();
+    assert_eq!(parties.journees.len(), donnees.joueurs[0].journees.len());
+}